@@ -0,0 +1,24 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::rngs::OsRng;
+use taiga_halo2::merkle_tree::{compute_anchor, compute_anchor_sequential, Node};
+
+fn bench_merkle_anchor(name: &str, c: &mut Criterion) {
+    let mut rng = OsRng;
+    let leaves: Vec<Node> = (0..512).map(|_| Node::rand(&mut rng)).collect();
+
+    // Sequential bench
+    let sequential_name = name.to_string() + "-sequential";
+    c.bench_function(&sequential_name, |b| {
+        b.iter(|| compute_anchor_sequential(&leaves))
+    });
+
+    // Parallel bench
+    let parallel_name = name.to_string() + "-parallel";
+    c.bench_function(&parallel_name, |b| b.iter(|| compute_anchor(&leaves)));
+}
+fn criterion_benchmark(c: &mut Criterion) {
+    bench_merkle_anchor("merkle-anchor", c);
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -11,11 +11,14 @@ use group::ff::PrimeField;
 use halo2_gadgets::utilities::bool_check;
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Value},
+    dev::{CircuitCost, MockProver},
     plonk::{
-        Advice, Column, ConstraintSystem, Constraints, Error, Instance, Selector, VirtualCells,
+        Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Instance, Selector,
+        VirtualCells,
     },
     poly::Rotation,
 };
+use pasta_curves::{pallas, vesta};
 use std::{convert::TryInto, marker::PhantomData};
 
 pub fn vp_commitment_gadget<F: PrimeField>(
@@ -74,6 +77,35 @@ const IV: [u32; 8] = [
     0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
 ];
 
+// The initial state h[0..8] = IV[0..8] ^ P[0..8], where P is the 32-byte parameter block of RFC 7693:
+// digest length, key length, fanout = 1 and depth = 1 in the first word, the zero leaf length,
+// node offset, node depth and inner length in the next three words, then the salt and the
+// personalization in the last four words.
+pub fn blake2s_param_block(
+    digest_len: u8,
+    key_len: u8,
+    salt: &[u8; 8],
+    personal: &[u8; 8],
+) -> [u32; 8] {
+    assert!((1..=32).contains(&digest_len));
+    assert!(key_len <= 32);
+    let param_block = [
+        0x01010000 ^ ((key_len as u32) << 8) ^ (digest_len as u32),
+        0,
+        0,
+        0,
+        LittleEndian::read_u32(&salt[0..4]),
+        LittleEndian::read_u32(&salt[4..8]),
+        LittleEndian::read_u32(&personal[0..4]),
+        LittleEndian::read_u32(&personal[4..8]),
+    ];
+    let mut h = IV;
+    h.iter_mut()
+        .zip(param_block.iter())
+        .for_each(|(h, p)| *h ^= p);
+    h
+}
+
 // The SIGMA constant in Blake2s is a 10x16 array that defines the message permutations in the algorithm. Each of the 10 rows corresponds to a round of the hashing process, and each of the 16 elements in the row determines the message block order.
 const SIGMA: [[usize; 16]; 10] = [
     [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
@@ -96,14 +128,131 @@ const R4: usize = 7;
 
 const ROUNDS: usize = 10;
 
+// The native Blake2s with the parameterization of the chip: an unsalted parameter block, the key
+// as a first zero-padded block and a digest of digest_len bytes. It's the reference the witnessed
+// digests are checked against.
+pub fn blake2s_native(message: &[u8], key: Option<&[u8]>, digest_len: usize) -> Vec<u8> {
+    blake2s_native_with_personal(message, key, digest_len, &[0; 8])
+}
+
+// Like blake2s_native, with the personalization of the parameter block
+pub fn blake2s_native_with_personal(
+    message: &[u8],
+    key: Option<&[u8]>,
+    digest_len: usize,
+    personal: &[u8; 8],
+) -> Vec<u8> {
+    let key = key.unwrap_or(&[]);
+    let mut h = blake2s_param_block(digest_len as u8, key.len() as u8, &[0; 8], personal);
+
+    let mut data = Vec::with_capacity(64 + message.len());
+    if !key.is_empty() {
+        data.extend_from_slice(key);
+        data.resize(64, 0);
+    }
+    data.extend_from_slice(message);
+    let data_len = data.len();
+    let block_num = std::cmp::max(1, (data_len + 63) / 64);
+    data.resize(block_num * 64, 0);
+
+    for (i, block) in data.chunks(64).enumerate() {
+        let mut m = [0u32; 16];
+        LittleEndian::read_u32_into(block, &mut m);
+        let is_final = i == block_num - 1;
+        let t = if is_final {
+            data_len as u64
+        } else {
+            (i as u64 + 1) * 64
+        };
+        blake2s_native_compress(&mut h, &m, t, is_final);
+    }
+
+    h.iter()
+        .flat_map(|word| word.to_le_bytes())
+        .take(digest_len)
+        .collect()
+}
+
+fn blake2s_native_compress(h: &mut [u32; 8], m: &[u32; 16], t: u64, f: bool) {
+    let mut v = [0u32; 16];
+    v[..8].copy_from_slice(h);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= t as u32;
+    v[13] ^= (t >> 32) as u32;
+    if f {
+        v[14] = !v[14];
+    }
+
+    for s in SIGMA.iter() {
+        blake2s_native_g(&mut v, (0, 4, 8, 12), m[s[0]], m[s[1]]);
+        blake2s_native_g(&mut v, (1, 5, 9, 13), m[s[2]], m[s[3]]);
+        blake2s_native_g(&mut v, (2, 6, 10, 14), m[s[4]], m[s[5]]);
+        blake2s_native_g(&mut v, (3, 7, 11, 15), m[s[6]], m[s[7]]);
+        blake2s_native_g(&mut v, (0, 5, 10, 15), m[s[8]], m[s[9]]);
+        blake2s_native_g(&mut v, (1, 6, 11, 12), m[s[10]], m[s[11]]);
+        blake2s_native_g(&mut v, (2, 7, 8, 13), m[s[12]], m[s[13]]);
+        blake2s_native_g(&mut v, (3, 4, 9, 14), m[s[14]], m[s[15]]);
+    }
+
+    for (i, h) in h.iter_mut().enumerate() {
+        *h ^= v[i] ^ v[i + 8];
+    }
+}
+
+fn blake2s_native_g(v: &mut [u32; 16], (a, b, c, d): (usize, usize, usize, usize), x: u32, y: u32) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(R1 as u32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(R2 as u32);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(R3 as u32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(R4 as u32);
+}
+
+// The area of a circuit in the halo2 cost model, to compare the hash chips. The rows are the
+// smallest power of two the circuit fits in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitArea {
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub selectors: usize,
+    pub lookups: usize,
+    pub k: u32,
+    pub proof_size: usize,
+}
+
+// Measure the area of a circuit without instances, or None if it doesn't fit in 2^max_k rows
+pub fn circuit_area<C: Circuit<pallas::Base>>(circuit: &C, max_k: u32) -> Option<CircuitArea> {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    let k = (1..=max_k).find(|k| MockProver::run(*k, circuit, vec![]).is_ok())?;
+    let proof_size = CircuitCost::<vesta::Point, C>::measure(k as usize, circuit)
+        .proof_size(1)
+        .into();
+    Some(CircuitArea {
+        advice_columns: cs.num_advice_columns(),
+        fixed_columns: cs.num_fixed_columns(),
+        selectors: cs.num_selectors(),
+        lookups: cs.lookups().len(),
+        k,
+        proof_size,
+    })
+}
+
 // ---------------
 
 #[derive(Clone, Debug)]
 pub struct Blake2sChip<F: PrimeField> {
     config: Blake2sConfig<F>,
+    // The digest length in bytes, which is encoded in the parameter block
+    digest_len: usize,
     _marker: PhantomData<F>,
 }
 
+// The state words are not pinned to dedicated columns: every decomposition, xor and
+// addition is laid out in its own region over the shared advice columns, and the
+// floor planner decides how the regions are packed.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Blake2sConfig<F: PrimeField> {
     pub advices: [Column<Advice>; 10],
@@ -356,12 +505,26 @@ impl<F: PrimeField> Blake2sConfig<F> {
 
 impl<F: PrimeField> Blake2sChip<F> {
     pub fn construct(config: Blake2sConfig<F>) -> Self {
+        Self::construct_with_digest_len(config, 32)
+    }
+
+    // The digest is returned as words, so its length must be a multiple of 4 bytes
+    pub fn construct_with_digest_len(config: Blake2sConfig<F>, digest_len: usize) -> Self {
+        assert!((4..=32).contains(&digest_len) && digest_len % 4 == 0);
         Self {
             config,
+            digest_len,
             _marker: PhantomData,
         }
     }
 
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 10],
+    ) -> Blake2sConfig<F> {
+        Blake2sConfig::configure(meta, advices)
+    }
+
     pub fn process(
         &self,
         layouter: &mut impl Layouter<F>,
@@ -372,24 +535,15 @@ impl<F: PrimeField> Blake2sChip<F> {
         assert!(inputs.len() % 2 == 0);
 
         // Init
-        let mut h = vec![
-            Blake2sWord::from_constant_u32(IV[0] ^ 0x01010000 ^ 32, layouter, self)?,
-            Blake2sWord::from_constant_u32(IV[1], layouter, self)?,
-            Blake2sWord::from_constant_u32(IV[2], layouter, self)?,
-            Blake2sWord::from_constant_u32(IV[3], layouter, self)?,
-            Blake2sWord::from_constant_u32(IV[4], layouter, self)?,
-            Blake2sWord::from_constant_u32(IV[5], layouter, self)?,
-            Blake2sWord::from_constant_u32(
-                IV[6] ^ LittleEndian::read_u32(&personalization[0..4]),
-                layouter,
-                self,
-            )?,
-            Blake2sWord::from_constant_u32(
-                IV[7] ^ LittleEndian::read_u32(&personalization[4..8]),
-                layouter,
-                self,
-            )?,
-        ];
+        let mut h = blake2s_param_block(
+            self.digest_len as u8,
+            0,
+            &[0; 8],
+            personalization.try_into().unwrap(),
+        )
+        .iter()
+        .map(|word| Blake2sWord::from_constant_u32(*word, layouter, self))
+        .collect::<Result<Vec<_>, Error>>()?;
 
         // Handle message: convert field message to blocks.
         let mut blocks = vec![];
@@ -415,18 +569,138 @@ impl<F: PrimeField> Blake2sChip<F> {
             self.compress(layouter, &mut h, block, (i as u64 + 1) * 64, false)?;
         }
 
-        // Compress(Final block)
+        // Compress(Final block). The counter is the number of message bytes, so the zero padding
+        // block of an empty message is compressed with t = 0.
         self.compress(
             layouter,
             &mut h,
             &blocks[block_len - 1],
-            (block_len as u64) * 64,
+            (inputs.len() as u64) * 32,
             true,
         )?;
 
+        // The digest is the prefix of the state
+        h.truncate(self.digest_len / 4);
+        Ok(h)
+    }
+
+    // Hash a message of byte cells. The bytes are packed into little-endian words, the last block is
+    // zero padded and the counter of each block is the number of message bytes up to its end.
+    pub fn hash(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        message: &[AssignedCell<F, F>],
+        personalization: &[u8],
+    ) -> Result<Vec<Blake2sWord<F>>, Error> {
+        assert_eq!(personalization.len(), 8);
+
+        // Init
+        let mut h = blake2s_param_block(
+            self.digest_len as u8,
+            0,
+            &[0; 8],
+            personalization.try_into().unwrap(),
+        )
+        .iter()
+        .map(|word| Blake2sWord::from_constant_u32(*word, layouter, self))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+        // Each message byte is decomposed to bits
+        let mut bytes = self.byte_check(layouter, message)?;
+
+        // Pad the message to full blocks, with at least one block
+        let block_num = std::cmp::max(1, (message.len() + 63) / 64);
+        for _ in message.len()..(block_num * 64) {
+            bytes.push(Blake2sByte::from_constant_u8(0, layouter, &self.config)?);
+        }
+
+        let words = self.bytes_to_words(layouter, &bytes)?;
+
+        for (i, block) in words.chunks(16).enumerate() {
+            let is_final = i == block_num - 1;
+            let t = if is_final {
+                message.len() as u64
+            } else {
+                (i as u64 + 1) * 64
+            };
+            self.compress(layouter, &mut h, block, t, is_final)?;
+        }
+
+        // The digest is the prefix of the state
+        h.truncate(self.digest_len / 4);
         Ok(h)
     }
 
+    // Pack byte cells into little-endian words, word = sum(bytes[i] * 256^i) for each four
+    // consecutive bytes. Every input is checked to be a byte.
+    pub fn pack_le_words<const N: usize>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        bytes: &[AssignedCell<F, F>; N],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        assert_eq!(N % 4, 0);
+        let bytes = self.byte_check(layouter, bytes)?;
+        let words = self.bytes_to_words(layouter, &bytes)?;
+        Ok(words.into_iter().map(|word| word.word).collect())
+    }
+
+    // Decompose each cell to eight bits, which fails unless it's a byte
+    fn byte_check(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        bytes: &[AssignedCell<F, F>],
+    ) -> Result<Vec<Blake2sByte<F>>, Error> {
+        bytes
+            .iter()
+            .map(|byte| {
+                let byte_value = byte.value().map(|b| b.to_repr().as_ref()[0]);
+                let decomposed = Blake2sByte::from_u8(
+                    byte_value,
+                    layouter.namespace(|| "from_u8"),
+                    &self.config,
+                )?;
+                layouter.assign_region(
+                    || "check byte",
+                    |mut region| region.constrain_equal(decomposed.get_byte().cell(), byte.cell()),
+                )?;
+                Ok(decomposed)
+            })
+            .collect()
+    }
+
+    fn bytes_to_words(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        bytes: &[Blake2sByte<F>],
+    ) -> Result<Vec<Blake2sWord<F>>, Error> {
+        let mut words = Vec::with_capacity(bytes.len() / 4);
+        for word_bytes in bytes.chunks(4) {
+            let byte_cells: Vec<_> = word_bytes.iter().map(|byte| byte.get_byte()).collect();
+            let byte_values: Value<Vec<_>> = byte_cells.iter().map(|byte| byte.value()).collect();
+            let word_value = byte_values.map(|bytes| {
+                bytes
+                    .into_iter()
+                    .rev()
+                    .fold(F::ZERO, |acc, byte| acc * F::from(1 << 8) + byte)
+            });
+            let word = assign_free_advice(
+                layouter.namespace(|| "assign word"),
+                self.config.advices[8],
+                word_value,
+            )?;
+            self.word_decompose(layouter.namespace(|| "word decompose"), &byte_cells, &word)?;
+            let bits: Vec<_> = word_bytes
+                .iter()
+                .flat_map(|byte| byte.get_bits().to_vec())
+                .collect();
+            words.push(Blake2sWord {
+                word,
+                bits: bits.try_into().unwrap(),
+            });
+        }
+        Ok(words)
+    }
+
     // Encode the eight words to two field elements
     pub fn encode_result(
         &self,
@@ -913,11 +1187,23 @@ impl<F: PrimeField> Blake2sChip<F> {
 
     fn add_mod_u32(
         &self,
-        mut layouter: impl Layouter<F>,
+        layouter: impl Layouter<F>,
         // x and y must be a word variable
         x: &AssignedCell<F, F>,
         y: &AssignedCell<F, F>,
     ) -> Result<AssignedCell<F, F>, Error> {
+        self.add_mod_u32_with_carry(layouter, x, y)
+            .map(|(ret, _carry)| ret)
+    }
+
+    // x + y = ret + carry * 2^32, with the carry a bit. The ret is only a word once it's been
+    // decomposed, e.g. by Blake2sWord::from_word.
+    fn add_mod_u32_with_carry(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+        y: &AssignedCell<F, F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
         layouter.assign_region(
             || "decompose bytes to bits",
             |mut region| {
@@ -936,13 +1222,13 @@ impl<F: PrimeField> Blake2sChip<F> {
                     1,
                     || sum.map(|sum| sum.0),
                 )?;
-                region.assign_advice(
+                let carry = region.assign_advice(
                     || "word_add carry",
                     self.config.advices[1],
                     1,
                     || sum.map(|sum| sum.1),
                 )?;
-                Ok(ret)
+                Ok((ret, carry))
             },
         )
     }
@@ -976,6 +1262,8 @@ impl<F: PrimeField> Blake2sWord<F> {
         })
     }
 
+    // Rotate the little-endian bits of a word right by `by` bits, i.e. `>>>`. The amounts from 32 on
+    // are taken modulo 32.
     pub fn word_rotate(bits: &Vec<AssignedCell<F, F>>, by: usize) -> Vec<AssignedCell<F, F>> {
         assert!(bits.len() == 32);
         let by = by % 32;
@@ -987,6 +1275,8 @@ impl<F: PrimeField> Blake2sWord<F> {
             .collect()
     }
 
+    // Shift the word right by `by` bits, i.e. `>>`. The low bits are dropped and the high bits are
+    // padded with a constant zero, so the result is the floor division by 2^by.
     pub fn shift(
         &self,
         by: usize,
@@ -1196,3 +1486,795 @@ fn test_blake2s_circuit() {
     let prover = MockProver::run(14, &circuit, vec![]).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
+
+#[test]
+fn test_blake2s_param_block() {
+    // BLAKE2s-256 without key, salt and personalization
+    let h = blake2s_param_block(32, 0, &[0; 8], &[0; 8]);
+    assert_eq!(h[0], 0x6B08E647);
+    assert_eq!(h[1..], IV[1..]);
+
+    // The key length is the second byte of the block and the digest length the first one
+    let h = blake2s_param_block(16, 32, &[0; 8], &[0; 8]);
+    assert_eq!(h[0], IV[0] ^ 0x01012010);
+
+    // The salt takes bytes 16..24 and the personalization bytes 24..32, in little endian
+    let salt = [1, 2, 3, 4, 5, 6, 7, 8];
+    let personal = *b"Taiga_VP";
+    let h = blake2s_param_block(32, 0, &salt, &personal);
+    assert_eq!(h[1..4], IV[1..4]);
+    assert_eq!(h[4], IV[4] ^ 0x04030201);
+    assert_eq!(h[5], IV[5] ^ 0x08070605);
+    assert_eq!(h[6], IV[6] ^ LittleEndian::read_u32(b"Taig"));
+    assert_eq!(h[7], IV[7] ^ LittleEndian::read_u32(b"a_VP"));
+}
+
+#[test]
+fn test_blake2s_config_selectors() {
+    use halo2_proofs::plonk::ConstraintSystem;
+    use pasta_curves::pallas;
+
+    let mut meta = ConstraintSystem::<pallas::Base>::default();
+    let advices = [(); 10].map(|_| meta.advice_column());
+    Blake2sConfig::configure(&mut meta, advices);
+
+    // Every selector is enabled by the chip and guards exactly one gate
+    assert_eq!(meta.num_selectors(), 6);
+    assert_eq!(meta.gates().len(), meta.num_selectors());
+}
+
+#[cfg(test)]
+mod testing {
+    use super::{Blake2sChip, Blake2sConfig};
+    use halo2_proofs::{
+        circuit::{floor_planner, Layouter},
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use pasta_curves::pallas;
+
+    /// The layout of a Blake2s test over the chip config. `Layouter` is not object safe, so the
+    /// layout is a trait method rather than a closure.
+    pub trait Blake2sTest {
+        fn synthesize(
+            &self,
+            config: Blake2sConfig<pallas::Base>,
+            layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error>;
+    }
+
+    /// Runs a Blake2s test over the ten advice columns of the chip and a constants column.
+    #[derive(Clone, Debug, Default)]
+    pub struct Blake2sTestCircuit<T>(pub T);
+
+    impl<T: Blake2sTest + Default> Circuit<pallas::Base> for Blake2sTestCircuit<T> {
+        type Config = Blake2sConfig<pallas::Base>;
+        type FloorPlanner = floor_planner::V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self(T::default())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [(); 10].map(|_| meta.advice_column());
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            Blake2sChip::configure(meta, advices)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            self.0.synthesize(config, layouter)
+        }
+    }
+}
+
+#[test]
+fn test_blake2s_word_xor() {
+    use crate::circuit::blake2s::testing::{Blake2sTest, Blake2sTestCircuit};
+    use halo2_proofs::{circuit::Layouter, dev::MockProver, plonk::Error};
+    use pasta_curves::pallas;
+    use rand::{rngs::OsRng, RngCore};
+
+    #[derive(Default)]
+    struct MyCircuit {
+        pairs: Vec<(u32, u32)>,
+    }
+
+    impl Blake2sTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: Blake2sConfig<pallas::Base>,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let blake2s_chip = Blake2sChip::construct(config);
+            for (x, y) in self.pairs.iter() {
+                let x_word = Blake2sWord::from_word(
+                    &blake2s_chip,
+                    layouter.namespace(|| "x"),
+                    assign_free_advice(
+                        layouter.namespace(|| "witness x"),
+                        config.advices[8],
+                        Value::known(pallas::Base::from(*x as u64)),
+                    )?,
+                )?;
+                let y_word = Blake2sWord::from_word(
+                    &blake2s_chip,
+                    layouter.namespace(|| "y"),
+                    assign_free_advice(
+                        layouter.namespace(|| "witness y"),
+                        config.advices[8],
+                        Value::known(pallas::Base::from(*y as u64)),
+                    )?,
+                )?;
+                let bits = blake2s_chip.word_xor(
+                    layouter.namespace(|| "x ^ y"),
+                    x_word.get_bits(),
+                    y_word.get_bits(),
+                )?;
+                let xor =
+                    Blake2sWord::from_bits(&blake2s_chip, layouter.namespace(|| "xor"), bits)?;
+
+                // Compare with the native xor
+                layouter.assign_region(
+                    || "check xor",
+                    |mut region| {
+                        region.constrain_constant(
+                            xor.get_word().cell(),
+                            pallas::Base::from((x ^ y) as u64),
+                        )
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    // Distinct 32-bit words, including ones with the top bit set
+    let circuit = Blake2sTestCircuit(MyCircuit {
+        pairs: vec![
+            (0xDEADBEEF, 0x0BADF00D),
+            (0xFFFFFFFF, 0x00000000),
+            (0x6A09E667, 0x6A09E667),
+            (OsRng.next_u32(), OsRng.next_u32()),
+        ],
+    });
+    let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_blake2s_add_mod_u32() {
+    use crate::circuit::blake2s::testing::{Blake2sTest, Blake2sTestCircuit};
+    use halo2_proofs::{circuit::Layouter, dev::MockProver, plonk::Error};
+    use pasta_curves::pallas;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        x: u32,
+        y: u32,
+    }
+
+    impl Blake2sTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: Blake2sConfig<pallas::Base>,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let blake2s_chip = Blake2sChip::construct(config);
+            let x = Blake2sWord::from_constant_u32(self.x, &mut layouter, &blake2s_chip)?;
+            let y = Blake2sWord::from_constant_u32(self.y, &mut layouter, &blake2s_chip)?;
+            let (sum, carry) = blake2s_chip.add_mod_u32_with_carry(
+                layouter.namespace(|| "x + y"),
+                x.get_word(),
+                y.get_word(),
+            )?;
+            // The sum is a word
+            let sum = Blake2sWord::from_word(&blake2s_chip, layouter.namespace(|| "sum"), sum)?;
+
+            let (expected_sum, expected_carry) = self.x.overflowing_add(self.y);
+            layouter.assign_region(
+                || "check sum",
+                |mut region| {
+                    region.constrain_constant(
+                        sum.get_word().cell(),
+                        pallas::Base::from(expected_sum as u64),
+                    )?;
+                    region
+                        .constrain_constant(carry.cell(), pallas::Base::from(expected_carry as u64))
+                },
+            )
+        }
+    }
+
+    // 0xFFFFFFFF + 1 wraps around to 0 with a carry
+    for (x, y) in [(0xFFFFFFFF, 1), (0x6A09E667, 0xBB67AE85), (12, 30)] {
+        let circuit = Blake2sTestCircuit(MyCircuit { x, y });
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
+
+#[test]
+fn test_blake2s_word_rotate() {
+    use crate::circuit::blake2s::testing::{Blake2sTest, Blake2sTestCircuit};
+    use halo2_proofs::{circuit::Layouter, dev::MockProver, plonk::Error};
+    use pasta_curves::pallas;
+
+    const WORD: u32 = 0x9B05688C;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        by: usize,
+    }
+
+    impl Blake2sTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: Blake2sConfig<pallas::Base>,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let blake2s_chip = Blake2sChip::construct(config);
+            let word = Blake2sWord::from_constant_u32(WORD, &mut layouter, &blake2s_chip)?;
+            let bits = Blake2sWord::word_rotate(&word.get_bits().to_vec(), self.by);
+            let rotated =
+                Blake2sWord::from_bits(&blake2s_chip, layouter.namespace(|| "rotated"), bits)?;
+            layouter.assign_region(
+                || "check rotation",
+                |mut region| {
+                    region.constrain_constant(
+                        rotated.get_word().cell(),
+                        pallas::Base::from(WORD.rotate_right(self.by as u32) as u64),
+                    )
+                },
+            )
+        }
+    }
+
+    for by in [R1, R2, R3, R4] {
+        let circuit = Blake2sTestCircuit(MyCircuit { by });
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
+
+#[test]
+fn test_blake2s_word_shift() {
+    use crate::circuit::blake2s::testing::{Blake2sTest, Blake2sTestCircuit};
+    use halo2_proofs::{circuit::Layouter, dev::MockProver, plonk::Error};
+    use pasta_curves::pallas;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        word: u32,
+        by: usize,
+    }
+
+    impl Blake2sTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: Blake2sConfig<pallas::Base>,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let blake2s_chip = Blake2sChip::construct(config);
+            let word = Blake2sWord::from_constant_u32(self.word, &mut layouter, &blake2s_chip)?;
+            let bits = word.shift(self.by, layouter.namespace(|| "shift"), config.advices[0])?;
+            let shifted =
+                Blake2sWord::from_bits(&blake2s_chip, layouter.namespace(|| "shifted"), bits)?;
+            layouter.assign_region(
+                || "check shift",
+                |mut region| {
+                    region.constrain_constant(
+                        shifted.get_word().cell(),
+                        pallas::Base::from((self.word >> self.by) as u64),
+                    )
+                },
+            )
+        }
+    }
+
+    for word in [0xFFFFFFFF, 0x80000001, 0x510E527F, 7] {
+        for by in [3, 7, 10, 31] {
+            let circuit = Blake2sTestCircuit(MyCircuit { word, by });
+            let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+}
+
+#[test]
+fn test_blake2s_chip_single_block() {
+    use crate::circuit::blake2s::testing::{Blake2sTest, Blake2sTestCircuit};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{Layouter, Value},
+        dev::MockProver,
+        plonk::Error,
+    };
+    use pasta_curves::pallas;
+    use rand::rngs::OsRng;
+
+    const PERSONALIZATION: &[u8; 8] = b"Taiga-Bk";
+
+    // Two field elements fill one 64-byte block
+    #[derive(Default)]
+    struct MyCircuit {
+        message: [pallas::Base; 2],
+    }
+
+    impl Blake2sTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: Blake2sConfig<pallas::Base>,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let message = self
+                .message
+                .iter()
+                .map(|field| {
+                    assign_free_advice(
+                        layouter.namespace(|| "message"),
+                        config.advices[0],
+                        Value::known(*field),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            let blake2s_chip = Blake2sChip::construct(config);
+            let words = blake2s_chip.process(&mut layouter, &message, PERSONALIZATION)?;
+
+            let message_bytes: Vec<u8> = self
+                .message
+                .iter()
+                .flat_map(|field| field.to_repr().as_ref().to_vec())
+                .collect();
+            let hash = blake2s_native_with_personal(&message_bytes, None, 32, PERSONALIZATION);
+            for (word, expected) in words.iter().zip(hash.chunks(4)) {
+                layouter.assign_region(
+                    || "check digest word",
+                    |mut region| {
+                        region.constrain_constant(
+                            word.get_word().cell(),
+                            pallas::Base::from(LittleEndian::read_u32(expected) as u64),
+                        )
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    let mut rng = OsRng;
+    let circuit = Blake2sTestCircuit(MyCircuit {
+        message: [
+            pallas::Base::random(&mut rng),
+            pallas::Base::random(&mut rng),
+        ],
+    });
+    let prover = MockProver::run(14, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_blake2s_hash() {
+    use crate::circuit::blake2s::testing::{Blake2sTest, Blake2sTestCircuit};
+    use halo2_proofs::{
+        circuit::{Layouter, Value},
+        dev::MockProver,
+        plonk::Error,
+    };
+    use pasta_curves::pallas;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        message: Vec<u8>,
+        expected: Vec<u8>,
+    }
+
+    impl Blake2sTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: Blake2sConfig<pallas::Base>,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let message = self
+                .message
+                .iter()
+                .map(|byte| {
+                    assign_free_advice(
+                        layouter.namespace(|| "message byte"),
+                        config.advices[0],
+                        Value::known(pallas::Base::from(*byte as u64)),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            let blake2s_chip = Blake2sChip::construct(config);
+            let digest = blake2s_chip.hash(&mut layouter, &message, &[0; 8])?;
+            for (word, expected) in digest.iter().zip(self.expected.chunks(4)) {
+                layouter.assign_region(
+                    || "check digest word",
+                    |mut region| {
+                        region.constrain_constant(
+                            word.get_word().cell(),
+                            pallas::Base::from(LittleEndian::read_u32(expected) as u64),
+                        )
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    // The BLAKE2s-256 test vector of RFC 7693, appendix B
+    let circuit = Blake2sTestCircuit(MyCircuit {
+        message: b"abc".to_vec(),
+        expected: vec![
+            0x50, 0x8c, 0x5e, 0x8c, 0x32, 0x7c, 0x14, 0xe2, 0xe1, 0xa7, 0x2b, 0xa3, 0x4e, 0xeb,
+            0x45, 0x2f, 0x37, 0x45, 0x8b, 0x20, 0x9e, 0xd6, 0x3a, 0x29, 0x4d, 0x99, 0x9b, 0x4c,
+            0x86, 0x67, 0x59, 0x82,
+        ],
+    });
+    let prover = MockProver::run(14, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The empty message
+    let circuit = Blake2sTestCircuit(MyCircuit {
+        message: vec![],
+        expected: vec![
+            0x69, 0x21, 0x7a, 0x30, 0x79, 0x90, 0x80, 0x94, 0xe1, 0x11, 0x21, 0xd0, 0x42, 0x35,
+            0x4a, 0x7c, 0x1f, 0x55, 0xb6, 0x48, 0x2c, 0xa1, 0xa5, 0x1e, 0x1b, 0x25, 0x0d, 0xfd,
+            0x1e, 0xd0, 0xee, 0xf9,
+        ],
+    });
+    let prover = MockProver::run(14, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A message over two blocks
+    let message: Vec<u8> = (0..100).collect();
+    let expected = blake2s_native(&message, None, 32);
+    let circuit = Blake2sTestCircuit(MyCircuit { message, expected });
+    let prover = MockProver::run(15, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A wrong digest
+    let circuit = Blake2sTestCircuit(MyCircuit {
+        message: b"abd".to_vec(),
+        expected: vec![
+            0x50, 0x8c, 0x5e, 0x8c, 0x32, 0x7c, 0x14, 0xe2, 0xe1, 0xa7, 0x2b, 0xa3, 0x4e, 0xeb,
+            0x45, 0x2f, 0x37, 0x45, 0x8b, 0x20, 0x9e, 0xd6, 0x3a, 0x29, 0x4d, 0x99, 0x9b, 0x4c,
+            0x86, 0x67, 0x59, 0x82,
+        ],
+    });
+    let prover = MockProver::run(14, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_blake2s_process_counters() {
+    use crate::circuit::blake2s::testing::{Blake2sTest, Blake2sTestCircuit};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{Layouter, Value},
+        dev::MockProver,
+        plonk::Error,
+    };
+    use pasta_curves::pallas;
+    use rand::rngs::OsRng;
+
+    const PERSONALIZATION: &[u8; 8] = b"Taiga-Bk";
+
+    // The counter of each block is the number of message bytes up to its end, and only the last
+    // block is final
+    #[derive(Default)]
+    struct MyCircuit {
+        message: Vec<pallas::Base>,
+    }
+
+    impl Blake2sTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: Blake2sConfig<pallas::Base>,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let message = self
+                .message
+                .iter()
+                .map(|field| {
+                    assign_free_advice(
+                        layouter.namespace(|| "message"),
+                        config.advices[0],
+                        Value::known(*field),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            let blake2s_chip = Blake2sChip::construct(config);
+            let words = blake2s_chip.process(&mut layouter, &message, PERSONALIZATION)?;
+
+            let message_bytes: Vec<u8> = self
+                .message
+                .iter()
+                .flat_map(|field| field.to_repr().as_ref().to_vec())
+                .collect();
+            let hash = blake2s_native_with_personal(&message_bytes, None, 32, PERSONALIZATION);
+            for (word, expected) in words.iter().zip(hash.chunks(4)) {
+                layouter.assign_region(
+                    || "check digest word",
+                    |mut region| {
+                        region.constrain_constant(
+                            word.get_word().cell(),
+                            pallas::Base::from(LittleEndian::read_u32(expected) as u64),
+                        )
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    // Four field elements fill two blocks, compressed with the counters 64 and 128
+    let mut rng = OsRng;
+    let circuit = Blake2sTestCircuit(MyCircuit {
+        message: (0..4).map(|_| pallas::Base::random(&mut rng)).collect(),
+    });
+    let prover = MockProver::run(15, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The empty message is a final zero padding block compressed with the counter 0
+    let circuit = Blake2sTestCircuit(MyCircuit { message: vec![] });
+    let prover = MockProver::run(14, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_blake2s_digest_len() {
+    use crate::circuit::blake2s::testing::{Blake2sTest, Blake2sTestCircuit};
+    use halo2_proofs::{
+        circuit::{Layouter, Value},
+        dev::MockProver,
+        plonk::Error,
+    };
+    use pasta_curves::pallas;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        expected: Vec<u8>,
+    }
+
+    impl Blake2sTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: Blake2sConfig<pallas::Base>,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let message = b"abc"
+                .iter()
+                .map(|byte| {
+                    assign_free_advice(
+                        layouter.namespace(|| "message byte"),
+                        config.advices[0],
+                        Value::known(pallas::Base::from(*byte as u64)),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            let blake2s_chip = Blake2sChip::construct_with_digest_len(config, self.expected.len());
+            let digest = blake2s_chip.hash(&mut layouter, &message, &[0; 8])?;
+            assert_eq!(digest.len() * 4, self.expected.len());
+            for (word, expected) in digest.iter().zip(self.expected.chunks(4)) {
+                layouter.assign_region(
+                    || "check digest word",
+                    |mut region| {
+                        region.constrain_constant(
+                            word.get_word().cell(),
+                            pallas::Base::from(LittleEndian::read_u32(expected) as u64),
+                        )
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    for digest_len in [16, 20] {
+        let circuit = Blake2sTestCircuit(MyCircuit {
+            expected: blake2s_native(b"abc", None, digest_len),
+        });
+        let prover = MockProver::run(14, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
+
+#[test]
+fn test_blake2s_native() {
+    use blake2s_simd::Params;
+
+    // The BLAKE2s-256 test vector of RFC 7693, appendix B
+    assert_eq!(
+        blake2s_native(b"abc", None, 32),
+        [
+            0x50, 0x8c, 0x5e, 0x8c, 0x32, 0x7c, 0x14, 0xe2, 0xe1, 0xa7, 0x2b, 0xa3, 0x4e, 0xeb,
+            0x45, 0x2f, 0x37, 0x45, 0x8b, 0x20, 0x9e, 0xd6, 0x3a, 0x29, 0x4d, 0x99, 0x9b, 0x4c,
+            0x86, 0x67, 0x59, 0x82,
+        ]
+    );
+
+    // BLAKE2s-128("abc") and BLAKE2s-160("abc")
+    assert_eq!(
+        blake2s_native(b"abc", None, 16),
+        [
+            0xaa, 0x49, 0x38, 0x11, 0x9b, 0x1d, 0xc7, 0xb8, 0x7c, 0xba, 0xd0, 0xff, 0xd2, 0x00,
+            0xd0, 0xae,
+        ]
+    );
+    assert_eq!(
+        blake2s_native(b"abc", None, 20),
+        [
+            0x5a, 0xe3, 0xb9, 0x9b, 0xe2, 0x9b, 0x01, 0x83, 0x4c, 0x3b, 0x50, 0x85, 0x21, 0xed,
+            0xe6, 0x04, 0x38, 0xf8, 0xde, 0x17,
+        ]
+    );
+
+    // Empty, block sized and multi-block messages, keyed or not, against blake2s_simd
+    let key: Vec<u8> = (0..32).collect();
+    for len in [0, 1, 63, 64, 65, 128, 200] {
+        let message: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        for digest_len in [1, 16, 32] {
+            assert_eq!(
+                blake2s_native(&message, None, digest_len),
+                Params::new()
+                    .hash_length(digest_len)
+                    .hash(&message)
+                    .as_bytes()
+            );
+            assert_eq!(
+                blake2s_native(&message, Some(&key), digest_len),
+                Params::new()
+                    .hash_length(digest_len)
+                    .key(&key)
+                    .hash(&message)
+                    .as_bytes()
+            );
+            assert_eq!(
+                blake2s_native_with_personal(&message, None, digest_len, b"Taiga-Bk"),
+                Params::new()
+                    .hash_length(digest_len)
+                    .personal(b"Taiga-Bk")
+                    .hash(&message)
+                    .as_bytes()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_blake2s_pack_le_words() {
+    use crate::circuit::blake2s::testing::{Blake2sTest, Blake2sTestCircuit};
+    use halo2_proofs::{
+        circuit::{Layouter, Value},
+        dev::MockProver,
+        plonk::Error,
+    };
+    use pasta_curves::pallas;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        bytes: Vec<u64>,
+    }
+
+    impl Blake2sTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: Blake2sConfig<pallas::Base>,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let bytes: [_; 64] = self
+                .bytes
+                .iter()
+                .map(|byte| {
+                    assign_free_advice(
+                        layouter.namespace(|| "byte"),
+                        config.advices[0],
+                        Value::known(pallas::Base::from(*byte)),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?
+                .try_into()
+                .unwrap();
+
+            let blake2s_chip = Blake2sChip::construct(config);
+            let words = blake2s_chip.pack_le_words(&mut layouter, &bytes)?;
+            assert_eq!(words.len(), 16);
+
+            let native_bytes: Vec<u8> = self.bytes.iter().map(|byte| *byte as u8).collect();
+            for (word, expected) in words.iter().zip(native_bytes.chunks(4)) {
+                layouter.assign_region(
+                    || "check word",
+                    |mut region| {
+                        region.constrain_constant(
+                            word.cell(),
+                            pallas::Base::from(LittleEndian::read_u32(expected) as u64),
+                        )
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    let bytes: Vec<u64> = (0..64).map(|i| (i * 37 + 11) % 256).collect();
+    let circuit = Blake2sTestCircuit(MyCircuit {
+        bytes: bytes.clone(),
+    });
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // An input isn't a byte
+    let mut bytes = bytes;
+    bytes[5] = 256;
+    let circuit = Blake2sTestCircuit(MyCircuit { bytes });
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_blake2s_circuit_area() {
+    use crate::circuit::blake2s::testing::{Blake2sTest, Blake2sTestCircuit};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{Layouter, Value},
+        plonk::Error,
+    };
+    use rand::rngs::OsRng;
+
+    // One hash of a single block
+    #[derive(Default)]
+    struct MyCircuit {
+        message: [pallas::Base; 2],
+    }
+
+    impl Blake2sTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: Blake2sConfig<pallas::Base>,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let message = self
+                .message
+                .iter()
+                .map(|field| {
+                    assign_free_advice(
+                        layouter.namespace(|| "message"),
+                        config.advices[0],
+                        Value::known(*field),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let blake2s_chip = Blake2sChip::construct(config);
+            blake2s_chip.process(&mut layouter, &message, b"Taiga-Bk")?;
+            Ok(())
+        }
+    }
+
+    let mut rng = OsRng;
+    let circuit = Blake2sTestCircuit(MyCircuit {
+        message: [(); 2].map(|_| pallas::Base::random(&mut rng)),
+    });
+    let area = circuit_area(&circuit, 16).unwrap();
+    println!("blake2s single block area: {:?}", area);
+
+    // The chip works over the ten advice columns with custom gates only, and the ten rounds of
+    // one compression take a few thousand rows
+    assert_eq!(area.advice_columns, 10);
+    assert_eq!(area.fixed_columns, 1);
+    assert_eq!(area.selectors, 6);
+    assert_eq!(area.lookups, 0);
+    assert!((12..=14).contains(&area.k));
+    assert!(area.proof_size < 1 << 14);
+}
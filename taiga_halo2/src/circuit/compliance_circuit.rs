@@ -2,7 +2,7 @@ use crate::circuit::blake2s::{vp_commitment_gadget, Blake2sChip, Blake2sConfig};
 use crate::circuit::gadgets::assign_free_advice;
 use crate::circuit::hash_to_curve::HashToCurveConfig;
 use crate::circuit::integrity::{
-    check_input_resource, check_output_resource, compute_delta_commitment,
+    check_input_resource, check_output_resource, compute_delta_commitment, ResourceCheckConstants,
 };
 use crate::circuit::merkle_circuit::{
     merkle_poseidon_gadget, MerklePoseidonChip, MerklePoseidonConfig,
@@ -152,7 +152,7 @@ impl Circuit<pallas::Base> for ComplianceCircuit {
 
         let resource_commit_config = ResourceCommitChip::configure(
             meta,
-            advices[0..3].try_into().unwrap(),
+            &advices[0..3],
             poseidon_config.clone(),
             range_check,
         );
@@ -202,6 +202,10 @@ impl Circuit<pallas::Base> for ComplianceCircuit {
 
         // Construct a resource_commit chip
         let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+        let resource_check_constants = ResourceCheckConstants::assign(
+            layouter.namespace(|| "resource check constants"),
+            config.advices[0],
+        )?;
 
         // Input resource
         // Check the input resource commitment
@@ -210,6 +214,7 @@ impl Circuit<pallas::Base> for ComplianceCircuit {
             config.advices,
             config.instances,
             resource_commit_chip.clone(),
+            &resource_check_constants,
             self.input_resource,
             COMPLIANCE_NF_PUBLIC_INPUT_ROW_IDX,
         )?;
@@ -228,6 +233,7 @@ impl Circuit<pallas::Base> for ComplianceCircuit {
             config.advices,
             config.instances,
             resource_commit_chip,
+            &resource_check_constants,
             self.output_resource,
             input_resource_variables.nf,
             COMPLIANCE_OUTPUT_CM_PUBLIC_INPUT_ROW_IDX,
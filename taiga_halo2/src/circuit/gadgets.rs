@@ -5,14 +5,60 @@ use halo2_proofs::{
 };
 
 pub mod add;
+pub mod age_threshold;
+pub mod airdrop_eligibility;
+pub mod allowance;
+pub mod approved_asset;
+pub mod ascii_tag;
+pub mod atomic_link;
+pub mod auditor_memo;
+pub mod balance_reconciliation;
+pub mod change_to_sender;
+pub mod channel_update;
+pub mod coin_selection;
+pub mod commitment_matches;
 pub mod conditional_equal;
 pub mod conditional_select;
+pub mod dao_owner;
+pub mod decompose_128;
+pub mod delegation;
 pub mod extended_or_relation;
+pub mod feature_bitmap;
+pub mod fee_output;
+pub mod hd_derivation;
+pub mod htlc;
+pub mod input_count_range;
+pub mod interest_accrual;
+pub mod linear_vesting;
+pub mod merge;
 pub mod mul;
+pub mod nonce_binding;
+pub mod nullifier_accumulator;
+pub mod nullifier_shard;
+pub mod oracle_conversion;
+pub mod owner_is_signer;
 pub mod poseidon_hash;
+pub mod preserve_field;
+pub mod quorum;
+pub mod reissue;
+pub mod resource_version;
+pub mod ring_swap;
+pub mod root_history;
+pub mod royalty_split;
+pub mod schnorr_verify;
+pub mod shard_membership;
+pub mod single_output;
+pub mod state_transition;
+pub mod structured_data;
 pub mod sub;
 pub mod target_resource_variable;
+pub mod template;
 pub mod triple_mul;
+pub mod tx_value_cap;
+pub mod value_bucket;
+pub mod value_encryption;
+pub mod variable_depth_membership;
+pub mod wrap_unwrap;
 
 pub fn assign_free_advice<F: arithmetic::Field, V: Copy>(
     mut layouter: impl Layouter<F>,
@@ -0,0 +1,196 @@
+/// Constrain (owner, birthdate) is an entry of the identity tree and current_date - birthdate >= threshold
+use crate::circuit::gadgets::{
+    coin_selection::u64_range_check,
+    poseidon_hash::poseidon_hash_gadget,
+    sub::{SubChip, SubInstructions},
+};
+use crate::circuit::merkle_circuit::{merkle_poseidon_gadget, MerklePoseidonChip};
+use crate::merkle_tree::LR;
+use crate::utils::poseidon_hash;
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// The leaf of an identity in the identity tree, with the birthdate in days since the epoch
+pub fn identity_leaf(owner: pallas::Base, birthdate: u64) -> pallas::Base {
+    poseidon_hash(owner, pallas::Base::from(birthdate))
+}
+
+// Check the owner is at least threshold days old at the current date. The birthdate stays private:
+// only the identity root, the current date and the threshold are expected to be public.
+#[allow(clippy::too_many_arguments)]
+pub fn age_threshold_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    merkle_chip: MerklePoseidonChip,
+    sub_chip: SubChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    owner: &AssignedCell<pallas::Base, pallas::Base>,
+    birthdate: &AssignedCell<pallas::Base, pallas::Base>,
+    identity_root: &AssignedCell<pallas::Base, pallas::Base>,
+    merkle_path: &[(pallas::Base, LR)],
+    current_date: &AssignedCell<pallas::Base, pallas::Base>,
+    threshold: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let leaf = poseidon_hash_gadget(
+        merkle_chip.get_poseidon_config(),
+        layouter.namespace(|| "identity leaf"),
+        [owner.clone(), birthdate.clone()],
+    )?;
+    let root = merkle_poseidon_gadget(
+        layouter.namespace(|| "identity membership"),
+        merkle_chip,
+        leaf,
+        merkle_path,
+    )?;
+    layouter.assign_region(
+        || "check identity root",
+        |mut region| region.constrain_equal(root.cell(), identity_root.cell()),
+    )?;
+
+    // The dates and the threshold are 64-bit, so the differences below can't wrap around
+    for value in [birthdate, current_date, threshold] {
+        u64_range_check(
+            layouter.namespace(|| "date range check"),
+            lookup_config,
+            value,
+        )?;
+    }
+
+    // age = current_date - birthdate >= 0
+    let age = sub_chip.sub(
+        layouter.namespace(|| "current_date - birthdate"),
+        current_date,
+        birthdate,
+    )?;
+    u64_range_check(
+        layouter.namespace(|| "age range check"),
+        lookup_config,
+        &age,
+    )?;
+
+    // age - threshold >= 0
+    let excess = sub_chip.sub(layouter.namespace(|| "age - threshold"), &age, threshold)?;
+    u64_range_check(
+        layouter.namespace(|| "age over threshold"),
+        lookup_config,
+        &excess,
+    )
+}
+
+#[test]
+fn test_halo2_age_threshold_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::merkle_circuit::MerklePoseidonConfig;
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{MerkleGadgetTest, MerkleGadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::merkle_tree::{MerklePath, Node};
+    use halo2_proofs::{arithmetic::Field, circuit::Value, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        owner: pallas::Base,
+        birthdate: u64,
+        merkle_path: MerklePath,
+    }
+
+    impl MerkleGadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            merkle_config: MerklePoseidonConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let owner = assign_free_advice(
+                layouter.namespace(|| "witness owner"),
+                config.advices[0],
+                Value::known(self.owner),
+            )?;
+            let birthdate = assign_free_advice(
+                layouter.namespace(|| "witness birthdate"),
+                config.advices[0],
+                Value::known(pallas::Base::from(self.birthdate)),
+            )?;
+            let identity_root = assign_free_instance(
+                layouter.namespace(|| "identity root"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+            let current_date = assign_free_instance(
+                layouter.namespace(|| "current date"),
+                config.instances,
+                1,
+                config.advices[0],
+            )?;
+            let threshold = assign_free_instance(
+                layouter.namespace(|| "age threshold"),
+                config.instances,
+                2,
+                config.advices[0],
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            age_threshold_gadget(
+                layouter.namespace(|| "age threshold"),
+                MerklePoseidonChip::construct(merkle_config),
+                SubChip::construct(config.sub_config, ()),
+                resource_commit_chip.get_lookup_config(),
+                &owner,
+                &birthdate,
+                &identity_root,
+                &self.merkle_path.get_path(),
+                &current_date,
+                &threshold,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    // Days since the epoch
+    let current_date = 20_000u64;
+    let threshold = 18 * 365u64;
+    let adult = (pallas::Base::random(&mut rng), current_date - 30 * 365);
+    let minor = (pallas::Base::random(&mut rng), current_date - 10 * 365);
+    let leaves =
+        [adult, minor].map(|(owner, birthdate)| Node::from(identity_leaf(owner, birthdate)));
+    let identity_root = Node::combine(&leaves[0], &leaves[1]).inner();
+    let public_inputs = vec![
+        identity_root,
+        pallas::Base::from(current_date),
+        pallas::Base::from(threshold),
+    ];
+
+    // The adult is over the threshold
+    let circuit = MerkleGadgetTestCircuit(MyCircuit {
+        owner: adult.0,
+        birthdate: adult.1,
+        merkle_path: MerklePath::from_path(vec![(leaves[1], LR::R)]),
+    });
+    let prover = MockProver::run(11, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The minor is rejected
+    let circuit = MerkleGadgetTestCircuit(MyCircuit {
+        owner: minor.0,
+        birthdate: minor.1,
+        merkle_path: MerklePath::from_path(vec![(leaves[0], LR::L)]),
+    });
+    let prover = MockProver::run(11, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The minor claims an older birthdate than the committed one
+    let circuit = MerkleGadgetTestCircuit(MyCircuit {
+        owner: minor.0,
+        birthdate: adult.1,
+        merkle_path: MerklePath::from_path(vec![(leaves[0], LR::L)]),
+    });
+    let prover = MockProver::run(11, &circuit, vec![public_inputs]).unwrap();
+    assert!(prover.verify().is_err());
+}
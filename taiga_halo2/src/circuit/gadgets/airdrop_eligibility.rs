@@ -0,0 +1,199 @@
+/// Constrain (address, amount) is an entry of the snapshot tree and claimed = amount
+use crate::circuit::gadgets::poseidon_hash::poseidon_hash_gadget;
+use crate::circuit::merkle_circuit::{merkle_poseidon_gadget, MerklePoseidonChip};
+use crate::merkle_tree::LR;
+use crate::utils::poseidon_hash;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// The leaf of a balance entry in the snapshot tree
+pub fn snapshot_leaf(address: pallas::Base, amount: u64) -> pallas::Base {
+    poseidon_hash(address, pallas::Base::from(amount))
+}
+
+// Check the address is entitled to the claimed quantity by the snapshot. The address and the
+// claimed quantity are expected to be the owner and the quantity of the airdropped output.
+pub fn airdrop_eligibility_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    merkle_chip: MerklePoseidonChip,
+    address: &AssignedCell<pallas::Base, pallas::Base>,
+    amount: &AssignedCell<pallas::Base, pallas::Base>,
+    claimed: &AssignedCell<pallas::Base, pallas::Base>,
+    snapshot_root: &AssignedCell<pallas::Base, pallas::Base>,
+    merkle_path: &[(pallas::Base, LR)],
+) -> Result<(), Error> {
+    let leaf = poseidon_hash_gadget(
+        merkle_chip.get_poseidon_config(),
+        layouter.namespace(|| "snapshot leaf"),
+        [address.clone(), amount.clone()],
+    )?;
+
+    let root = merkle_poseidon_gadget(
+        layouter.namespace(|| "snapshot membership"),
+        merkle_chip,
+        leaf,
+        merkle_path,
+    )?;
+
+    layouter.assign_region(
+        || "check airdrop claim",
+        |mut region| {
+            region.constrain_equal(root.cell(), snapshot_root.cell())?;
+            region.constrain_equal(claimed.cell(), amount.cell())
+        },
+    )
+}
+
+#[test]
+fn test_halo2_airdrop_eligibility_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::merkle_circuit::MerklePoseidonConfig;
+    use crate::merkle_tree::{MerklePath, Node};
+    use halo2_gadgets::poseidon::{primitives as poseidon, Pow5Chip as PoseidonChip};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        address: pallas::Base,
+        amount: u64,
+        claimed: u64,
+        merkle_path: MerklePath,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = ([Column<Advice>; 5], Column<Instance>, MerklePoseidonConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let state = (0..3).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let partial_sbox = meta.advice_column();
+            let rc_a = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            meta.enable_constant(rc_b[0]);
+            let poseidon_config = PoseidonChip::configure::<poseidon::P128Pow5T3>(
+                meta,
+                state.try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            );
+
+            let merkle_config = MerklePoseidonChip::configure(meta, advices, poseidon_config);
+            (advices, instance, merkle_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, merkle_config) = config;
+            let address = assign_free_advice(
+                layouter.namespace(|| "witness address"),
+                advices[0],
+                Value::known(self.address),
+            )?;
+            let amount = assign_free_advice(
+                layouter.namespace(|| "witness amount"),
+                advices[1],
+                Value::known(pallas::Base::from(self.amount)),
+            )?;
+            let claimed = assign_free_advice(
+                layouter.namespace(|| "witness claimed"),
+                advices[2],
+                Value::known(pallas::Base::from(self.claimed)),
+            )?;
+            let snapshot_root = assign_free_instance(
+                layouter.namespace(|| "snapshot root"),
+                instance,
+                0,
+                advices[3],
+            )?;
+
+            airdrop_eligibility_gadget(
+                layouter.namespace(|| "airdrop eligibility"),
+                MerklePoseidonChip::construct(merkle_config),
+                &address,
+                &amount,
+                &claimed,
+                &snapshot_root,
+                &self.merkle_path.get_path(),
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    // A snapshot of four balances
+    let addresses: Vec<pallas::Base> = (0..4).map(|_| pallas::Base::random(&mut rng)).collect();
+    let amounts = [100u64, 250, 40, 1000];
+    let leaves: Vec<Node> = addresses
+        .iter()
+        .zip(amounts.iter())
+        .map(|(address, amount)| Node::from(snapshot_leaf(*address, *amount)))
+        .collect();
+    // The merkle path of the second entry
+    let merkle_path = MerklePath::from_path(vec![
+        (leaves[0], LR::L),
+        (Node::combine(&leaves[2], &leaves[3]), LR::R),
+    ]);
+    let snapshot_root = merkle_path.root(leaves[1]).inner();
+
+    // The entitled amount is claimed
+    let circuit = MyCircuit {
+        address: addresses[1],
+        amount: 250,
+        claimed: 250,
+        merkle_path: merkle_path.clone(),
+    };
+    let prover = MockProver::run(11, &circuit, vec![vec![snapshot_root]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // An over-claim, either of the claimed quantity or of the snapshot amount
+    for (amount, claimed) in [(250, 300), (300, 300)] {
+        let circuit = MyCircuit {
+            address: addresses[1],
+            amount,
+            claimed,
+            merkle_path: merkle_path.clone(),
+        };
+        let prover = MockProver::run(11, &circuit, vec![vec![snapshot_root]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // An address out of the snapshot
+    let circuit = MyCircuit {
+        address: pallas::Base::random(&mut rng),
+        amount: 250,
+        claimed: 250,
+        merkle_path,
+    };
+    let prover = MockProver::run(11, &circuit, vec![vec![snapshot_root]]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,187 @@
+/// Constrain value(allowance) = poseidon_hash(owner, spender), amount <= allowance and updated allowance = allowance - amount
+use crate::circuit::gadgets::{
+    coin_selection::u64_range_check,
+    poseidon_hash::poseidon_hash_gadget,
+    sub::{SubChip, SubInstructions},
+};
+use crate::circuit::vp_circuit::ResourceVariables;
+use crate::utils::poseidon_hash;
+use halo2_gadgets::{
+    poseidon::Pow5Config as PoseidonConfig, utilities::lookup_range_check::LookupRangeCheckConfig,
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// The value of the allowance resource the owner grants to the spender
+pub fn allowance_value(owner: pallas::Base, spender: pallas::Base) -> pallas::Base {
+    poseidon_hash(owner, spender)
+}
+
+// Check the spender spends the amount under the allowance of the owner. The quantity of the
+// allowance resource is the approved amount, and the resource is consumed and recreated with the
+// amount deducted.
+#[allow(clippy::too_many_arguments)]
+pub fn allowance_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    sub_chip: SubChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    owner: &AssignedCell<pallas::Base, pallas::Base>,
+    spender: &AssignedCell<pallas::Base, pallas::Base>,
+    allowance_input: &ResourceVariables,
+    allowance_output: &ResourceVariables,
+    amount: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let value = poseidon_hash_gadget(
+        poseidon_config,
+        layouter.namespace(|| "allowance value"),
+        [owner.clone(), spender.clone()],
+    )?;
+    layouter.assign_region(
+        || "check allowance resource",
+        |mut region| {
+            region.constrain_equal(allowance_input.value.cell(), value.cell())?;
+            region.constrain_equal(allowance_input.logic.cell(), allowance_output.logic.cell())?;
+            region.constrain_equal(allowance_input.label.cell(), allowance_output.label.cell())?;
+            region.constrain_equal(allowance_input.value.cell(), allowance_output.value.cell())
+        },
+    )?;
+
+    for quantity in [&allowance_input.quantity, amount] {
+        u64_range_check(
+            layouter.namespace(|| "quantity range check"),
+            lookup_config,
+            quantity,
+        )?;
+    }
+    // allowance - amount doesn't wrap around, so amount <= allowance
+    let remaining = sub_chip.sub(
+        layouter.namespace(|| "allowance - amount"),
+        &allowance_input.quantity,
+        amount,
+    )?;
+    u64_range_check(
+        layouter.namespace(|| "amount <= allowance"),
+        lookup_config,
+        &remaining,
+    )?;
+    layouter.assign_region(
+        || "check updated allowance",
+        |mut region| region.constrain_equal(remaining.cell(), allowance_output.quantity.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_allowance_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::resource::{tests::random_resource, Resource};
+    use halo2_proofs::{arithmetic::Field, circuit::Value, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        owner: pallas::Base,
+        spender: pallas::Base,
+        allowance_input: Resource,
+        allowance_output: Resource,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let advice = config.advices[0];
+            let owner = assign_free_advice(
+                layouter.namespace(|| "witness owner"),
+                advice,
+                Value::known(self.owner),
+            )?;
+            let spender = assign_free_advice(
+                layouter.namespace(|| "witness spender"),
+                advice,
+                Value::known(self.spender),
+            )?;
+            let mut witness = |resource: &Resource| -> Result<ResourceVariables, Error> {
+                let mut witness = |name: &'static str, value: pallas::Base| {
+                    assign_free_advice(layouter.namespace(|| name), advice, Value::known(value))
+                };
+                Ok(ResourceVariables {
+                    logic: witness("logic", resource.get_logic())?,
+                    label: witness("label", resource.get_label())?,
+                    quantity: witness("quantity", pallas::Base::from(resource.quantity))?,
+                    is_ephemeral: witness(
+                        "is_ephemeral",
+                        pallas::Base::from(resource.is_ephemeral),
+                    )?,
+                    value: witness("value", resource.value)?,
+                    nonce: witness("nonce", resource.nonce.inner())?,
+                    npk: witness("npk", resource.get_npk())?,
+                    rseed: witness("rseed", resource.rseed)?,
+                })
+            };
+            let allowance_input = witness(&self.allowance_input)?;
+            let allowance_output = witness(&self.allowance_output)?;
+            let amount = assign_free_instance(
+                layouter.namespace(|| "spend amount"),
+                config.instances,
+                0,
+                advice,
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            allowance_gadget(
+                layouter.namespace(|| "allowance"),
+                config.poseidon_config,
+                SubChip::construct(config.sub_config, ()),
+                resource_commit_chip.get_lookup_config(),
+                &owner,
+                &spender,
+                &allowance_input,
+                &allowance_output,
+                &amount,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let owner = pallas::Base::random(&mut rng);
+    let spender = pallas::Base::random(&mut rng);
+    let mut allowance_input = random_resource(&mut rng);
+    allowance_input.value = allowance_value(owner, spender);
+    allowance_input.quantity = 100;
+    let spend = |spender: pallas::Base, amount: u64, remaining: u64| {
+        let mut allowance_output = allowance_input;
+        allowance_output.quantity = remaining;
+        let circuit = GadgetTestCircuit(MyCircuit {
+            owner,
+            spender,
+            allowance_input,
+            allowance_output,
+        });
+        MockProver::run(11, &circuit, vec![vec![pallas::Base::from(amount)]])
+            .unwrap()
+            .verify()
+    };
+
+    // 40 of the approved 100 are spent
+    assert_eq!(spend(spender, 40, 60), Ok(()));
+    // The whole allowance is spent
+    assert_eq!(spend(spender, 100, 0), Ok(()));
+    // The allowance isn't decremented
+    assert!(spend(spender, 40, 100).is_err());
+    // The spend is above the allowance
+    assert!(spend(spender, 101, 0).is_err());
+    assert!(spend(spender, 101, u64::MAX).is_err());
+    // Another spender uses the allowance
+    assert!(spend(pallas::Base::random(&mut rng), 40, 60).is_err());
+}
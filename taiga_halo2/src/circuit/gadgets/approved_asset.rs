@@ -0,0 +1,168 @@
+/// Constrain the resource kind (logic, label) is a leaf of the approved asset tree
+use crate::circuit::gadgets::poseidon_hash::poseidon_hash_gadget;
+use crate::circuit::merkle_circuit::{merkle_poseidon_gadget, MerklePoseidonChip};
+use crate::merkle_tree::LR;
+use crate::utils::poseidon_hash;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// The leaf of an approved asset in the approved asset tree
+pub fn approved_asset_leaf(logic: pallas::Base, label: pallas::Base) -> pallas::Base {
+    poseidon_hash(logic, label)
+}
+
+// Check the asset of the resource is on the allowlist committed to approved_asset_root
+pub fn approved_asset_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    merkle_chip: MerklePoseidonChip,
+    logic: &AssignedCell<pallas::Base, pallas::Base>,
+    label: &AssignedCell<pallas::Base, pallas::Base>,
+    approved_asset_root: &AssignedCell<pallas::Base, pallas::Base>,
+    merkle_path: &[(pallas::Base, LR)],
+) -> Result<(), Error> {
+    let leaf = poseidon_hash_gadget(
+        merkle_chip.get_poseidon_config(),
+        layouter.namespace(|| "approved asset leaf"),
+        [logic.clone(), label.clone()],
+    )?;
+
+    let root = merkle_poseidon_gadget(
+        layouter.namespace(|| "approved asset membership"),
+        merkle_chip,
+        leaf,
+        merkle_path,
+    )?;
+
+    layouter.assign_region(
+        || "check approved asset root",
+        |mut region| region.constrain_equal(root.cell(), approved_asset_root.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_approved_asset_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::merkle_circuit::MerklePoseidonConfig;
+    use crate::merkle_tree::{MerklePath, Node};
+    use crate::resource::{tests::random_kind, ResourceKind};
+    use halo2_gadgets::poseidon::{primitives as poseidon, Pow5Chip as PoseidonChip};
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        kind: ResourceKind,
+        merkle_path: MerklePath,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = ([Column<Advice>; 5], Column<Instance>, MerklePoseidonConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let state = (0..3).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let partial_sbox = meta.advice_column();
+            let rc_a = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            meta.enable_constant(rc_b[0]);
+            let poseidon_config = PoseidonChip::configure::<poseidon::P128Pow5T3>(
+                meta,
+                state.try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            );
+
+            let merkle_config = MerklePoseidonChip::configure(meta, advices, poseidon_config);
+            (advices, instance, merkle_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, merkle_config) = config;
+            let logic = assign_free_advice(
+                layouter.namespace(|| "witness logic"),
+                advices[0],
+                Value::known(self.kind.logic),
+            )?;
+            let label = assign_free_advice(
+                layouter.namespace(|| "witness label"),
+                advices[1],
+                Value::known(self.kind.label),
+            )?;
+            let approved_asset_root = assign_free_instance(
+                layouter.namespace(|| "approved asset root"),
+                instance,
+                0,
+                advices[2],
+            )?;
+
+            approved_asset_gadget(
+                layouter.namespace(|| "approved asset"),
+                MerklePoseidonChip::construct(merkle_config),
+                &logic,
+                &label,
+                &approved_asset_root,
+                &self.merkle_path.get_path(),
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    // An allowlist of four approved assets
+    let approved_kinds: Vec<ResourceKind> = (0..4).map(|_| random_kind(&mut rng)).collect();
+    let leaves: Vec<Node> = approved_kinds
+        .iter()
+        .map(|kind| Node::from(approved_asset_leaf(kind.logic, kind.label)))
+        .collect();
+    // The merkle path of the second asset
+    let merkle_path = MerklePath::from_path(vec![
+        (leaves[0], LR::L),
+        (Node::combine(&leaves[2], &leaves[3]), LR::R),
+    ]);
+    let approved_asset_root = merkle_path.root(leaves[1]).inner();
+
+    // An approved asset
+    let circuit = MyCircuit {
+        kind: approved_kinds[1],
+        merkle_path: merkle_path.clone(),
+    };
+    let prover = MockProver::run(11, &circuit, vec![vec![approved_asset_root]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // An unapproved asset
+    let circuit = MyCircuit {
+        kind: random_kind(&mut rng),
+        merkle_path,
+    };
+    let prover = MockProver::run(11, &circuit, vec![vec![approved_asset_root]]).unwrap();
+    assert!(prover.verify().is_err());
+}
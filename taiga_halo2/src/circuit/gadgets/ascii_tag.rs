@@ -0,0 +1,211 @@
+/// Constrain data = sum(bytes[i] * 256^i) with N 7-bit bytes, of which the ones from len on are zero
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_advice, assign_free_constant,
+    conditional_equal::ConditionalEqualConfig,
+    mul::{MulChip, MulInstructions},
+};
+use ff::PrimeField;
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// Pack an ASCII tag into a field element, little-endian
+pub fn ascii_tag(tag: &str) -> pallas::Base {
+    assert!(tag.is_ascii() && tag.len() < 32);
+    let mut bytes = [0u8; 32];
+    bytes[..tag.len()].copy_from_slice(tag.as_bytes());
+    pallas::Base::from_repr(bytes).unwrap()
+}
+
+// Check the data is an ASCII tag of length len with at most N bytes
+#[allow(clippy::too_many_arguments)]
+pub fn ascii_tag_gadget<const N: usize>(
+    mut layouter: impl Layouter<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    conditional_equal_config: ConditionalEqualConfig,
+    add_chip: AddChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    advice: Column<Advice>,
+    data: &AssignedCell<pallas::Base, pallas::Base>,
+    len: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    assert!(N > 0 && N < 32);
+    let zero = assign_free_constant(
+        layouter.namespace(|| "constant zero"),
+        advice,
+        pallas::Base::zero(),
+    )?;
+    let one = assign_free_constant(
+        layouter.namespace(|| "constant one"),
+        advice,
+        pallas::Base::one(),
+    )?;
+    let byte_base = assign_free_constant(
+        layouter.namespace(|| "constant 256"),
+        advice,
+        pallas::Base::from(256u64),
+    )?;
+
+    let mut bytes = Vec::with_capacity(N);
+    let mut padding_flags: Vec<AssignedCell<pallas::Base, pallas::Base>> = Vec::with_capacity(N);
+    for i in 0..N {
+        // byte < 128
+        let byte = lookup_config.witness_short_check(
+            layouter.namespace(|| "7 bits range check"),
+            data.value()
+                .map(|data| pallas::Base::from(data.to_repr()[i] as u64)),
+            7,
+        )?;
+
+        // padding_flag = 1 if the byte is after the tag
+        let padding_flag = assign_free_advice(
+            layouter.namespace(|| "witness padding flag"),
+            advice,
+            len.value()
+                .map(|len| pallas::Base::from(i as u64 >= len.to_repr()[0] as u64)),
+        )?;
+        let flag_square = mul_chip.mul(
+            layouter.namespace(|| "padding_flag * padding_flag"),
+            &padding_flag,
+            &padding_flag,
+        )?;
+        layouter.assign_region(
+            || "bool_check padding flag",
+            |mut region| region.constrain_equal(flag_square.cell(), padding_flag.cell()),
+        )?;
+
+        layouter.assign_region(
+            || "zero padding",
+            |mut region| {
+                // padding_flag * byte = 0
+                conditional_equal_config.assign_region(
+                    &padding_flag,
+                    &byte,
+                    &zero,
+                    0,
+                    &mut region,
+                )?;
+                // The padding is contiguous: padding_flag[i - 1] * (padding_flag[i] - 1) = 0
+                if let Some(prev_flag) = padding_flags.last() {
+                    conditional_equal_config.assign_region(
+                        prev_flag,
+                        &padding_flag,
+                        &one,
+                        1,
+                        &mut region,
+                    )?;
+                }
+                Ok(())
+            },
+        )?;
+
+        bytes.push(byte);
+        padding_flags.push(padding_flag);
+    }
+
+    // len + sum(padding_flags) = N
+    let mut tag_len = len.clone();
+    for padding_flag in padding_flags.iter() {
+        tag_len = add_chip.add(
+            layouter.namespace(|| "count padding"),
+            &tag_len,
+            padding_flag,
+        )?;
+    }
+    layouter.assign_region(
+        || "check tag length",
+        |mut region| region.constrain_constant(tag_len.cell(), pallas::Base::from(N as u64)),
+    )?;
+
+    // data = sum(bytes[i] * 256^i)
+    let mut packed = bytes[N - 1].clone();
+    for byte in bytes.iter().rev().skip(1) {
+        let shifted = mul_chip.mul(layouter.namespace(|| "packed * 256"), &packed, &byte_base)?;
+        packed = add_chip.add(layouter.namespace(|| "packed + byte"), &shifted, byte)?;
+    }
+    layouter.assign_region(
+        || "check packed tag",
+        |mut region| region.constrain_equal(packed.cell(), data.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_ascii_tag_gadget() {
+    use crate::circuit::{
+        resource_commitment::ResourceCommitChip,
+        vp_circuit::{
+            testing::{GadgetTest, GadgetTestCircuit},
+            ValidityPredicateConfig,
+        },
+    };
+    use halo2_proofs::dev::MockProver;
+
+    const TAG_MAX_LEN: usize = 8;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        data: pallas::Base,
+        len: u64,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let data = assign_free_advice(
+                layouter.namespace(|| "witness data"),
+                config.advices[0],
+                Value::known(self.data),
+            )?;
+            let len = assign_free_advice(
+                layouter.namespace(|| "witness len"),
+                config.advices[0],
+                Value::known(pallas::Base::from(self.len)),
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            ascii_tag_gadget::<TAG_MAX_LEN>(
+                layouter.namespace(|| "ascii tag"),
+                resource_commit_chip.get_lookup_config(),
+                config.conditional_equal_config,
+                AddChip::construct(config.add_config, ()),
+                MulChip::construct(config.mul_config),
+                config.advices[0],
+                &data,
+                &len,
+            )
+        }
+    }
+
+    // "abc"
+    let circuit = GadgetTestCircuit(MyCircuit {
+        data: ascii_tag("abc"),
+        len: 3,
+    });
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The length doesn't cover the tag
+    let circuit = GadgetTestCircuit(MyCircuit {
+        data: ascii_tag("abc"),
+        len: 2,
+    });
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // A byte >= 128
+    let mut bytes = [0u8; 32];
+    bytes[..3].copy_from_slice(&[b'a', 200, b'c']);
+    let circuit = GadgetTestCircuit(MyCircuit {
+        data: pallas::Base::from_repr(bytes).unwrap(),
+        len: 3,
+    });
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,201 @@
+/// Constrain value = poseidon_hash(shared_secret, 0) and binding = poseidon_hash(shared_secret, 1)
+use crate::circuit::gadgets::{assign_free_constant, poseidon_hash::poseidon_hash_gadget};
+use crate::utils::poseidon_hash;
+use halo2_gadgets::poseidon::Pow5Config as PoseidonConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// The lock stored in the resource value of both halves of the linked transactions
+pub fn atomic_link_lock(shared_secret: pallas::Base) -> pallas::Base {
+    poseidon_hash(shared_secret, pallas::Base::zero())
+}
+
+// The public binding of each half. It's derived in another domain than the lock so that publishing
+// it doesn't reveal the resource value.
+pub fn atomic_link_binding(shared_secret: pallas::Base) -> pallas::Base {
+    poseidon_hash(shared_secret, pallas::Base::one())
+}
+
+// Check the resource is locked by the shared secret and publish the binding of the secret. The
+// verifier links two transactions by checking their bindings are equal.
+pub fn atomic_link_gadget(
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    mut layouter: impl Layouter<pallas::Base>,
+    advice: Column<Advice>,
+    shared_secret: &AssignedCell<pallas::Base, pallas::Base>,
+    value: &AssignedCell<pallas::Base, pallas::Base>,
+    binding: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let lock_domain = assign_free_constant(
+        layouter.namespace(|| "lock domain"),
+        advice,
+        pallas::Base::zero(),
+    )?;
+    let lock = poseidon_hash_gadget(
+        poseidon_config.clone(),
+        layouter.namespace(|| "atomic link lock"),
+        [shared_secret.clone(), lock_domain],
+    )?;
+    layouter.assign_region(
+        || "check atomic link lock",
+        |mut region| region.constrain_equal(lock.cell(), value.cell()),
+    )?;
+
+    let binding_domain = assign_free_constant(
+        layouter.namespace(|| "binding domain"),
+        advice,
+        pallas::Base::one(),
+    )?;
+    let expected_binding = poseidon_hash_gadget(
+        poseidon_config,
+        layouter.namespace(|| "atomic link binding"),
+        [shared_secret.clone(), binding_domain],
+    )?;
+    layouter.assign_region(
+        || "check atomic link binding",
+        |mut region| region.constrain_equal(expected_binding.cell(), binding.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_atomic_link_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use halo2_gadgets::poseidon::{primitives as poseidon, Pow5Chip as PoseidonChip};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    // One half of the linked transactions
+    #[derive(Default)]
+    struct MyCircuit {
+        shared_secret: pallas::Base,
+        value: pallas::Base,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        #[allow(clippy::type_complexity)]
+        type Config = (
+            [Column<Advice>; 4],
+            Column<Instance>,
+            PoseidonConfig<pallas::Base, 3, 2>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let lagrange_coeffs = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let poseidon_config = PoseidonChip::configure::<poseidon::P128Pow5T3>(
+                meta,
+                advices[1..4].try_into().unwrap(),
+                advices[0],
+                lagrange_coeffs[0..3].try_into().unwrap(),
+                lagrange_coeffs[3..6].try_into().unwrap(),
+            );
+            (advices, instance, poseidon_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, poseidon_config) = config;
+            let shared_secret = assign_free_advice(
+                layouter.namespace(|| "witness shared secret"),
+                advices[0],
+                Value::known(self.shared_secret),
+            )?;
+            let value = assign_free_advice(
+                layouter.namespace(|| "witness value"),
+                advices[0],
+                Value::known(self.value),
+            )?;
+            let binding = assign_free_instance(
+                layouter.namespace(|| "public binding"),
+                instance,
+                0,
+                advices[0],
+            )?;
+
+            atomic_link_gadget(
+                poseidon_config,
+                layouter.namespace(|| "atomic link"),
+                advices[0],
+                &shared_secret,
+                &value,
+                &binding,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let shared_secret = pallas::Base::random(&mut rng);
+    let lock = atomic_link_lock(shared_secret);
+    let binding = atomic_link_binding(shared_secret);
+
+    // Both halves reference the same secret and publish the same binding
+    let first_half = MyCircuit {
+        shared_secret,
+        value: lock,
+    };
+    let prover = MockProver::run(8, &first_half, vec![vec![binding]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    let second_half = MyCircuit {
+        shared_secret,
+        value: lock,
+    };
+    let prover = MockProver::run(8, &second_half, vec![vec![binding]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The second half is locked by another secret, so it can't match the binding of the first half
+    let other_secret = pallas::Base::random(&mut rng);
+    assert_ne!(atomic_link_binding(other_secret), binding);
+    let second_half = MyCircuit {
+        shared_secret: other_secret,
+        value: atomic_link_lock(other_secret),
+    };
+    let prover = MockProver::run(8, &second_half, vec![vec![binding]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The lock doesn't belong to the secret
+    let second_half = MyCircuit {
+        shared_secret,
+        value: atomic_link_lock(other_secret),
+    };
+    let prover = MockProver::run(8, &second_half, vec![vec![binding]]).unwrap();
+    assert!(prover.verify().is_err());
+}
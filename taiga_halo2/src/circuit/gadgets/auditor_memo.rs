@@ -0,0 +1,197 @@
+/// Constrain the public ciphertext encrypts (cm, memo) to the auditor key
+use crate::circuit::{
+    gadgets::add::AddChip, resource_encryption_circuit::resource_encryption_gadget,
+};
+use crate::constant::{TaigaFixedBases, RESOURCE_ENCRYPTION_PLAINTEXT_NUM};
+use crate::resource_encryption::{ResourceCiphertext, ResourcePlaintext, SecretKey};
+use crate::utils::mod_r_p;
+use halo2_gadgets::{
+    ecc::{chip::EccChip, NonIdentityPoint},
+    poseidon::Pow5Config as PoseidonConfig,
+};
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error, Instance},
+};
+use pasta_curves::{
+    group::{Curve, Group},
+    pallas,
+};
+
+// The resource encryption public inputs of the memo encrypted to the auditor key: the ciphertext
+// followed by the sender's pk. The first plaintext element is the output resource commitment.
+pub fn encrypt_auditor_memo(
+    cm: pallas::Base,
+    memo: &[pallas::Base],
+    auditor_pk: &pallas::Point,
+    sender_sk: pallas::Base,
+    encrypt_nonce: pallas::Base,
+) -> Vec<pallas::Base> {
+    assert!(memo.len() < RESOURCE_ENCRYPTION_PLAINTEXT_NUM);
+    let mut message = vec![cm];
+    message.extend_from_slice(memo);
+    let plaintext = ResourcePlaintext::padding(&message);
+    let key = SecretKey::from_dh_exchange(auditor_pk, &mod_r_p(sender_sk));
+    let cipher = ResourceCiphertext::encrypt(&plaintext, &key, &encrypt_nonce);
+
+    let sender_pk = pallas::Point::generator() * mod_r_p(sender_sk);
+    let sender_pk_coord = sender_pk.to_affine().coordinates().unwrap();
+    let mut public_inputs = cipher.inner().to_vec();
+    public_inputs.push(*sender_pk_coord.x());
+    public_inputs.push(*sender_pk_coord.y());
+    public_inputs
+}
+
+// Check the ciphertext in the resource encryption public inputs is the memo encrypted to the
+// auditor key. The commitment is encrypted along with the memo, so the memo can't be replayed on
+// another output resource.
+#[allow(clippy::too_many_arguments)]
+pub fn auditor_memo_gadget(
+    layouter: impl Layouter<pallas::Base>,
+    advice: Column<Advice>,
+    instances: Column<Instance>,
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    add_chip: AddChip<pallas::Base>,
+    ecc_chip: EccChip<TaigaFixedBases>,
+    encrypt_nonce: AssignedCell<pallas::Base, pallas::Base>,
+    sender_sk: AssignedCell<pallas::Base, pallas::Base>,
+    auditor_pk: NonIdentityPoint<pallas::Affine, EccChip<TaigaFixedBases>>,
+    cm: &AssignedCell<pallas::Base, pallas::Base>,
+    memo: &[AssignedCell<pallas::Base, pallas::Base>],
+) -> Result<(), Error> {
+    assert!(memo.len() < RESOURCE_ENCRYPTION_PLAINTEXT_NUM);
+    let mut message = vec![cm.clone()];
+    message.extend_from_slice(memo);
+    resource_encryption_gadget(
+        layouter,
+        advice,
+        instances,
+        poseidon_config,
+        add_chip,
+        ecc_chip,
+        encrypt_nonce,
+        sender_sk,
+        auditor_pk,
+        &mut message,
+    )
+}
+
+#[test]
+fn test_halo2_auditor_memo_gadget() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::constant::{
+        VP_CIRCUIT_PUBLIC_INPUT_NUM, VP_CIRCUIT_RESOURCE_ENCRYPTION_PUBLIC_INPUT_BEGIN_IDX,
+    };
+    use halo2_proofs::{arithmetic::Field, circuit::Value, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        cm: pallas::Base,
+        memo: Vec<pallas::Base>,
+        auditor_pk: pallas::Point,
+        sender_sk: pallas::Base,
+        encrypt_nonce: pallas::Base,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let cm = assign_free_advice(
+                layouter.namespace(|| "witness cm"),
+                config.advices[0],
+                Value::known(self.cm),
+            )?;
+            let memo = self
+                .memo
+                .iter()
+                .map(|word| {
+                    assign_free_advice(
+                        layouter.namespace(|| "witness memo"),
+                        config.advices[0],
+                        Value::known(*word),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let encrypt_nonce = assign_free_advice(
+                layouter.namespace(|| "witness encrypt nonce"),
+                config.advices[0],
+                Value::known(self.encrypt_nonce),
+            )?;
+            let sender_sk = assign_free_advice(
+                layouter.namespace(|| "witness sender sk"),
+                config.advices[0],
+                Value::known(self.sender_sk),
+            )?;
+            let ecc_chip = EccChip::construct(config.ecc_config);
+            let auditor_pk = NonIdentityPoint::new(
+                ecc_chip.clone(),
+                layouter.namespace(|| "witness auditor pk"),
+                Value::known(self.auditor_pk.to_affine()),
+            )?;
+
+            auditor_memo_gadget(
+                layouter.namespace(|| "auditor memo"),
+                config.advices[0],
+                config.instances,
+                config.poseidon_config,
+                AddChip::construct(config.add_config, ()),
+                ecc_chip,
+                encrypt_nonce,
+                sender_sk,
+                auditor_pk,
+                &cm,
+                &memo,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let auditor_sk = pallas::Scalar::random(&mut rng);
+    let auditor_pk = pallas::Point::generator() * auditor_sk;
+    let sender_sk = pallas::Base::random(&mut rng);
+    let encrypt_nonce = pallas::Base::random(&mut rng);
+    let cm = pallas::Base::random(&mut rng);
+    let memo = vec![
+        pallas::Base::random(&mut rng),
+        pallas::Base::random(&mut rng),
+    ];
+    let circuit = GadgetTestCircuit(MyCircuit {
+        cm,
+        memo: memo.clone(),
+        auditor_pk,
+        sender_sk,
+        encrypt_nonce,
+    });
+    let public_inputs = |cipher: Vec<pallas::Base>| {
+        let mut public_inputs = vec![pallas::Base::zero(); VP_CIRCUIT_PUBLIC_INPUT_NUM];
+        public_inputs[VP_CIRCUIT_RESOURCE_ENCRYPTION_PUBLIC_INPUT_BEGIN_IDX..]
+            .copy_from_slice(&cipher);
+        public_inputs
+    };
+
+    // The auditor decrypts the memo along with the commitment it is bound to
+    let cipher = encrypt_auditor_memo(cm, &memo, &auditor_pk, sender_sk, encrypt_nonce);
+    let sender_pk = pallas::Point::generator() * mod_r_p(sender_sk);
+    let auditor_key = SecretKey::from_dh_exchange(&sender_pk, &auditor_sk);
+    let ciphertext = ResourceCiphertext::from(cipher[..cipher.len() - 2].to_vec());
+    let plaintext = ciphertext.decrypt(&auditor_key).unwrap();
+    assert_eq!(plaintext[0], cm);
+    assert_eq!(plaintext[1..3], memo[..]);
+    let prover = MockProver::run(12, &circuit, vec![public_inputs(cipher)]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The memo is bound to another commitment
+    let other_cm = pallas::Base::random(&mut rng);
+    let cipher = encrypt_auditor_memo(other_cm, &memo, &auditor_pk, sender_sk, encrypt_nonce);
+    let prover = MockProver::run(12, &circuit, vec![public_inputs(cipher)]).unwrap();
+    assert!(prover.verify().is_err());
+}
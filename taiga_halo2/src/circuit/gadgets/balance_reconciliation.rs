@@ -0,0 +1,161 @@
+/// Constrain value_commitment = kind * balance + rcv * R for each asset
+use crate::constant::{TaigaFixedBases, TaigaFixedBasesFull, RESOURCE_COMMITMENT_R_GENERATOR};
+use crate::utils::mod_r_p;
+use halo2_gadgets::ecc::{
+    chip::EccChip, FixedPoint, NonIdentityPoint, Point, ScalarFixed, ScalarVar,
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::Error,
+};
+use pasta_curves::group::Curve;
+use pasta_curves::pallas;
+
+// The value commitment of one asset in the shielded pool
+pub fn value_commitment(
+    kind: pallas::Point,
+    balance: pallas::Base,
+    rcv: pallas::Scalar,
+) -> pallas::Point {
+    kind * mod_r_p(balance) + RESOURCE_COMMITMENT_R_GENERATOR.to_curve() * rcv
+}
+
+// Check that every per-asset value commitment opens to the corresponding transparent balance
+pub fn balance_reconciliation_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    ecc_chip: EccChip<TaigaFixedBases>,
+    value_commitments: &[Point<pallas::Affine, EccChip<TaigaFixedBases>>],
+    kinds: &[NonIdentityPoint<pallas::Affine, EccChip<TaigaFixedBases>>],
+    // The transparent balances are expected to be public inputs
+    transparent_balances: &[AssignedCell<pallas::Base, pallas::Base>],
+    rcvs: &[pallas::Scalar],
+) -> Result<(), Error> {
+    assert_eq!(value_commitments.len(), kinds.len());
+    assert_eq!(value_commitments.len(), transparent_balances.len());
+    assert_eq!(value_commitments.len(), rcvs.len());
+
+    for (((value_commitment, kind), balance), rcv) in value_commitments
+        .iter()
+        .zip(kinds.iter())
+        .zip(transparent_balances.iter())
+        .zip(rcvs.iter())
+    {
+        let balance_scalar = ScalarVar::from_base(
+            ecc_chip.clone(),
+            layouter.namespace(|| "ScalarVar from_base"),
+            balance,
+        )?;
+        let (value_point, _) = kind.mul(layouter.namespace(|| "value point"), balance_scalar)?;
+
+        // blind point
+        let blind_scalar = ScalarFixed::new(
+            ecc_chip.clone(),
+            layouter.namespace(|| "blind scalar"),
+            Value::known(*rcv),
+        )?;
+        let blind_base =
+            FixedPoint::from_inner(ecc_chip.clone(), TaigaFixedBasesFull::ResourceCommitmentR);
+        let (blind, _) = blind_base.mul(
+            layouter.namespace(|| "blind_scalar * blind_base"),
+            &blind_scalar,
+        )?;
+
+        let expected_commitment =
+            value_point.add(layouter.namespace(|| "expected value commitment"), &blind)?;
+        expected_commitment.constrain_equal(
+            layouter.namespace(|| "reconcile value commitment"),
+            value_commitment,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_halo2_balance_reconciliation_gadget() {
+    use crate::circuit::gadgets::assign_free_instance;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{arithmetic::Field, dev::MockProver};
+    use pasta_curves::group::Group;
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        kinds: Vec<pallas::Point>,
+        value_commitments: Vec<pallas::Point>,
+        rcvs: Vec<pallas::Scalar>,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let ecc_chip = EccChip::construct(config.ecc_config);
+            let mut value_commitments = vec![];
+            let mut kinds = vec![];
+            let mut transparent_balances = vec![];
+            for (i, (kind, value_commitment)) in self
+                .kinds
+                .iter()
+                .zip(self.value_commitments.iter())
+                .enumerate()
+            {
+                kinds.push(NonIdentityPoint::new(
+                    ecc_chip.clone(),
+                    layouter.namespace(|| "witness kind"),
+                    Value::known(kind.to_affine()),
+                )?);
+                value_commitments.push(Point::new(
+                    ecc_chip.clone(),
+                    layouter.namespace(|| "witness value commitment"),
+                    Value::known(value_commitment.to_affine()),
+                )?);
+                transparent_balances.push(assign_free_instance(
+                    layouter.namespace(|| "transparent balance"),
+                    config.instances,
+                    i,
+                    config.advices[0],
+                )?);
+            }
+
+            balance_reconciliation_gadget(
+                layouter.namespace(|| "balance reconciliation"),
+                ecc_chip,
+                &value_commitments,
+                &kinds,
+                &transparent_balances,
+                &self.rcvs,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let kinds: Vec<pallas::Point> = (0..2).map(|_| pallas::Point::random(&mut rng)).collect();
+    let balances = [pallas::Base::from(100u64), pallas::Base::from(37u64)];
+    let rcvs: Vec<pallas::Scalar> = (0..2).map(|_| pallas::Scalar::random(&mut rng)).collect();
+    let value_commitments = kinds
+        .iter()
+        .zip(balances.iter())
+        .zip(rcvs.iter())
+        .map(|((kind, balance), rcv)| value_commitment(*kind, *balance, *rcv))
+        .collect();
+    let circuit = GadgetTestCircuit(MyCircuit {
+        kinds,
+        value_commitments,
+        rcvs,
+    });
+
+    // The shielded commitments match the transparent summary
+    let prover = MockProver::run(12, &circuit, vec![balances.to_vec()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Shielded/transparent mismatch
+    let mismatched_balances = vec![balances[0], balances[1] + pallas::Base::one()];
+    let prover = MockProver::run(12, &circuit, vec![mismatched_balances]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,110 @@
+/// Constrain the change output resource is owned by the sender: value(change) = value(spent)
+use crate::circuit::vp_circuit::ResourceVariables;
+use halo2_proofs::{circuit::Layouter, plonk::Error};
+use pasta_curves::pallas;
+
+// Check the designated change output returns to the owner of the spent resource
+pub fn change_to_sender_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    spent: &ResourceVariables,
+    change_output: &ResourceVariables,
+) -> Result<(), Error> {
+    layouter.assign_region(
+        || "check change owner",
+        |mut region| region.constrain_equal(change_output.value.cell(), spent.value.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_change_to_sender_gadget() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::resource::{tests::random_resource, Resource};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        spent: Resource,
+        change_output: Resource,
+    }
+
+    fn assign_resource(
+        mut layouter: impl Layouter<pallas::Base>,
+        advice: Column<Advice>,
+        resource: &Resource,
+    ) -> Result<ResourceVariables, Error> {
+        let mut witness = |name: &'static str, value: pallas::Base| {
+            assign_free_advice(layouter.namespace(|| name), advice, Value::known(value))
+        };
+        Ok(ResourceVariables {
+            logic: witness("logic", resource.get_logic())?,
+            label: witness("label", resource.get_label())?,
+            quantity: witness("quantity", pallas::Base::from(resource.quantity))?,
+            is_ephemeral: witness("is_ephemeral", pallas::Base::from(resource.is_ephemeral))?,
+            value: witness("value", resource.value)?,
+            nonce: witness("nonce", resource.nonce.inner())?,
+            npk: witness("npk", resource.get_npk())?,
+            rseed: witness("rseed", resource.rseed)?,
+        })
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advice = meta.advice_column();
+            meta.enable_equality(advice);
+            advice
+        }
+
+        fn synthesize(
+            &self,
+            advice: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let spent = assign_resource(layouter.namespace(|| "spent"), advice, &self.spent)?;
+            let change_output = assign_resource(
+                layouter.namespace(|| "change output"),
+                advice,
+                &self.change_output,
+            )?;
+            change_to_sender_gadget(
+                layouter.namespace(|| "change to sender"),
+                &spent,
+                &change_output,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let spent = random_resource(&mut rng);
+    let mut change_output = random_resource(&mut rng);
+    change_output.value = spent.value;
+
+    // The change returns to the sender
+    let circuit = MyCircuit {
+        spent,
+        change_output,
+    };
+    let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The change is routed to another owner
+    change_output.value = pallas::Base::random(&mut rng);
+    let circuit = MyCircuit {
+        spent,
+        change_output,
+    };
+    let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,190 @@
+/// Constrain a signed channel update (channel_id, new_balance, seq): output_value = new_balance and seq = prev_seq + 1
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_constant,
+    poseidon_hash::poseidon_hash_gadget,
+    schnorr_verify::{schnorr_verify_gadget, SchnorrSignature},
+};
+use crate::constant::TaigaFixedBases;
+use crate::utils::poseidon_hash_n;
+use halo2_gadgets::{
+    ecc::{chip::EccChip, NonIdentityPoint},
+    poseidon::Pow5Config as PoseidonConfig,
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// The message signed by the channel counterparty
+pub fn channel_update_message(
+    channel_id: pallas::Base,
+    new_balance: pallas::Base,
+    seq: pallas::Base,
+) -> pallas::Base {
+    poseidon_hash_n([channel_id, new_balance, seq])
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn channel_update_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    ecc_chip: EccChip<TaigaFixedBases>,
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    add_chip: AddChip<pallas::Base>,
+    advice: Column<Advice>,
+    pk: &NonIdentityPoint<pallas::Affine, EccChip<TaigaFixedBases>>,
+    signature: &SchnorrSignature,
+    channel_id: &AssignedCell<pallas::Base, pallas::Base>,
+    new_balance: &AssignedCell<pallas::Base, pallas::Base>,
+    seq: &AssignedCell<pallas::Base, pallas::Base>,
+    prev_seq: &AssignedCell<pallas::Base, pallas::Base>,
+    output_value: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    // Verify the signature over the channel update
+    let message = poseidon_hash_gadget(
+        poseidon_config.clone(),
+        layouter.namespace(|| "channel update message"),
+        [channel_id.clone(), new_balance.clone(), seq.clone()],
+    )?;
+    schnorr_verify_gadget(
+        layouter.namespace(|| "verify channel update signature"),
+        ecc_chip,
+        poseidon_config,
+        pk,
+        signature,
+        message,
+    )?;
+
+    // The output resource reflects the new balance
+    layouter.assign_region(
+        || "check output value",
+        |mut region| region.constrain_equal(output_value.cell(), new_balance.cell()),
+    )?;
+
+    // The sequence number increments, so an old update can't be replayed
+    let one = assign_free_constant(
+        layouter.namespace(|| "constant one"),
+        advice,
+        pallas::Base::one(),
+    )?;
+    let next_seq = add_chip.add(layouter.namespace(|| "prev_seq + 1"), prev_seq, &one)?;
+    layouter.assign_region(
+        || "check seq increment",
+        |mut region| region.constrain_equal(next_seq.cell(), seq.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_channel_update_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{arithmetic::Field, circuit::Value, dev::MockProver};
+    use pasta_curves::group::Curve;
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        signature: SchnorrSignature,
+        new_balance: pallas::Base,
+        seq: pallas::Base,
+        output_value: pallas::Base,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let ecc_chip = EccChip::construct(config.ecc_config);
+            let pk = NonIdentityPoint::new(
+                ecc_chip.clone(),
+                layouter.namespace(|| "witness pk"),
+                Value::known(self.signature.pk.to_affine()),
+            )?;
+            let channel_id = assign_free_instance(
+                layouter.namespace(|| "channel id"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+            let prev_seq = assign_free_instance(
+                layouter.namespace(|| "prev seq"),
+                config.instances,
+                1,
+                config.advices[0],
+            )?;
+            let new_balance = assign_free_advice(
+                layouter.namespace(|| "witness new balance"),
+                config.advices[0],
+                Value::known(self.new_balance),
+            )?;
+            let seq = assign_free_advice(
+                layouter.namespace(|| "witness seq"),
+                config.advices[0],
+                Value::known(self.seq),
+            )?;
+            let output_value = assign_free_advice(
+                layouter.namespace(|| "witness output value"),
+                config.advices[0],
+                Value::known(self.output_value),
+            )?;
+
+            channel_update_gadget(
+                layouter.namespace(|| "channel update"),
+                ecc_chip,
+                config.poseidon_config,
+                AddChip::construct(config.add_config, ()),
+                config.advices[0],
+                &pk,
+                &self.signature,
+                &channel_id,
+                &new_balance,
+                &seq,
+                &prev_seq,
+                &output_value,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let sk = pallas::Scalar::random(&mut rng);
+    let channel_id = pallas::Base::random(&mut rng);
+    let new_balance = pallas::Base::from(42u64);
+    let prev_seq = pallas::Base::from(7u64);
+
+    // A valid update
+    let seq = prev_seq + pallas::Base::one();
+    let signature = SchnorrSignature::sign(
+        &mut rng,
+        sk,
+        channel_update_message(channel_id, new_balance, seq),
+    );
+    let circuit = GadgetTestCircuit(MyCircuit {
+        signature,
+        new_balance,
+        seq,
+        output_value: new_balance,
+    });
+    let prover = MockProver::run(12, &circuit, vec![vec![channel_id, prev_seq]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A replayed update whose seq doesn't increment
+    let signature = SchnorrSignature::sign(
+        &mut rng,
+        sk,
+        channel_update_message(channel_id, new_balance, prev_seq),
+    );
+    let circuit = GadgetTestCircuit(MyCircuit {
+        signature,
+        new_balance,
+        seq: prev_seq,
+        output_value: new_balance,
+    });
+    let prover = MockProver::run(12, &circuit, vec![vec![channel_id, prev_seq]]).unwrap();
+    assert!(prover.verify().is_err());
+}
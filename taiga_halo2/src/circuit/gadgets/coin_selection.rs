@@ -0,0 +1,138 @@
+/// Constrain sum(selected_quantities) = amount + change, with all the terms in 64 bits
+use crate::circuit::gadgets::add::{AddChip, AddInstructions};
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// Check the cell is a 64-bit value
+pub fn u64_range_check(
+    mut layouter: impl Layouter<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    value: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let zs = lookup_config.copy_check(
+        layouter.namespace(|| "6 * K(10) bits range check"),
+        value.clone(),
+        6,
+        false,
+    )?;
+    lookup_config.copy_short_check(
+        layouter.namespace(|| "4 bits range check"),
+        zs[6].clone(),
+        4,
+    )
+}
+
+// Check the selected inputs cover the requested amount and the change exactly. The range checks
+// keep the sums far from the field modulus so that an understated change can't wrap around.
+pub fn coin_selection_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    selected_quantities: &[AssignedCell<pallas::Base, pallas::Base>],
+    amount: &AssignedCell<pallas::Base, pallas::Base>,
+    change: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    assert!(!selected_quantities.is_empty());
+    for quantity in selected_quantities.iter().chain([amount, change]) {
+        u64_range_check(
+            layouter.namespace(|| "quantity range check"),
+            lookup_config,
+            quantity,
+        )?;
+    }
+
+    let mut selected_sum = selected_quantities[0].clone();
+    for quantity in selected_quantities.iter().skip(1) {
+        selected_sum = add_chip.add(
+            layouter.namespace(|| "sum selected quantities"),
+            &selected_sum,
+            quantity,
+        )?;
+    }
+    let target = add_chip.add(layouter.namespace(|| "amount + change"), amount, change)?;
+
+    layouter.assign_region(
+        || "check coin selection",
+        |mut region| region.constrain_equal(selected_sum.cell(), target.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_coin_selection_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+
+    #[derive(Default)]
+    struct MyCircuit {
+        selected_quantities: Vec<u64>,
+        change: u64,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let selected_quantities = self
+                .selected_quantities
+                .iter()
+                .map(|quantity| {
+                    assign_free_advice(
+                        layouter.namespace(|| "witness selected quantity"),
+                        config.advices[0],
+                        Value::known(pallas::Base::from(*quantity)),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let amount = assign_free_instance(
+                layouter.namespace(|| "requested amount"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+            let change = assign_free_advice(
+                layouter.namespace(|| "witness change"),
+                config.advices[0],
+                Value::known(pallas::Base::from(self.change)),
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            coin_selection_gadget(
+                layouter.namespace(|| "coin selection"),
+                AddChip::construct(config.add_config, ()),
+                resource_commit_chip.get_lookup_config(),
+                &selected_quantities,
+                &amount,
+                &change,
+            )
+        }
+    }
+
+    let amount = pallas::Base::from(70u64);
+
+    // 30 + 50 = 70 + 10
+    let circuit = GadgetTestCircuit(MyCircuit {
+        selected_quantities: vec![30, 50],
+        change: 10,
+    });
+    let prover = MockProver::run(11, &circuit, vec![vec![amount]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The change is understated
+    let circuit = GadgetTestCircuit(MyCircuit {
+        selected_quantities: vec![30, 50],
+        change: 5,
+    });
+    let prover = MockProver::run(11, &circuit, vec![vec![amount]]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,198 @@
+/// Constrain resource_commit(resource) = cm
+use crate::circuit::{
+    gadgets::{assign_free_advice, assign_free_constant, poseidon_hash::poseidon_hash_gadget},
+    integrity::quantity_range_check,
+    resource_commitment::{resource_commit, ResourceCommitChip},
+};
+use crate::resource::Resource;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// Recompute the commitment of a witnessed resource and check it against an externally-provided commitment
+pub fn commitment_matches_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    advice: Column<Advice>,
+    resource_commit_chip: ResourceCommitChip,
+    resource: &Resource,
+    cm: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let expected_cm = witness_resource_commitment(
+        layouter.namespace(|| "witness resource"),
+        advice,
+        resource_commit_chip,
+        resource,
+        None,
+        None,
+        None,
+    )?;
+
+    layouter.assign_region(
+        || "check resource commitment",
+        |mut region| region.constrain_equal(expected_cm.cell(), cm.cell()),
+    )
+}
+
+// Witness the resource and compute its commitment. The logic, label and value are circuit
+// constants when given, and witnessed from the resource otherwise.
+pub fn witness_resource_commitment(
+    mut layouter: impl Layouter<pallas::Base>,
+    advice: Column<Advice>,
+    resource_commit_chip: ResourceCommitChip,
+    resource: &Resource,
+    logic: Option<pallas::Base>,
+    label: Option<pallas::Base>,
+    value: Option<pallas::Base>,
+) -> Result<AssignedCell<pallas::Base, pallas::Base>, Error> {
+    // npk = Com_r(nk, zero) if the nk is known, otherwise witness the npk directly
+    let npk = match resource.get_nk() {
+        Some(nk) => {
+            let nk_var = assign_free_advice(
+                layouter.namespace(|| "witness nk"),
+                advice,
+                Value::known(nk),
+            )?;
+            let zero_constant = assign_free_constant(
+                layouter.namespace(|| "constant zero"),
+                advice,
+                pallas::Base::zero(),
+            )?;
+            poseidon_hash_gadget(
+                resource_commit_chip.get_poseidon_config(),
+                layouter.namespace(|| "npk encoding"),
+                [nk_var, zero_constant],
+            )?
+        }
+        None => assign_free_advice(
+            layouter.namespace(|| "witness npk"),
+            advice,
+            Value::known(resource.get_npk()),
+        )?,
+    };
+
+    let logic = assign_constant_or_advice(
+        layouter.namespace(|| "logic"),
+        advice,
+        logic,
+        resource.get_logic(),
+    )?;
+    let label = assign_constant_or_advice(
+        layouter.namespace(|| "label"),
+        advice,
+        label,
+        resource.get_label(),
+    )?;
+    let value = assign_constant_or_advice(
+        layouter.namespace(|| "value"),
+        advice,
+        value,
+        resource.value,
+    )?;
+    let quantity = quantity_range_check(
+        layouter.namespace(|| "quantity range check"),
+        resource_commit_chip.get_lookup_config(),
+        resource.quantity,
+    )?;
+    let nonce = assign_free_advice(
+        layouter.namespace(|| "witness nonce"),
+        advice,
+        Value::known(resource.nonce.inner()),
+    )?;
+    let psi = assign_free_advice(
+        layouter.namespace(|| "witness psi"),
+        advice,
+        Value::known(resource.get_psi()),
+    )?;
+    let rcm = assign_free_advice(
+        layouter.namespace(|| "witness rcm"),
+        advice,
+        Value::known(resource.get_rcm()),
+    )?;
+    let is_ephemeral = assign_free_advice(
+        layouter.namespace(|| "witness is_ephemeral"),
+        advice,
+        Value::known(pallas::Base::from(resource.is_ephemeral)),
+    )?;
+
+    resource_commit(
+        layouter.namespace(|| "resource commitment"),
+        resource_commit_chip,
+        logic,
+        label,
+        value,
+        npk,
+        nonce,
+        psi,
+        quantity,
+        is_ephemeral,
+        rcm,
+    )
+}
+
+fn assign_constant_or_advice(
+    layouter: impl Layouter<pallas::Base>,
+    advice: Column<Advice>,
+    constant: Option<pallas::Base>,
+    witness: pallas::Base,
+) -> Result<AssignedCell<pallas::Base, pallas::Base>, Error> {
+    match constant {
+        Some(constant) => assign_free_constant(layouter, advice, constant),
+        None => assign_free_advice(layouter, advice, Value::known(witness)),
+    }
+}
+
+#[test]
+fn test_halo2_commitment_matches_gadget() {
+    use crate::circuit::gadgets::assign_free_instance;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::resource::tests::random_resource;
+    use halo2_proofs::dev::MockProver;
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        resource: Resource,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let cm = assign_free_instance(
+                layouter.namespace(|| "public cm"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+
+            commitment_matches_gadget(
+                layouter.namespace(|| "commitment matches"),
+                config.advices[0],
+                ResourceCommitChip::construct(config.resource_commit_config),
+                &self.resource,
+                &cm,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let resource = random_resource(&mut rng);
+    let circuit = GadgetTestCircuit(MyCircuit { resource });
+
+    // The public commitment matches the resource
+    let cm = resource.commitment().inner();
+    let prover = MockProver::run(11, &circuit, vec![vec![cm]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The public commitment belongs to another resource
+    let other_cm = random_resource(&mut rng).commitment().inner();
+    let prover = MockProver::run(11, &circuit, vec![vec![other_cm]]).unwrap();
+    assert!(prover.verify().is_err());
+}
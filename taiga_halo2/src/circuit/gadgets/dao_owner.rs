@@ -0,0 +1,142 @@
+/// Constrain owner = poseidon_hash(governance_root)
+use crate::circuit::gadgets::poseidon_hash::poseidon_hash_gadget;
+use crate::utils::poseidon_hash_n;
+use halo2_gadgets::poseidon::Pow5Config as PoseidonConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// The owner address of a DAO-governed resource, derived from the state root of the governance contract
+pub fn dao_owner(governance_root: pallas::Base) -> pallas::Base {
+    poseidon_hash_n([governance_root])
+}
+
+// Check the resource owner is derived from the governance root
+pub fn dao_owner_gadget(
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    mut layouter: impl Layouter<pallas::Base>,
+    owner: &AssignedCell<pallas::Base, pallas::Base>,
+    governance_root: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let expected_owner = poseidon_hash_gadget(
+        poseidon_config,
+        layouter.namespace(|| "derive dao owner"),
+        [governance_root.clone()],
+    )?;
+
+    layouter.assign_region(
+        || "check dao owner",
+        |mut region| region.constrain_equal(expected_owner.cell(), owner.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_dao_owner_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use halo2_gadgets::poseidon::{primitives as poseidon, Pow5Chip as PoseidonChip};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        owner: pallas::Base,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        #[allow(clippy::type_complexity)]
+        type Config = (
+            [Column<Advice>; 4],
+            Column<Instance>,
+            PoseidonConfig<pallas::Base, 3, 2>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let lagrange_coeffs = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let poseidon_config = PoseidonChip::configure::<poseidon::P128Pow5T3>(
+                meta,
+                advices[1..4].try_into().unwrap(),
+                advices[0],
+                lagrange_coeffs[0..3].try_into().unwrap(),
+                lagrange_coeffs[3..6].try_into().unwrap(),
+            );
+            (advices, instance, poseidon_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, poseidon_config) = config;
+            let owner = assign_free_advice(
+                layouter.namespace(|| "witness owner"),
+                advices[0],
+                Value::known(self.owner),
+            )?;
+            let governance_root = assign_free_instance(
+                layouter.namespace(|| "governance root"),
+                instance,
+                0,
+                advices[1],
+            )?;
+
+            dao_owner_gadget(
+                poseidon_config,
+                layouter.namespace(|| "dao owner"),
+                &owner,
+                &governance_root,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let governance_root = pallas::Base::random(&mut rng);
+    let circuit = MyCircuit {
+        owner: dao_owner(governance_root),
+    };
+
+    // A matching governance root
+    let prover = MockProver::run(8, &circuit, vec![vec![governance_root]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A stale governance root
+    let stale_root = pallas::Base::random(&mut rng);
+    let prover = MockProver::run(8, &circuit, vec![vec![stale_root]]).unwrap();
+    assert!(prover.verify().is_err());
+}
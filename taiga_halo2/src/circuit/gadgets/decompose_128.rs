@@ -0,0 +1,287 @@
+/// Constrain value = low + high * 2^128, with low in 128 bits and high in 126 bits
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_advice, assign_free_constant,
+    mul::{MulChip, MulInstructions},
+};
+use ff::PrimeField;
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// The shift of the high part
+pub fn two_pow_128() -> pallas::Base {
+    pallas::Base::from_u128(1 << 64).square()
+}
+
+// Split the value into its low 128 bits and the rest
+pub fn decompose_128(value: pallas::Base) -> (pallas::Base, pallas::Base) {
+    let repr = value.to_repr();
+    let low = u128::from_le_bytes(repr[..16].try_into().unwrap());
+    let high = u128::from_le_bytes(repr[16..].try_into().unwrap());
+    (pallas::Base::from_u128(low), pallas::Base::from_u128(high))
+}
+
+// Check the range of the cell with num_words 10-bit words and a short word of short_bits
+pub fn range_check(
+    mut layouter: impl Layouter<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    value: &AssignedCell<pallas::Base, pallas::Base>,
+    num_words: usize,
+    short_bits: usize,
+) -> Result<(), Error> {
+    let zs = lookup_config.copy_check(
+        layouter.namespace(|| "K(10) bits range check"),
+        value.clone(),
+        num_words,
+        false,
+    )?;
+    lookup_config.copy_short_check(
+        layouter.namespace(|| "short range check"),
+        zs[num_words].clone(),
+        short_bits,
+    )
+}
+
+// Decompose the value into (low, high). The recomposition is below 2^254 so it can't wrap around
+// and the decomposition is unique, which leaves out the values in [2^254, p).
+pub fn decompose_128_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    advice: Column<Advice>,
+    value: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<
+    (
+        AssignedCell<pallas::Base, pallas::Base>,
+        AssignedCell<pallas::Base, pallas::Base>,
+    ),
+    Error,
+> {
+    let low = assign_free_advice(
+        layouter.namespace(|| "witness low"),
+        advice,
+        value.value().map(|value| decompose_128(*value).0),
+    )?;
+    range_check(
+        layouter.namespace(|| "128 bits range check"),
+        lookup_config,
+        &low,
+        12,
+        8,
+    )?;
+    let high = assign_free_advice(
+        layouter.namespace(|| "witness high"),
+        advice,
+        value.value().map(|value| decompose_128(*value).1),
+    )?;
+    range_check(
+        layouter.namespace(|| "126 bits range check"),
+        lookup_config,
+        &high,
+        12,
+        6,
+    )?;
+
+    // value = low + high * 2^128
+    let shift = assign_free_constant(
+        layouter.namespace(|| "constant 2^128"),
+        advice,
+        two_pow_128(),
+    )?;
+    let shifted_high = mul_chip.mul(layouter.namespace(|| "high * 2^128"), &high, &shift)?;
+    let recomposed = add_chip.add(
+        layouter.namespace(|| "low + high * 2^128"),
+        &low,
+        &shifted_high,
+    )?;
+    layouter.assign_region(
+        || "check decomposition",
+        |mut region| region.constrain_equal(recomposed.cell(), value.cell()),
+    )?;
+
+    Ok((low, high))
+}
+
+fn low_u128(value: &pallas::Base) -> u128 {
+    u128::from_le_bytes(value.to_repr()[..16].try_into().unwrap())
+}
+
+// Check a < b from their decompositions: b - a - 1 = low_gap + high_gap * 2^128, with a borrow from
+// the high part when b_low <= a_low. The gaps are in 128 and 126 bits and both sums are far below
+// p, so neither side can wrap around.
+#[allow(clippy::too_many_arguments)]
+pub fn less_than_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    advice: Column<Advice>,
+    a: &(
+        AssignedCell<pallas::Base, pallas::Base>,
+        AssignedCell<pallas::Base, pallas::Base>,
+    ),
+    b: &(
+        AssignedCell<pallas::Base, pallas::Base>,
+        AssignedCell<pallas::Base, pallas::Base>,
+    ),
+) -> Result<(), Error> {
+    let (a_low, a_high) = a;
+    let (b_low, b_high) = b;
+    let borrow = assign_free_advice(
+        layouter.namespace(|| "witness borrow"),
+        advice,
+        a_low
+            .value()
+            .zip(b_low.value())
+            .map(|(a_low, b_low)| pallas::Base::from(low_u128(b_low) <= low_u128(a_low))),
+    )?;
+    let borrow_square = mul_chip.mul(layouter.namespace(|| "borrow * borrow"), &borrow, &borrow)?;
+    layouter.assign_region(
+        || "bool_check borrow",
+        |mut region| region.constrain_equal(borrow_square.cell(), borrow.cell()),
+    )?;
+
+    // a_low + 1 + low_gap = b_low + borrow * 2^128
+    let low_gap = assign_free_advice(
+        layouter.namespace(|| "witness low gap"),
+        advice,
+        a_low
+            .value()
+            .zip(b_low.value())
+            .zip(borrow.value())
+            .map(|((a_low, b_low), borrow)| {
+                b_low + borrow * two_pow_128() - a_low - pallas::Base::one()
+            }),
+    )?;
+    range_check(
+        layouter.namespace(|| "low gap range check"),
+        lookup_config,
+        &low_gap,
+        12,
+        8,
+    )?;
+    let one = assign_free_constant(
+        layouter.namespace(|| "constant one"),
+        advice,
+        pallas::Base::one(),
+    )?;
+    let shift = assign_free_constant(
+        layouter.namespace(|| "constant 2^128"),
+        advice,
+        two_pow_128(),
+    )?;
+    let lhs = {
+        let next = add_chip.add(layouter.namespace(|| "a_low + 1"), a_low, &one)?;
+        add_chip.add(
+            layouter.namespace(|| "a_low + 1 + low_gap"),
+            &next,
+            &low_gap,
+        )?
+    };
+    let rhs = {
+        let shifted = mul_chip.mul(layouter.namespace(|| "borrow * 2^128"), &borrow, &shift)?;
+        add_chip.add(
+            layouter.namespace(|| "b_low + borrow * 2^128"),
+            b_low,
+            &shifted,
+        )?
+    };
+    layouter.assign_region(
+        || "check low gap",
+        |mut region| region.constrain_equal(lhs.cell(), rhs.cell()),
+    )?;
+
+    // a_high + borrow + high_gap = b_high
+    let high_gap = assign_free_advice(
+        layouter.namespace(|| "witness high gap"),
+        advice,
+        a_high
+            .value()
+            .zip(b_high.value())
+            .zip(borrow.value())
+            .map(|((a_high, b_high), borrow)| b_high - a_high - borrow),
+    )?;
+    range_check(
+        layouter.namespace(|| "high gap range check"),
+        lookup_config,
+        &high_gap,
+        12,
+        6,
+    )?;
+    let lhs = {
+        let borrowed = add_chip.add(layouter.namespace(|| "a_high + borrow"), a_high, &borrow)?;
+        add_chip.add(
+            layouter.namespace(|| "a_high + borrow + high_gap"),
+            &borrowed,
+            &high_gap,
+        )?
+    };
+    layouter.assign_region(
+        || "check high gap",
+        |mut region| region.constrain_equal(lhs.cell(), b_high.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_decompose_128_gadget() {
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+
+    #[derive(Default)]
+    struct MyCircuit {
+        value: pallas::Base,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let value = assign_free_advice(
+                layouter.namespace(|| "witness value"),
+                config.advices[0],
+                Value::known(self.value),
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            let (low, high) = decompose_128_gadget(
+                layouter.namespace(|| "decompose 128"),
+                AddChip::construct(config.add_config, ()),
+                MulChip::construct(config.mul_config),
+                resource_commit_chip.get_lookup_config(),
+                config.advices[0],
+                &value,
+            )?;
+            layouter.constrain_instance(low.cell(), config.instances, 0)?;
+            layouter.constrain_instance(high.cell(), config.instances, 1)
+        }
+    }
+
+    // A known value and its recomposition
+    let low = pallas::Base::from_u128(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    let high = pallas::Base::from_u128(0x2000_0000_0000_0000_0000_0000_0042);
+    let value = low + high * two_pow_128();
+    assert_eq!(decompose_128(value), (low, high));
+    let circuit = GadgetTestCircuit(MyCircuit { value });
+    let prover = MockProver::run(11, &circuit, vec![vec![low, high]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Another split of the value
+    let prover = MockProver::run(
+        11,
+        &circuit,
+        vec![vec![low + pallas::Base::one(), high - pallas::Base::one()]],
+    )
+    .unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,182 @@
+/// Constrain the pair (owner, delegate) is a leaf of the approval tree committed by the owner
+use crate::circuit::gadgets::poseidon_hash::poseidon_hash_gadget;
+use crate::circuit::merkle_circuit::{merkle_poseidon_gadget, MerklePoseidonChip};
+use crate::merkle_tree::LR;
+use crate::utils::poseidon_hash;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// The leaf of an approved delegate in the approval tree of the owner
+pub fn delegation_leaf(owner: pallas::Base, delegate: pallas::Base) -> pallas::Base {
+    poseidon_hash(owner, delegate)
+}
+
+// Check the owner approved the delegate. The owner is hashed into the leaf so that an approval
+// tree can't be reused to delegate the resources of another owner.
+pub fn delegation_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    merkle_chip: MerklePoseidonChip,
+    owner: &AssignedCell<pallas::Base, pallas::Base>,
+    delegate: &AssignedCell<pallas::Base, pallas::Base>,
+    approval_root: &AssignedCell<pallas::Base, pallas::Base>,
+    merkle_path: &[(pallas::Base, LR)],
+) -> Result<(), Error> {
+    let leaf = poseidon_hash_gadget(
+        merkle_chip.get_poseidon_config(),
+        layouter.namespace(|| "delegation leaf"),
+        [owner.clone(), delegate.clone()],
+    )?;
+
+    let root = merkle_poseidon_gadget(
+        layouter.namespace(|| "delegation membership"),
+        merkle_chip,
+        leaf,
+        merkle_path,
+    )?;
+
+    layouter.assign_region(
+        || "check approval root",
+        |mut region| region.constrain_equal(root.cell(), approval_root.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_delegation_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::merkle_circuit::MerklePoseidonConfig;
+    use crate::merkle_tree::{MerklePath, Node};
+    use halo2_gadgets::poseidon::{primitives as poseidon, Pow5Chip as PoseidonChip};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        owner: pallas::Base,
+        delegate: pallas::Base,
+        merkle_path: MerklePath,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = ([Column<Advice>; 5], Column<Instance>, MerklePoseidonConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let state = (0..3).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let partial_sbox = meta.advice_column();
+            let rc_a = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            meta.enable_constant(rc_b[0]);
+            let poseidon_config = PoseidonChip::configure::<poseidon::P128Pow5T3>(
+                meta,
+                state.try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            );
+
+            let merkle_config = MerklePoseidonChip::configure(meta, advices, poseidon_config);
+            (advices, instance, merkle_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, merkle_config) = config;
+            let owner = assign_free_advice(
+                layouter.namespace(|| "witness owner"),
+                advices[0],
+                Value::known(self.owner),
+            )?;
+            let delegate = assign_free_advice(
+                layouter.namespace(|| "witness delegate"),
+                advices[1],
+                Value::known(self.delegate),
+            )?;
+            let approval_root = assign_free_instance(
+                layouter.namespace(|| "approval root"),
+                instance,
+                0,
+                advices[2],
+            )?;
+
+            delegation_gadget(
+                layouter.namespace(|| "delegation"),
+                MerklePoseidonChip::construct(merkle_config),
+                &owner,
+                &delegate,
+                &approval_root,
+                &self.merkle_path.get_path(),
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    // The owner approved four delegates
+    let owner = pallas::Base::random(&mut rng);
+    let delegates: Vec<pallas::Base> = (0..4).map(|_| pallas::Base::random(&mut rng)).collect();
+    let leaves: Vec<Node> = delegates
+        .iter()
+        .map(|delegate| Node::from(delegation_leaf(owner, *delegate)))
+        .collect();
+    // The merkle path of the third delegate
+    let merkle_path = MerklePath::from_path(vec![
+        (leaves[3], LR::R),
+        (Node::combine(&leaves[0], &leaves[1]), LR::L),
+    ]);
+    let approval_root = merkle_path.root(leaves[2]).inner();
+
+    // An approved delegate
+    let circuit = MyCircuit {
+        owner,
+        delegate: delegates[2],
+        merkle_path: merkle_path.clone(),
+    };
+    let prover = MockProver::run(11, &circuit, vec![vec![approval_root]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // An unapproved delegate
+    let circuit = MyCircuit {
+        owner,
+        delegate: pallas::Base::random(&mut rng),
+        merkle_path: merkle_path.clone(),
+    };
+    let prover = MockProver::run(11, &circuit, vec![vec![approval_root]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The delegate approved by another owner
+    let circuit = MyCircuit {
+        owner: pallas::Base::random(&mut rng),
+        delegate: delegates[2],
+        merkle_path,
+    };
+    let prover = MockProver::run(11, &circuit, vec![vec![approval_root]]).unwrap();
+    assert!(prover.verify().is_err());
+}
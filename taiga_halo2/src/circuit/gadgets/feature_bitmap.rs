@@ -0,0 +1,133 @@
+/// Constrain data = sum(flags[i] * 2^i) with N boolean flags, of which flags[required] is set
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_advice, assign_free_constant,
+    mul::{MulChip, MulInstructions},
+};
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// Pack the enabled features into a bitmap
+pub fn feature_bitmap(enabled: &[usize]) -> pallas::Base {
+    let mut bytes = [0u8; 32];
+    for feature in enabled.iter() {
+        assert!(*feature < 253);
+        bytes[feature / 8] |= 1 << (feature % 8);
+    }
+    pallas::Base::from_repr(bytes).unwrap()
+}
+
+// Check the data is a bitmap of N features with the required one enabled. The flags are below
+// 2^253, so the recomposition can't wrap around and the bitmap of the data is unique.
+pub fn feature_bitmap_gadget<const N: usize>(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    advice: Column<Advice>,
+    data: &AssignedCell<pallas::Base, pallas::Base>,
+    required: usize,
+) -> Result<(), Error> {
+    assert!(N > 0 && N < 254 && required < N);
+    let two = assign_free_constant(
+        layouter.namespace(|| "constant two"),
+        advice,
+        pallas::Base::from(2u64),
+    )?;
+
+    let mut flags = Vec::with_capacity(N);
+    for i in 0..N {
+        let flag = assign_free_advice(
+            layouter.namespace(|| "witness flag"),
+            advice,
+            data.value()
+                .map(|data| pallas::Base::from(((data.to_repr()[i / 8] >> (i % 8)) & 1) as u64)),
+        )?;
+        let flag_square = mul_chip.mul(layouter.namespace(|| "flag * flag"), &flag, &flag)?;
+        layouter.assign_region(
+            || "bool_check flag",
+            |mut region| region.constrain_equal(flag_square.cell(), flag.cell()),
+        )?;
+        flags.push(flag);
+    }
+
+    // data = sum(flags[i] * 2^i)
+    let mut packed = flags[N - 1].clone();
+    for flag in flags.iter().rev().skip(1) {
+        let shifted = mul_chip.mul(layouter.namespace(|| "packed * 2"), &packed, &two)?;
+        packed = add_chip.add(layouter.namespace(|| "packed + flag"), &shifted, flag)?;
+    }
+    layouter.assign_region(
+        || "check bitmap",
+        |mut region| region.constrain_equal(packed.cell(), data.cell()),
+    )?;
+
+    layouter.assign_region(
+        || "check required feature",
+        |mut region| region.constrain_constant(flags[required].cell(), pallas::Base::one()),
+    )
+}
+
+#[test]
+fn test_halo2_feature_bitmap_gadget() {
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+
+    const FEATURE_NUM: usize = 8;
+    const REQUIRED_FEATURE: usize = 3;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        data: pallas::Base,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let data = assign_free_advice(
+                layouter.namespace(|| "witness data"),
+                config.advices[0],
+                Value::known(self.data),
+            )?;
+            feature_bitmap_gadget::<FEATURE_NUM>(
+                layouter.namespace(|| "feature bitmap"),
+                AddChip::construct(config.add_config, ()),
+                MulChip::construct(config.mul_config),
+                config.advices[0],
+                &data,
+                REQUIRED_FEATURE,
+            )
+        }
+    }
+
+    // The features 0, 3 and 6 are enabled
+    let circuit = GadgetTestCircuit(MyCircuit {
+        data: feature_bitmap(&[0, 3, 6]),
+    });
+    assert_eq!(circuit.0.data, pallas::Base::from(0b1001001u64));
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The required feature is unset
+    let circuit = GadgetTestCircuit(MyCircuit {
+        data: feature_bitmap(&[0, 6]),
+    });
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The data has a bit beyond the N features
+    let circuit = GadgetTestCircuit(MyCircuit {
+        data: feature_bitmap(&[3, FEATURE_NUM]),
+    });
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
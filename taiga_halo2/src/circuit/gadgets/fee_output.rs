@@ -0,0 +1,116 @@
+/// Constrain the fee output resource: value(owner) = collector and quantity = fee
+use crate::circuit::vp_circuit::ResourceVariables;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// Check the fee output pays the declared fee to the collector, both of which are expected to be public inputs
+pub fn fee_output_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    fee_output: &ResourceVariables,
+    collector: &AssignedCell<pallas::Base, pallas::Base>,
+    fee: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    layouter.assign_region(
+        || "check fee output",
+        |mut region| {
+            region.constrain_equal(fee_output.value.cell(), collector.cell())?;
+            region.constrain_equal(fee_output.quantity.cell(), fee.cell())
+        },
+    )
+}
+
+#[test]
+fn test_halo2_fee_output_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::resource::{tests::random_resource, Resource};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        fee_output: Resource,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advice = meta.advice_column();
+            meta.enable_equality(advice);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advice, instance) = config;
+            let mut witness = |name: &'static str, value: pallas::Base| {
+                assign_free_advice(layouter.namespace(|| name), advice, Value::known(value))
+            };
+            let fee_output = ResourceVariables {
+                logic: witness("logic", self.fee_output.get_logic())?,
+                label: witness("label", self.fee_output.get_label())?,
+                quantity: witness("quantity", pallas::Base::from(self.fee_output.quantity))?,
+                is_ephemeral: witness(
+                    "is_ephemeral",
+                    pallas::Base::from(self.fee_output.is_ephemeral),
+                )?,
+                value: witness("value", self.fee_output.value)?,
+                nonce: witness("nonce", self.fee_output.nonce.inner())?,
+                npk: witness("npk", self.fee_output.get_npk())?,
+                rseed: witness("rseed", self.fee_output.rseed)?,
+            };
+
+            let collector =
+                assign_free_instance(layouter.namespace(|| "collector"), instance, 0, advice)?;
+            let fee = assign_free_instance(layouter.namespace(|| "fee"), instance, 1, advice)?;
+
+            fee_output_gadget(
+                layouter.namespace(|| "fee output"),
+                &fee_output,
+                &collector,
+                &fee,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let collector = pallas::Base::random(&mut rng);
+    let fee = 25u64;
+    let mut fee_output = random_resource(&mut rng);
+    fee_output.value = collector;
+    fee_output.quantity = fee;
+    let circuit = MyCircuit { fee_output };
+
+    // The fee is paid to the collector
+    let prover =
+        MockProver::run(6, &circuit, vec![vec![collector, pallas::Base::from(fee)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The fee is paid to the wrong address
+    let wrong_collector = pallas::Base::random(&mut rng);
+    let prover = MockProver::run(
+        6,
+        &circuit,
+        vec![vec![wrong_collector, pallas::Base::from(fee)]],
+    )
+    .unwrap();
+    assert!(prover.verify().is_err());
+}
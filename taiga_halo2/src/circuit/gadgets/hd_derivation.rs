@@ -0,0 +1,190 @@
+/// Constrain seed_commitment = poseidon_hash(seed) and rseed = poseidon_hash(seed, path)
+use crate::circuit::gadgets::poseidon_hash::poseidon_hash_gadget;
+use crate::utils::{poseidon_hash, poseidon_hash_n};
+use halo2_gadgets::poseidon::Pow5Config as PoseidonConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// The public commitment to the wallet seed. It hashes a single element, so it's in another domain
+// than the derived randomness and can't collide with the rseed of any path.
+pub fn hd_seed_commitment(seed: pallas::Base) -> pallas::Base {
+    poseidon_hash_n([seed])
+}
+
+// The resource rseed derived from the wallet seed at the path. The rcm and psi are expanded from the
+// rseed, so the whole commitment randomness follows the derivation.
+pub fn hd_derive_rseed(seed: pallas::Base, path: pallas::Base) -> pallas::Base {
+    poseidon_hash(seed, path)
+}
+
+// Check the rseed is derived from the committed seed at the public path
+pub fn hd_derivation_gadget(
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    mut layouter: impl Layouter<pallas::Base>,
+    seed: &AssignedCell<pallas::Base, pallas::Base>,
+    seed_commitment: &AssignedCell<pallas::Base, pallas::Base>,
+    path: &AssignedCell<pallas::Base, pallas::Base>,
+    rseed: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let expected_seed_commitment = poseidon_hash_gadget(
+        poseidon_config.clone(),
+        layouter.namespace(|| "seed commitment"),
+        [seed.clone()],
+    )?;
+    layouter.assign_region(
+        || "check seed commitment",
+        |mut region| {
+            region.constrain_equal(expected_seed_commitment.cell(), seed_commitment.cell())
+        },
+    )?;
+
+    let expected_rseed = poseidon_hash_gadget(
+        poseidon_config,
+        layouter.namespace(|| "derive rseed"),
+        [seed.clone(), path.clone()],
+    )?;
+    layouter.assign_region(
+        || "check rseed derivation",
+        |mut region| region.constrain_equal(expected_rseed.cell(), rseed.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_hd_derivation_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use halo2_gadgets::poseidon::{primitives as poseidon, Pow5Chip as PoseidonChip};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        seed: pallas::Base,
+        rseed: pallas::Base,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        #[allow(clippy::type_complexity)]
+        type Config = (
+            [Column<Advice>; 4],
+            Column<Instance>,
+            PoseidonConfig<pallas::Base, 3, 2>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let lagrange_coeffs = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let poseidon_config = PoseidonChip::configure::<poseidon::P128Pow5T3>(
+                meta,
+                advices[1..4].try_into().unwrap(),
+                advices[0],
+                lagrange_coeffs[0..3].try_into().unwrap(),
+                lagrange_coeffs[3..6].try_into().unwrap(),
+            );
+            (advices, instance, poseidon_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, poseidon_config) = config;
+            let seed = assign_free_advice(
+                layouter.namespace(|| "witness seed"),
+                advices[0],
+                Value::known(self.seed),
+            )?;
+            let rseed = assign_free_advice(
+                layouter.namespace(|| "witness rseed"),
+                advices[0],
+                Value::known(self.rseed),
+            )?;
+            let seed_commitment = assign_free_instance(
+                layouter.namespace(|| "public seed commitment"),
+                instance,
+                0,
+                advices[0],
+            )?;
+            let path = assign_free_instance(
+                layouter.namespace(|| "public path"),
+                instance,
+                1,
+                advices[0],
+            )?;
+
+            hd_derivation_gadget(
+                poseidon_config,
+                layouter.namespace(|| "hd derivation"),
+                &seed,
+                &seed_commitment,
+                &path,
+                &rseed,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let seed = pallas::Base::random(&mut rng);
+    let seed_commitment = hd_seed_commitment(seed);
+    let path = pallas::Base::from(7u64);
+
+    // The rseed is derived at the path
+    let circuit = MyCircuit {
+        seed,
+        rseed: hd_derive_rseed(seed, path),
+    };
+    let prover = MockProver::run(8, &circuit, vec![vec![seed_commitment, path]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The rseed is derived at another path
+    let circuit = MyCircuit {
+        seed,
+        rseed: hd_derive_rseed(seed, pallas::Base::from(8u64)),
+    };
+    let prover = MockProver::run(8, &circuit, vec![vec![seed_commitment, path]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The rseed is not derived from the seed
+    let circuit = MyCircuit {
+        seed,
+        rseed: pallas::Base::random(&mut rng),
+    };
+    let prover = MockProver::run(8, &circuit, vec![vec![seed_commitment, path]]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,231 @@
+/// Constrain the HTLC resource is redeemed with the hashlock preimage before the timeout, or refunded from the timeout on
+use crate::circuit::gadgets::{
+    assign_free_constant,
+    coin_selection::u64_range_check,
+    conditional_equal::ConditionalEqualConfig,
+    conditional_select::ConditionalSelectConfig,
+    mul::{MulChip, MulInstructions},
+    poseidon_hash::poseidon_hash_gadget,
+    sub::{SubChip, SubInstructions},
+};
+use crate::utils::poseidon_hash;
+use halo2_gadgets::{
+    poseidon::Pow5Config as PoseidonConfig, utilities::lookup_range_check::LookupRangeCheckConfig,
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// The hashlock of the preimage
+pub fn htlc_hashlock(preimage: pallas::Base) -> pallas::Base {
+    poseidon_hash(preimage, pallas::Base::zero())
+}
+
+// The lock stored in the value of the HTLC resource
+pub fn htlc_value(hashlock: pallas::Base, timeout: u64) -> pallas::Base {
+    poseidon_hash(hashlock, pallas::Base::from(timeout))
+}
+
+// Check the HTLC resource is spent on the branch selected by the redeem flag, which is expected to
+// be a public input. The redemption reveals the preimage before the timeout and the refund only
+// opens at the timeout, so the two branches never overlap.
+#[allow(clippy::too_many_arguments)]
+pub fn htlc_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    sub_chip: SubChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    conditional_equal_config: ConditionalEqualConfig,
+    conditional_select_config: ConditionalSelectConfig,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    advice: Column<Advice>,
+    value: &AssignedCell<pallas::Base, pallas::Base>,
+    hashlock: &AssignedCell<pallas::Base, pallas::Base>,
+    timeout: &AssignedCell<pallas::Base, pallas::Base>,
+    preimage: &AssignedCell<pallas::Base, pallas::Base>,
+    now: &AssignedCell<pallas::Base, pallas::Base>,
+    redeem: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    // The resource is locked by the hashlock and the timeout
+    let lock = poseidon_hash_gadget(
+        poseidon_config.clone(),
+        layouter.namespace(|| "htlc lock"),
+        [hashlock.clone(), timeout.clone()],
+    )?;
+    layouter.assign_region(
+        || "check htlc lock",
+        |mut region| region.constrain_equal(lock.cell(), value.cell()),
+    )?;
+
+    // bool_check redeem
+    let redeem_square = mul_chip.mul(layouter.namespace(|| "redeem * redeem"), redeem, redeem)?;
+    layouter.assign_region(
+        || "bool_check redeem",
+        |mut region| region.constrain_equal(redeem_square.cell(), redeem.cell()),
+    )?;
+
+    // redeem * (poseidon_hash(preimage, 0) - hashlock) = 0
+    let zero = assign_free_constant(
+        layouter.namespace(|| "constant zero"),
+        advice,
+        pallas::Base::zero(),
+    )?;
+    let preimage_hash = poseidon_hash_gadget(
+        poseidon_config,
+        layouter.namespace(|| "preimage hash"),
+        [preimage.clone(), zero],
+    )?;
+    layouter.assign_region(
+        || "check preimage",
+        |mut region| {
+            conditional_equal_config.assign_region(redeem, &preimage_hash, hashlock, 0, &mut region)
+        },
+    )?;
+
+    // The dates are 64-bit, so the differences below can't wrap around
+    for date in [timeout, now] {
+        u64_range_check(
+            layouter.namespace(|| "date range check"),
+            lookup_config,
+            date,
+        )?;
+    }
+
+    // Redeem: timeout - now - 1 >= 0. Refund: now - timeout >= 0.
+    let one = assign_free_constant(
+        layouter.namespace(|| "constant one"),
+        advice,
+        pallas::Base::one(),
+    )?;
+    let time_left = sub_chip.sub(layouter.namespace(|| "timeout - now"), timeout, now)?;
+    let time_left = sub_chip.sub(layouter.namespace(|| "time_left - 1"), &time_left, &one)?;
+    let time_passed = sub_chip.sub(layouter.namespace(|| "now - timeout"), now, timeout)?;
+    let margin = layouter.assign_region(
+        || "select time margin",
+        |mut region| {
+            conditional_select_config.assign_region(
+                redeem,
+                &time_left,
+                &time_passed,
+                0,
+                &mut region,
+            )
+        },
+    )?;
+    u64_range_check(
+        layouter.namespace(|| "time margin range check"),
+        lookup_config,
+        &margin,
+    )
+}
+
+#[test]
+fn test_halo2_htlc_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{arithmetic::Field, circuit::Value, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        value: pallas::Base,
+        hashlock: pallas::Base,
+        timeout: u64,
+        preimage: pallas::Base,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let mut witness = |name: &'static str, value: pallas::Base| {
+                assign_free_advice(
+                    layouter.namespace(|| name),
+                    config.advices[0],
+                    Value::known(value),
+                )
+            };
+            let value = witness("value", self.value)?;
+            let hashlock = witness("hashlock", self.hashlock)?;
+            let timeout = witness("timeout", pallas::Base::from(self.timeout))?;
+            let preimage = witness("preimage", self.preimage)?;
+            let now = assign_free_instance(
+                layouter.namespace(|| "now"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+            let redeem = assign_free_instance(
+                layouter.namespace(|| "redeem flag"),
+                config.instances,
+                1,
+                config.advices[0],
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            htlc_gadget(
+                layouter.namespace(|| "htlc"),
+                config.poseidon_config,
+                SubChip::construct(config.sub_config, ()),
+                MulChip::construct(config.mul_config),
+                config.conditional_equal_config,
+                config.conditional_select_config,
+                resource_commit_chip.get_lookup_config(),
+                config.advices[0],
+                &value,
+                &hashlock,
+                &timeout,
+                &preimage,
+                &now,
+                &redeem,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let preimage = pallas::Base::random(&mut rng);
+    let hashlock = htlc_hashlock(preimage);
+    let timeout = 1000u64;
+    let htlc = |preimage: pallas::Base| {
+        GadgetTestCircuit(MyCircuit {
+            value: htlc_value(hashlock, timeout),
+            hashlock,
+            timeout,
+            preimage,
+        })
+    };
+    let run = |circuit: &GadgetTestCircuit<MyCircuit>, now: u64, redeem: bool| {
+        MockProver::run(
+            11,
+            circuit,
+            vec![vec![pallas::Base::from(now), pallas::Base::from(redeem)]],
+        )
+        .unwrap()
+        .verify()
+    };
+
+    // The preimage is revealed before the timeout
+    assert_eq!(run(&htlc(preimage), 999, true), Ok(()));
+
+    // The refund after the timeout, which needs no preimage
+    let other = pallas::Base::random(&mut rng);
+    assert_eq!(run(&htlc(other), 1000, false), Ok(()));
+
+    // The preimage is revealed at or after the timeout
+    assert!(run(&htlc(preimage), 1000, true).is_err());
+    assert!(run(&htlc(preimage), 5000, true).is_err());
+
+    // A wrong preimage before the timeout
+    assert!(run(&htlc(other), 999, true).is_err());
+
+    // The refund before the timeout
+    assert!(run(&htlc(preimage), 999, false).is_err());
+}
@@ -0,0 +1,138 @@
+/// Constrain min <= n - sum(is_ephemeral) <= max
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_constant,
+    sub::{SubChip, SubInstructions},
+};
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// Check the number of real inputs is in [min, max]. The is_ephemeral flags are bool-checked in the
+// resource commitment, so the count is at most the number of inputs, and both count - min and
+// max - count wrap around to a huge value out of range if the count is out of the bounds.
+#[allow(clippy::too_many_arguments)]
+pub fn input_count_range_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    sub_chip: SubChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    advice: Column<Advice>,
+    input_is_ephemeral: &[AssignedCell<pallas::Base, pallas::Base>],
+    min: u64,
+    max: u64,
+) -> Result<(), Error> {
+    assert!(!input_is_ephemeral.is_empty() && input_is_ephemeral.len() < 1 << 8);
+    assert!(min <= max);
+    let mut ephemeral_num = input_is_ephemeral[0].clone();
+    for is_ephemeral in input_is_ephemeral.iter().skip(1) {
+        ephemeral_num = add_chip.add(
+            layouter.namespace(|| "count ephemeral inputs"),
+            &ephemeral_num,
+            is_ephemeral,
+        )?;
+    }
+    let input_num = assign_free_constant(
+        layouter.namespace(|| "input num"),
+        advice,
+        pallas::Base::from(input_is_ephemeral.len() as u64),
+    )?;
+    let real_num = sub_chip.sub(
+        layouter.namespace(|| "input num - ephemeral num"),
+        &input_num,
+        &ephemeral_num,
+    )?;
+
+    // real_num - min in [0, 2^8)
+    let min = assign_free_constant(
+        layouter.namespace(|| "min"),
+        advice,
+        pallas::Base::from(min),
+    )?;
+    let lower = sub_chip.sub(layouter.namespace(|| "real num - min"), &real_num, &min)?;
+    lookup_config.copy_short_check(layouter.namespace(|| "8 bits range check"), lower, 8)?;
+
+    // max - real_num in [0, 2^8)
+    let max = assign_free_constant(
+        layouter.namespace(|| "max"),
+        advice,
+        pallas::Base::from(max),
+    )?;
+    let upper = sub_chip.sub(layouter.namespace(|| "max - real num"), &max, &real_num)?;
+    lookup_config.copy_short_check(layouter.namespace(|| "8 bits range check"), upper, 8)
+}
+
+#[test]
+fn test_halo2_input_count_range_gadget() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+
+    const MIN_INPUT_NUM: u64 = 2;
+    const MAX_INPUT_NUM: u64 = 3;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        input_is_ephemeral: [bool; 4],
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let input_is_ephemeral = self
+                .input_is_ephemeral
+                .iter()
+                .map(|is_ephemeral| {
+                    assign_free_advice(
+                        layouter.namespace(|| "witness is_ephemeral"),
+                        config.advices[0],
+                        Value::known(pallas::Base::from(*is_ephemeral)),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            input_count_range_gadget(
+                layouter.namespace(|| "input count range"),
+                AddChip::construct(config.add_config, ()),
+                SubChip::construct(config.sub_config, ()),
+                resource_commit_chip.get_lookup_config(),
+                config.advices[0],
+                &input_is_ephemeral,
+                MIN_INPUT_NUM,
+                MAX_INPUT_NUM,
+            )
+        }
+    }
+
+    // Two and three real inputs
+    for input_is_ephemeral in [[false, true, false, true], [false, false, true, false]] {
+        let circuit = GadgetTestCircuit(MyCircuit { input_is_ephemeral });
+        let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    // Too few real inputs
+    let circuit = GadgetTestCircuit(MyCircuit {
+        input_is_ephemeral: [true, true, false, true],
+    });
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Too many real inputs
+    let circuit = GadgetTestCircuit(MyCircuit {
+        input_is_ephemeral: [false; 4],
+    });
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
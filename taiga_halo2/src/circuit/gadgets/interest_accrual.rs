@@ -0,0 +1,316 @@
+/// Constrain redemption = principal + floor(principal * rate_bps * elapsed / (10000 * period)), with elapsed clamped to the period
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_advice, assign_free_constant,
+    coin_selection::u64_range_check,
+    conditional_select::ConditionalSelectConfig,
+    decompose_128::range_check,
+    mul::{MulChip, MulInstructions},
+    royalty_split::BPS_DENOMINATOR,
+    sub::{SubChip, SubInstructions},
+};
+use crate::utils::to_u64;
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// The redemption of the principal at now, with the interest accrued at rate_bps per period from
+// start. No interest accrues after the period.
+pub fn interest_accrual_redemption(
+    principal: u64,
+    rate_bps: u64,
+    start: u64,
+    period: u64,
+    now: u64,
+) -> u64 {
+    assert!(start <= now && period > 0);
+    let elapsed = (now - start).min(period);
+    let interest = principal as u128 * rate_bps as u128 * elapsed as u128
+        / (BPS_DENOMINATOR as u128 * period as u128);
+    principal + interest as u64
+}
+
+// Check the redemption of a bond-like resource at the public time now. The inputs and the interest
+// are 64-bit values and rem is in 128 bits, so both sides of the division stay far below the modulus.
+#[allow(clippy::too_many_arguments)]
+pub fn interest_accrual_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    sub_chip: SubChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    conditional_select_config: ConditionalSelectConfig,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    advice: Column<Advice>,
+    principal: &AssignedCell<pallas::Base, pallas::Base>,
+    rate_bps: &AssignedCell<pallas::Base, pallas::Base>,
+    start: &AssignedCell<pallas::Base, pallas::Base>,
+    period: &AssignedCell<pallas::Base, pallas::Base>,
+    now: &AssignedCell<pallas::Base, pallas::Base>,
+    redemption: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    for value in [principal, rate_bps, start, period, now, redemption] {
+        u64_range_check(
+            layouter.namespace(|| "interest range check"),
+            lookup_config,
+            value,
+        )?;
+    }
+    let one = assign_free_constant(
+        layouter.namespace(|| "constant one"),
+        advice,
+        pallas::Base::one(),
+    )?;
+
+    // period > 0
+    let period_minus_one = sub_chip.sub(layouter.namespace(|| "period - 1"), period, &one)?;
+    u64_range_check(
+        layouter.namespace(|| "period > 0"),
+        lookup_config,
+        &period_minus_one,
+    )?;
+
+    // start <= now
+    let raw_elapsed = sub_chip.sub(layouter.namespace(|| "now - start"), now, start)?;
+    u64_range_check(
+        layouter.namespace(|| "start <= now"),
+        lookup_config,
+        &raw_elapsed,
+    )?;
+
+    // is_after = 1 only if raw_elapsed >= period, then is_after * (raw_elapsed - period) is in 64 bits
+    let is_after = assign_free_advice(
+        layouter.namespace(|| "witness is_after"),
+        advice,
+        raw_elapsed
+            .value()
+            .zip(period.value())
+            .map(|(raw_elapsed, period)| pallas::Base::from(to_u64(raw_elapsed) >= to_u64(period))),
+    )?;
+    let flag_square = mul_chip.mul(
+        layouter.namespace(|| "is_after * is_after"),
+        &is_after,
+        &is_after,
+    )?;
+    layouter.assign_region(
+        || "bool_check is_after",
+        |mut region| region.constrain_equal(flag_square.cell(), is_after.cell()),
+    )?;
+    let after_gap = {
+        let gap = sub_chip.sub(
+            layouter.namespace(|| "raw_elapsed - period"),
+            &raw_elapsed,
+            period,
+        )?;
+        mul_chip.mul(layouter.namespace(|| "is_after * gap"), &is_after, &gap)?
+    };
+    u64_range_check(
+        layouter.namespace(|| "raw_elapsed >= period"),
+        lookup_config,
+        &after_gap,
+    )?;
+
+    // elapsed = is_after ? period : raw_elapsed, and elapsed <= period so that a wrong flag of zero
+    // fails
+    let elapsed = layouter.assign_region(
+        || "clamp elapsed",
+        |mut region| {
+            conditional_select_config.assign_region(&is_after, period, &raw_elapsed, 0, &mut region)
+        },
+    )?;
+    let remaining = sub_chip.sub(layouter.namespace(|| "period - elapsed"), period, &elapsed)?;
+    u64_range_check(
+        layouter.namespace(|| "elapsed <= period"),
+        lookup_config,
+        &remaining,
+    )?;
+
+    // interest * denominator + rem = principal * rate_bps * elapsed, with denominator = 10000 * period
+    // and rem < denominator
+    let interest = sub_chip.sub(
+        layouter.namespace(|| "redemption - principal"),
+        redemption,
+        principal,
+    )?;
+    u64_range_check(
+        layouter.namespace(|| "interest range check"),
+        lookup_config,
+        &interest,
+    )?;
+    let bps_denominator = assign_free_constant(
+        layouter.namespace(|| "constant bps denominator"),
+        advice,
+        pallas::Base::from(BPS_DENOMINATOR),
+    )?;
+    let denominator = mul_chip.mul(
+        layouter.namespace(|| "10000 * period"),
+        &bps_denominator,
+        period,
+    )?;
+    let accrued = {
+        let scaled = mul_chip.mul(
+            layouter.namespace(|| "principal * rate_bps"),
+            principal,
+            rate_bps,
+        )?;
+        mul_chip.mul(
+            layouter.namespace(|| "principal * rate_bps * elapsed"),
+            &scaled,
+            &elapsed,
+        )?
+    };
+    // The product doesn't fit in 128 bits, so rem is witnessed in the field from the redemption.
+    // A wrong redemption gives a rem out of range.
+    let rem = assign_free_advice(
+        layouter.namespace(|| "witness rem"),
+        advice,
+        accrued
+            .value()
+            .zip(interest.value())
+            .zip(denominator.value())
+            .map(|((accrued, interest), denominator)| accrued - interest * denominator),
+    )?;
+    range_check(
+        layouter.namespace(|| "rem range check"),
+        lookup_config,
+        &rem,
+        12,
+        8,
+    )?;
+    let rem_gap = {
+        let gap = sub_chip.sub(
+            layouter.namespace(|| "denominator - rem"),
+            &denominator,
+            &rem,
+        )?;
+        sub_chip.sub(layouter.namespace(|| "denominator - rem - 1"), &gap, &one)?
+    };
+    range_check(
+        layouter.namespace(|| "rem < denominator"),
+        lookup_config,
+        &rem_gap,
+        12,
+        8,
+    )?;
+
+    let lhs = {
+        let scaled = mul_chip.mul(
+            layouter.namespace(|| "interest * denominator"),
+            &interest,
+            &denominator,
+        )?;
+        add_chip.add(
+            layouter.namespace(|| "interest * denominator + rem"),
+            &scaled,
+            &rem,
+        )?
+    };
+    layouter.assign_region(
+        || "check redemption",
+        |mut region| region.constrain_equal(lhs.cell(), accrued.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_interest_accrual_gadget() {
+    use crate::circuit::gadgets::assign_free_instance;
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::dev::MockProver;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        principal: u64,
+        rate_bps: u64,
+        start: u64,
+        period: u64,
+        redemption: u64,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let mut witness = |name: &'static str, value: u64| {
+                assign_free_advice(
+                    layouter.namespace(|| name),
+                    config.advices[0],
+                    Value::known(pallas::Base::from(value)),
+                )
+            };
+            let principal = witness("witness principal", self.principal)?;
+            let rate_bps = witness("witness rate_bps", self.rate_bps)?;
+            let start = witness("witness start", self.start)?;
+            let period = witness("witness period", self.period)?;
+            let redemption = witness("witness redemption", self.redemption)?;
+            let now = assign_free_instance(
+                layouter.namespace(|| "public now"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            interest_accrual_gadget(
+                layouter.namespace(|| "interest accrual"),
+                AddChip::construct(config.add_config, ()),
+                SubChip::construct(config.sub_config, ()),
+                MulChip::construct(config.mul_config),
+                config.conditional_select_config,
+                resource_commit_chip.get_lookup_config(),
+                config.advices[0],
+                &principal,
+                &rate_bps,
+                &start,
+                &period,
+                &now,
+                &redemption,
+            )
+        }
+    }
+
+    // 5% over a period of 1000 from 100
+    let redeem = |now: u64, redemption: u64| {
+        let circuit = GadgetTestCircuit(MyCircuit {
+            principal: 10000,
+            rate_bps: 500,
+            start: 100,
+            period: 1000,
+            redemption,
+        });
+        MockProver::run(11, &circuit, vec![vec![pallas::Base::from(now)]])
+            .unwrap()
+            .verify()
+    };
+
+    // Half of the interest at the midpoint
+    assert_eq!(
+        interest_accrual_redemption(10000, 500, 100, 1000, 600),
+        10250
+    );
+    assert_eq!(redeem(600, 10250), Ok(()));
+    // An inflated redemption
+    assert!(redeem(600, 10251).is_err());
+    // The interest of a part of a unit is rounded down
+    assert_eq!(
+        interest_accrual_redemption(10000, 500, 100, 1000, 103),
+        10001
+    );
+    assert_eq!(redeem(103, 10001), Ok(()));
+    // The elapsed time is clamped to the period
+    assert_eq!(
+        interest_accrual_redemption(10000, 500, 100, 1000, 5000),
+        10500
+    );
+    assert_eq!(redeem(5000, 10500), Ok(()));
+    assert!(redeem(5000, 10501).is_err());
+    // A redemption before the start
+    assert!(redeem(50, 10000).is_err());
+}
@@ -0,0 +1,265 @@
+/// Constrain claimed = floor(total * (t - start) / (end - start)), with t the time now clamped to [start, end]
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_advice, assign_free_constant,
+    coin_selection::u64_range_check,
+    conditional_select::ConditionalSelectConfig,
+    mul::{MulChip, MulInstructions},
+    sub::{SubChip, SubInstructions},
+};
+use crate::utils::to_u64;
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// The quantity unlocked at now by a linear vesting of total from start to end
+pub fn linear_vesting_claimable(total: u64, start: u64, end: u64, now: u64) -> u64 {
+    assert!(start < end);
+    let elapsed = now.clamp(start, end) - start;
+    (total as u128 * elapsed as u128 / (end - start) as u128) as u64
+}
+
+// Check the claimed quantity is the part of the total unlocked at the public time now. All the
+// inputs are 64-bit values, so the products can't wrap around.
+#[allow(clippy::too_many_arguments)]
+pub fn linear_vesting_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    sub_chip: SubChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    conditional_select_config: ConditionalSelectConfig,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    advice: Column<Advice>,
+    total: &AssignedCell<pallas::Base, pallas::Base>,
+    start: &AssignedCell<pallas::Base, pallas::Base>,
+    end: &AssignedCell<pallas::Base, pallas::Base>,
+    now: &AssignedCell<pallas::Base, pallas::Base>,
+    claimed: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    for value in [total, start, end, now, claimed] {
+        u64_range_check(
+            layouter.namespace(|| "vesting range check"),
+            lookup_config,
+            value,
+        )?;
+    }
+    let one = assign_free_constant(
+        layouter.namespace(|| "constant one"),
+        advice,
+        pallas::Base::one(),
+    )?;
+
+    // start < end
+    let duration = sub_chip.sub(layouter.namespace(|| "end - start"), end, start)?;
+    let duration_minus_one =
+        sub_chip.sub(layouter.namespace(|| "duration - 1"), &duration, &one)?;
+    u64_range_check(
+        layouter.namespace(|| "start < end"),
+        lookup_config,
+        &duration_minus_one,
+    )?;
+
+    // is_before = 1 only if now < start, and is_after = 1 only if now >= end
+    let is_before = assign_free_advice(
+        layouter.namespace(|| "witness is_before"),
+        advice,
+        now.value()
+            .zip(start.value())
+            .map(|(now, start)| pallas::Base::from(to_u64(now) < to_u64(start))),
+    )?;
+    let is_after = assign_free_advice(
+        layouter.namespace(|| "witness is_after"),
+        advice,
+        now.value()
+            .zip(end.value())
+            .map(|(now, end)| pallas::Base::from(to_u64(now) >= to_u64(end))),
+    )?;
+    for flag in [&is_before, &is_after] {
+        let flag_square = mul_chip.mul(layouter.namespace(|| "flag * flag"), flag, flag)?;
+        layouter.assign_region(
+            || "bool_check flag",
+            |mut region| region.constrain_equal(flag_square.cell(), flag.cell()),
+        )?;
+    }
+    // is_before * (start - now - 1) and is_after * (now - end) are in 64 bits
+    let before_gap = {
+        let gap = sub_chip.sub(layouter.namespace(|| "start - now"), start, now)?;
+        let gap = sub_chip.sub(layouter.namespace(|| "start - now - 1"), &gap, &one)?;
+        mul_chip.mul(layouter.namespace(|| "is_before * gap"), &is_before, &gap)?
+    };
+    u64_range_check(
+        layouter.namespace(|| "now < start"),
+        lookup_config,
+        &before_gap,
+    )?;
+    let after_gap = {
+        let gap = sub_chip.sub(layouter.namespace(|| "now - end"), now, end)?;
+        mul_chip.mul(layouter.namespace(|| "is_after * gap"), &is_after, &gap)?
+    };
+    u64_range_check(
+        layouter.namespace(|| "now >= end"),
+        lookup_config,
+        &after_gap,
+    )?;
+
+    // t = is_after ? end : (is_before ? start : now), and start <= t <= end so that a wrong flag
+    // of zero fails
+    let t = layouter.assign_region(
+        || "clamp now",
+        |mut region| {
+            let t =
+                conditional_select_config.assign_region(&is_before, start, now, 0, &mut region)?;
+            conditional_select_config.assign_region(&is_after, end, &t, 1, &mut region)
+        },
+    )?;
+    let elapsed = sub_chip.sub(layouter.namespace(|| "t - start"), &t, start)?;
+    u64_range_check(layouter.namespace(|| "start <= t"), lookup_config, &elapsed)?;
+    let remaining = sub_chip.sub(layouter.namespace(|| "end - t"), end, &t)?;
+    u64_range_check(layouter.namespace(|| "t <= end"), lookup_config, &remaining)?;
+
+    // claimed * duration + rem = total * elapsed, with rem < duration
+    let rem = assign_free_advice(
+        layouter.namespace(|| "witness rem"),
+        advice,
+        total
+            .value()
+            .zip(elapsed.value())
+            .zip(duration.value())
+            .map(|((total, elapsed), duration)| {
+                // A zero duration gets a dummy rem, which fails rem < duration
+                let product = to_u64(total) as u128 * to_u64(elapsed) as u128;
+                let rem = product.checked_rem(to_u64(duration) as u128).unwrap_or(0);
+                pallas::Base::from(rem as u64)
+            }),
+    )?;
+    u64_range_check(
+        layouter.namespace(|| "rem range check"),
+        lookup_config,
+        &rem,
+    )?;
+    let rem_gap = sub_chip.sub(
+        layouter.namespace(|| "duration - 1 - rem"),
+        &duration_minus_one,
+        &rem,
+    )?;
+    u64_range_check(
+        layouter.namespace(|| "rem < duration"),
+        lookup_config,
+        &rem_gap,
+    )?;
+
+    let lhs = {
+        let scaled = mul_chip.mul(
+            layouter.namespace(|| "claimed * duration"),
+            claimed,
+            &duration,
+        )?;
+        add_chip.add(
+            layouter.namespace(|| "claimed * duration + rem"),
+            &scaled,
+            &rem,
+        )?
+    };
+    let rhs = mul_chip.mul(layouter.namespace(|| "total * elapsed"), total, &elapsed)?;
+    layouter.assign_region(
+        || "check claimed",
+        |mut region| region.constrain_equal(lhs.cell(), rhs.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_linear_vesting_gadget() {
+    use crate::circuit::gadgets::assign_free_instance;
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::dev::MockProver;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        total: u64,
+        start: u64,
+        end: u64,
+        claimed: u64,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let mut witness = |name: &'static str, value: u64| {
+                assign_free_advice(
+                    layouter.namespace(|| name),
+                    config.advices[0],
+                    Value::known(pallas::Base::from(value)),
+                )
+            };
+            let total = witness("witness total", self.total)?;
+            let start = witness("witness start", self.start)?;
+            let end = witness("witness end", self.end)?;
+            let claimed = witness("witness claimed", self.claimed)?;
+            let now = assign_free_instance(
+                layouter.namespace(|| "public now"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            linear_vesting_gadget(
+                layouter.namespace(|| "linear vesting"),
+                AddChip::construct(config.add_config, ()),
+                SubChip::construct(config.sub_config, ()),
+                MulChip::construct(config.mul_config),
+                config.conditional_select_config,
+                resource_commit_chip.get_lookup_config(),
+                config.advices[0],
+                &total,
+                &start,
+                &end,
+                &now,
+                &claimed,
+            )
+        }
+    }
+
+    let vesting = |now: u64, claimed: u64| {
+        let circuit = GadgetTestCircuit(MyCircuit {
+            total: 1000,
+            start: 100,
+            end: 200,
+            claimed,
+        });
+        MockProver::run(11, &circuit, vec![vec![pallas::Base::from(now)]])
+            .unwrap()
+            .verify()
+    };
+
+    // Half of the total at the midpoint
+    assert_eq!(linear_vesting_claimable(1000, 100, 200, 150), 500);
+    assert_eq!(vesting(150, 500), Ok(()));
+    // An over-claim
+    assert!(vesting(150, 501).is_err());
+    // Nothing before the start and everything after the end
+    assert_eq!(vesting(50, 0), Ok(()));
+    assert!(vesting(50, 1).is_err());
+    assert_eq!(vesting(300, 1000), Ok(()));
+    assert!(vesting(300, 1001).is_err());
+
+    // A vesting that ends at its start
+    let circuit = GadgetTestCircuit(MyCircuit {
+        total: 1000,
+        start: 100,
+        end: 100,
+        claimed: 0,
+    });
+    let prover = MockProver::run(11, &circuit, vec![vec![pallas::Base::from(100u64)]]).unwrap();
+    assert!(prover.verify().is_err());
+}
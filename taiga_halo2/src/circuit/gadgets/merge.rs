@@ -0,0 +1,144 @@
+/// Constrain the output resource merges the inputs: same asset and owner, and quantity = sum(input quantities)
+use crate::circuit::gadgets::add::{AddChip, AddInstructions};
+use crate::circuit::vp_circuit::ResourceVariables;
+use halo2_proofs::{circuit::Layouter, plonk::Error};
+use pasta_curves::pallas;
+
+// Check a consolidation of the inputs into one output. The asset is the (logic, label) pair and the
+// owner is encoded in the value. The quantities are 64-bit in the resource commitments, so a sum of a
+// few of them can't wrap around.
+pub fn merge_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    inputs: &[ResourceVariables],
+    output: &ResourceVariables,
+) -> Result<(), Error> {
+    assert!(!inputs.is_empty());
+    layouter.assign_region(
+        || "check merged asset and owner",
+        |mut region| {
+            for input in inputs.iter() {
+                region.constrain_equal(input.logic.cell(), output.logic.cell())?;
+                region.constrain_equal(input.label.cell(), output.label.cell())?;
+                region.constrain_equal(input.value.cell(), output.value.cell())?;
+            }
+            Ok(())
+        },
+    )?;
+
+    let mut quantity_sum = inputs[0].quantity.clone();
+    for input in inputs.iter().skip(1) {
+        quantity_sum = add_chip.add(
+            layouter.namespace(|| "sum input quantities"),
+            &quantity_sum,
+            &input.quantity,
+        )?;
+    }
+    layouter.assign_region(
+        || "check merged quantity",
+        |mut region| region.constrain_equal(quantity_sum.cell(), output.quantity.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_merge_gadget() {
+    use crate::circuit::gadgets::{add::AddConfig, assign_free_advice};
+    use crate::resource::{tests::random_resource, Resource};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        inputs: Vec<Resource>,
+        output: Resource,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = ([Column<Advice>; 2], AddConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [meta.advice_column(), meta.advice_column()];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+            let add_config = AddChip::configure(meta, advices);
+            (advices, add_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, add_config) = config;
+            let mut witness = |name: &'static str, value: pallas::Base| {
+                assign_free_advice(layouter.namespace(|| name), advices[0], Value::known(value))
+            };
+            let mut witness_resource = |resource: &Resource| -> Result<ResourceVariables, Error> {
+                Ok(ResourceVariables {
+                    logic: witness("logic", resource.get_logic())?,
+                    label: witness("label", resource.get_label())?,
+                    quantity: witness("quantity", pallas::Base::from(resource.quantity))?,
+                    is_ephemeral: witness(
+                        "is_ephemeral",
+                        pallas::Base::from(resource.is_ephemeral),
+                    )?,
+                    value: witness("value", resource.value)?,
+                    nonce: witness("nonce", resource.nonce.inner())?,
+                    npk: witness("npk", resource.get_npk())?,
+                    rseed: witness("rseed", resource.rseed)?,
+                })
+            };
+            let inputs = self
+                .inputs
+                .iter()
+                .map(&mut witness_resource)
+                .collect::<Result<Vec<_>, Error>>()?;
+            let output = witness_resource(&self.output)?;
+
+            merge_gadget(
+                layouter.namespace(|| "merge"),
+                AddChip::construct(add_config, ()),
+                &inputs,
+                &output,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let dust = random_resource(&mut rng);
+    let inputs: Vec<Resource> = [3u64, 5, 7]
+        .iter()
+        .map(|quantity| {
+            let mut input = dust;
+            input.quantity = *quantity;
+            input
+        })
+        .collect();
+
+    // 3 + 5 + 7 = 15
+    let mut output = dust;
+    output.quantity = 15;
+    let circuit = MyCircuit {
+        inputs: inputs.clone(),
+        output,
+    };
+    let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The merge changes the owner
+    output.value = pallas::Base::random(&mut rng);
+    let circuit = MyCircuit { inputs, output };
+    let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
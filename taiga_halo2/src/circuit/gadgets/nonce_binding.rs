@@ -0,0 +1,110 @@
+/// Constrain resource.nonce = session_nonce
+use crate::circuit::vp_circuit::ResourceVariables;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// Check the resource is bound to the session nonce, which is expected to be a public input. A
+// resource created in a stale session can't be replayed in the current one.
+pub fn nonce_binding_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    resource: &ResourceVariables,
+    session_nonce: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    layouter.assign_region(
+        || "check nonce binding",
+        |mut region| region.constrain_equal(resource.nonce.cell(), session_nonce.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_nonce_binding_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::nullifier::Nullifier;
+    use crate::resource::{tests::random_resource, Resource};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        resource: Resource,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advice = meta.advice_column();
+            meta.enable_equality(advice);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advice, instance) = config;
+            let mut witness = |name: &'static str, value: pallas::Base| {
+                assign_free_advice(layouter.namespace(|| name), advice, Value::known(value))
+            };
+            let resource = ResourceVariables {
+                logic: witness("logic", self.resource.get_logic())?,
+                label: witness("label", self.resource.get_label())?,
+                quantity: witness("quantity", pallas::Base::from(self.resource.quantity))?,
+                is_ephemeral: witness(
+                    "is_ephemeral",
+                    pallas::Base::from(self.resource.is_ephemeral),
+                )?,
+                value: witness("value", self.resource.value)?,
+                nonce: witness("nonce", self.resource.nonce.inner())?,
+                npk: witness("npk", self.resource.get_npk())?,
+                rseed: witness("rseed", self.resource.rseed)?,
+            };
+
+            let session_nonce =
+                assign_free_instance(layouter.namespace(|| "session nonce"), instance, 0, advice)?;
+
+            nonce_binding_gadget(
+                layouter.namespace(|| "nonce binding"),
+                &resource,
+                &session_nonce,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let session_nonce = pallas::Base::random(&mut rng);
+    let mut resource = random_resource(&mut rng);
+    resource.nonce = Nullifier::from(session_nonce);
+    let circuit = MyCircuit { resource };
+
+    // The resource is created in the current session
+    let prover = MockProver::run(6, &circuit, vec![vec![session_nonce]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The resource is created with a stale nonce
+    let stale_nonce = pallas::Base::random(&mut rng);
+    let circuit = MyCircuit {
+        resource: Resource {
+            nonce: Nullifier::from(stale_nonce),
+            ..resource
+        },
+    };
+    let prover = MockProver::run(6, &circuit, vec![vec![session_nonce]]).unwrap();
+    assert!(prover.verify().is_err());
+}
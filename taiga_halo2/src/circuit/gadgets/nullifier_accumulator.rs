@@ -0,0 +1,163 @@
+/// Constrain acc = poseidon_hash(...poseidon_hash(prev_acc, nf_0)..., nf_n) and member is one of the nf_i
+use crate::circuit::gadgets::{
+    mul::{MulChip, MulInstructions},
+    poseidon_hash::poseidon_hash_gadget,
+    sub::{SubChip, SubInstructions},
+};
+use crate::nullifier::Nullifier;
+use crate::utils::poseidon_hash;
+use halo2_gadgets::poseidon::Pow5Config as PoseidonConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// Absorb the spent nullifiers into the accumulator, in order
+pub fn accumulate_nullifiers(prev_acc: pallas::Base, nullifiers: &[Nullifier]) -> pallas::Base {
+    nullifiers
+        .iter()
+        .fold(prev_acc, |acc, nf| poseidon_hash(acc, nf.inner()))
+}
+
+// Check the accumulator is prev_acc updated with the nullifiers and return it
+pub fn nullifier_accumulator_gadget(
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    mut layouter: impl Layouter<pallas::Base>,
+    prev_acc: &AssignedCell<pallas::Base, pallas::Base>,
+    nullifiers: &[AssignedCell<pallas::Base, pallas::Base>],
+    acc: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let mut expected_acc = prev_acc.clone();
+    for nf in nullifiers.iter() {
+        expected_acc = poseidon_hash_gadget(
+            poseidon_config.clone(),
+            layouter.namespace(|| "absorb nullifier"),
+            [expected_acc, nf.clone()],
+        )?;
+    }
+    layouter.assign_region(
+        || "check nullifier accumulator",
+        |mut region| region.constrain_equal(expected_acc.cell(), acc.cell()),
+    )
+}
+
+// Check the member is one of the accumulated nullifiers: prod(nf_i - member) = 0
+pub fn accumulated_nullifier_membership_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    sub_chip: SubChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    nullifiers: &[AssignedCell<pallas::Base, pallas::Base>],
+    member: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    assert!(!nullifiers.is_empty());
+    let mut product = sub_chip.sub(layouter.namespace(|| "nf - member"), &nullifiers[0], member)?;
+    for nf in nullifiers.iter().skip(1) {
+        let diff = sub_chip.sub(layouter.namespace(|| "nf - member"), nf, member)?;
+        product = mul_chip.mul(layouter.namespace(|| "product * diff"), &product, &diff)?;
+    }
+    layouter.assign_region(
+        || "check membership",
+        |mut region| region.constrain_constant(product.cell(), pallas::Base::zero()),
+    )
+}
+
+#[test]
+fn test_halo2_nullifier_accumulator_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{arithmetic::Field, circuit::Value, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        nullifiers: Vec<Nullifier>,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let nullifiers = self
+                .nullifiers
+                .iter()
+                .map(|nf| {
+                    assign_free_advice(
+                        layouter.namespace(|| "witness nullifier"),
+                        config.advices[0],
+                        Value::known(nf.inner()),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let prev_acc = assign_free_instance(
+                layouter.namespace(|| "previous accumulator"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+            let acc = assign_free_instance(
+                layouter.namespace(|| "accumulator"),
+                config.instances,
+                1,
+                config.advices[0],
+            )?;
+            let member = assign_free_instance(
+                layouter.namespace(|| "member"),
+                config.instances,
+                2,
+                config.advices[0],
+            )?;
+
+            nullifier_accumulator_gadget(
+                config.poseidon_config,
+                layouter.namespace(|| "nullifier accumulator"),
+                &prev_acc,
+                &nullifiers,
+                &acc,
+            )?;
+            accumulated_nullifier_membership_gadget(
+                layouter.namespace(|| "nullifier membership"),
+                SubChip::construct(config.sub_config, ()),
+                MulChip::construct(config.mul_config),
+                &nullifiers,
+                &member,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let prev_acc = pallas::Base::random(&mut rng);
+    let nullifiers: Vec<Nullifier> = (0..3).map(|_| Nullifier::random(&mut rng)).collect();
+    let acc = accumulate_nullifiers(prev_acc, &nullifiers);
+    let circuit = GadgetTestCircuit(MyCircuit {
+        nullifiers: nullifiers.clone(),
+    });
+
+    // A spent nullifier is a member of the accumulator
+    let prover = MockProver::run(
+        11,
+        &circuit,
+        vec![vec![prev_acc, acc, nullifiers[1].inner()]],
+    )
+    .unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A non-member
+    let other = Nullifier::random(&mut rng).inner();
+    let prover = MockProver::run(11, &circuit, vec![vec![prev_acc, acc, other]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The accumulator wasn't updated with the nullifiers
+    let prover = MockProver::run(
+        11,
+        &circuit,
+        vec![vec![prev_acc, prev_acc, nullifiers[1].inner()]],
+    )
+    .unwrap();
+    assert!(prover.verify().is_err());
+}
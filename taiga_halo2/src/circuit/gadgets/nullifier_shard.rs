@@ -0,0 +1,166 @@
+/// Constrain nf = shard + rest * 2^shard_bits, with shard in shard_bits bits and rest in 254 - shard_bits bits
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_advice, assign_free_constant,
+    decompose_128::range_check,
+    mul::{MulChip, MulInstructions},
+};
+use crate::nullifier::Nullifier;
+use ff::PrimeField;
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// The shard of a nullifier is its low shard_bits bits
+pub fn nullifier_shard(nf: &Nullifier, shard_bits: usize) -> u64 {
+    assert!(shard_bits > 0 && shard_bits < 10);
+    (nf.inner().to_repr()[0] as u64) & ((1 << shard_bits) - 1)
+}
+
+// Split the nullifier into its shard and the rest
+fn split_nullifier(nf: pallas::Base, shard_bits: usize) -> (pallas::Base, pallas::Base) {
+    let shard = nullifier_shard(&Nullifier::from(nf), shard_bits);
+    let shift = pallas::Base::from(1u64 << shard_bits);
+    let rest = (nf - pallas::Base::from(shard)) * shift.invert().unwrap();
+    (pallas::Base::from(shard), rest)
+}
+
+// Check the nullifier belongs to the claimed shard. The recomposition is below 2^254 so it can't
+// wrap around, and a nullifier can't be claimed for another shard than its low bits.
+#[allow(clippy::too_many_arguments)]
+pub fn nullifier_shard_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    advice: Column<Advice>,
+    nf: &AssignedCell<pallas::Base, pallas::Base>,
+    shard: &AssignedCell<pallas::Base, pallas::Base>,
+    shard_bits: usize,
+) -> Result<(), Error> {
+    assert!(shard_bits > 0 && shard_bits < 10);
+    lookup_config.copy_short_check(
+        layouter.namespace(|| "shard range check"),
+        shard.clone(),
+        shard_bits,
+    )?;
+
+    let rest = assign_free_advice(
+        layouter.namespace(|| "witness rest"),
+        advice,
+        nf.value().map(|nf| split_nullifier(*nf, shard_bits).1),
+    )?;
+    // The short check takes less than 10 bits, so a multiple of 10 bits is checked by words only
+    let rest_bits = 254 - shard_bits;
+    if rest_bits % 10 == 0 {
+        lookup_config.copy_check(
+            layouter.namespace(|| "rest range check"),
+            rest.clone(),
+            rest_bits / 10,
+            true,
+        )?;
+    } else {
+        range_check(
+            layouter.namespace(|| "rest range check"),
+            lookup_config,
+            &rest,
+            rest_bits / 10,
+            rest_bits % 10,
+        )?;
+    }
+
+    // nf = shard + rest * 2^shard_bits
+    let shift = assign_free_constant(
+        layouter.namespace(|| "constant 2^shard_bits"),
+        advice,
+        pallas::Base::from(1u64 << shard_bits),
+    )?;
+    let shifted_rest = mul_chip.mul(layouter.namespace(|| "rest * 2^shard_bits"), &rest, &shift)?;
+    let recomposed = add_chip.add(
+        layouter.namespace(|| "shard + rest * 2^shard_bits"),
+        shard,
+        &shifted_rest,
+    )?;
+    layouter.assign_region(
+        || "check nullifier shard",
+        |mut region| region.constrain_equal(recomposed.cell(), nf.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_nullifier_shard_gadget() {
+    use crate::circuit::gadgets::assign_free_instance;
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    const SHARD_BITS: usize = 4;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        nf: Nullifier,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let nf = assign_free_advice(
+                layouter.namespace(|| "witness nullifier"),
+                config.advices[0],
+                Value::known(self.nf.inner()),
+            )?;
+            let shard = assign_free_instance(
+                layouter.namespace(|| "source shard"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            nullifier_shard_gadget(
+                layouter.namespace(|| "nullifier shard"),
+                AddChip::construct(config.add_config, ()),
+                MulChip::construct(config.mul_config),
+                resource_commit_chip.get_lookup_config(),
+                config.advices[0],
+                &nf,
+                &shard,
+                SHARD_BITS,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let nf = Nullifier::random(&mut rng);
+    let shard = nullifier_shard(&nf, SHARD_BITS);
+    let circuit = GadgetTestCircuit(MyCircuit { nf });
+
+    let prover = MockProver::run(11, &circuit, vec![vec![pallas::Base::from(shard)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The nullifier is claimed for another shard
+    let other_shard = (shard + 1) % (1 << SHARD_BITS);
+    let prover =
+        MockProver::run(11, &circuit, vec![vec![pallas::Base::from(other_shard)]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The claimed shard is out of range, even though nf - shard is a multiple of 2^SHARD_BITS
+    let prover = MockProver::run(
+        11,
+        &circuit,
+        vec![vec![pallas::Base::from(shard + (1 << SHARD_BITS))]],
+    )
+    .unwrap();
+    assert!(prover.verify().is_err());
+}
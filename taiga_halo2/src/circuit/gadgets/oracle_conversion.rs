@@ -0,0 +1,152 @@
+/// Constrain out_quantity = in_quantity * rate, where the rate is signed by the oracle
+use crate::circuit::gadgets::{
+    mul::{MulChip, MulInstructions},
+    schnorr_verify::{schnorr_verify_gadget, SchnorrSignature},
+};
+use crate::constant::TaigaFixedBases;
+use halo2_gadgets::{
+    ecc::{chip::EccChip, NonIdentityPoint},
+    poseidon::Pow5Config as PoseidonConfig,
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// Verify the oracle signature over the rate and check the conversion of the quantities. The caller
+// binds oracle_pk to the oracle, e.g. by publishing its coordinates.
+#[allow(clippy::too_many_arguments)]
+pub fn oracle_conversion_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    ecc_chip: EccChip<TaigaFixedBases>,
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    mul_chip: MulChip<pallas::Base>,
+    oracle_pk: &NonIdentityPoint<pallas::Affine, EccChip<TaigaFixedBases>>,
+    signature: &SchnorrSignature,
+    rate: &AssignedCell<pallas::Base, pallas::Base>,
+    in_quantity: &AssignedCell<pallas::Base, pallas::Base>,
+    out_quantity: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    schnorr_verify_gadget(
+        layouter.namespace(|| "verify oracle signature"),
+        ecc_chip,
+        poseidon_config,
+        oracle_pk,
+        signature,
+        rate.clone(),
+    )?;
+
+    let converted = mul_chip.mul(
+        layouter.namespace(|| "in_quantity * rate"),
+        in_quantity,
+        rate,
+    )?;
+    layouter.assign_region(
+        || "check conversion",
+        |mut region| region.constrain_equal(converted.cell(), out_quantity.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_oracle_conversion_gadget() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{arithmetic::Field, circuit::Value, dev::MockProver};
+    use pasta_curves::{arithmetic::CurveAffine, group::Curve};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        signature: SchnorrSignature,
+        rate: pallas::Base,
+        in_quantity: u64,
+        out_quantity: u64,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let ecc_chip = EccChip::construct(config.ecc_config);
+            let pk = NonIdentityPoint::new(
+                ecc_chip.clone(),
+                layouter.namespace(|| "witness pk"),
+                Value::known(self.signature.pk.to_affine()),
+            )?;
+            // The oracle public key is public
+            layouter.constrain_instance(pk.inner().x().cell(), config.instances, 0)?;
+            layouter.constrain_instance(pk.inner().y().cell(), config.instances, 1)?;
+
+            let rate = assign_free_advice(
+                layouter.namespace(|| "witness rate"),
+                config.advices[0],
+                Value::known(self.rate),
+            )?;
+            let in_quantity = assign_free_advice(
+                layouter.namespace(|| "witness in_quantity"),
+                config.advices[0],
+                Value::known(pallas::Base::from(self.in_quantity)),
+            )?;
+            let out_quantity = assign_free_advice(
+                layouter.namespace(|| "witness out_quantity"),
+                config.advices[0],
+                Value::known(pallas::Base::from(self.out_quantity)),
+            )?;
+
+            oracle_conversion_gadget(
+                layouter.namespace(|| "oracle conversion"),
+                ecc_chip,
+                config.poseidon_config,
+                MulChip::construct(config.mul_config),
+                &pk,
+                &self.signature,
+                &rate,
+                &in_quantity,
+                &out_quantity,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let oracle_sk = pallas::Scalar::random(&mut rng);
+    let rate = pallas::Base::from(3u64);
+    let signature = SchnorrSignature::sign(&mut rng, oracle_sk, rate);
+    let oracle_pk = signature.pk.to_affine().coordinates().unwrap();
+    let public_inputs = vec![*oracle_pk.x(), *oracle_pk.y()];
+
+    // Convert 10 at the signed rate 3
+    let circuit = GadgetTestCircuit(MyCircuit {
+        signature,
+        rate,
+        in_quantity: 10,
+        out_quantity: 30,
+    });
+    let prover = MockProver::run(12, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A forged rate 4 with the signature of the rate 3
+    let circuit = GadgetTestCircuit(MyCircuit {
+        signature,
+        rate: pallas::Base::from(4u64),
+        in_quantity: 10,
+        out_quantity: 40,
+    });
+    let prover = MockProver::run(12, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The conversion doesn't follow the signed rate
+    let circuit = GadgetTestCircuit(MyCircuit {
+        signature,
+        rate,
+        in_quantity: 10,
+        out_quantity: 40,
+    });
+    let prover = MockProver::run(12, &circuit, vec![public_inputs]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,126 @@
+/// Constrain owner = poseidon_hash(pk.x, pk.y), where pk signs the public binding message
+use crate::circuit::gadgets::{
+    poseidon_hash::poseidon_hash_gadget,
+    schnorr_verify::{schnorr_verify_gadget, SchnorrSignature},
+};
+use crate::constant::TaigaFixedBases;
+use crate::utils::poseidon_hash;
+use halo2_gadgets::{
+    ecc::{chip::EccChip, NonIdentityPoint},
+    poseidon::Pow5Config as PoseidonConfig,
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::Error,
+};
+use pasta_curves::{arithmetic::CurveAffine, group::Curve, pallas};
+
+// The owner address of a resource controlled by the key pk
+pub fn owner_address(pk: &pallas::Point) -> pallas::Base {
+    let pk_coord = pk.to_affine().coordinates().unwrap();
+    poseidon_hash(*pk_coord.x(), *pk_coord.y())
+}
+
+// Verify the signature on the binding message of the transaction and check the owner of the
+// resource is the address of the signer. The signer pk is taken from the signature and stays private.
+pub fn owner_is_signer_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    ecc_chip: EccChip<TaigaFixedBases>,
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    signature: &SchnorrSignature,
+    binding_message: &AssignedCell<pallas::Base, pallas::Base>,
+    owner: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let pk = NonIdentityPoint::new(
+        ecc_chip.clone(),
+        layouter.namespace(|| "witness signer pk"),
+        Value::known(signature.pk.to_affine()),
+    )?;
+    schnorr_verify_gadget(
+        layouter.namespace(|| "verify binding signature"),
+        ecc_chip,
+        poseidon_config.clone(),
+        &pk,
+        signature,
+        binding_message.clone(),
+    )?;
+
+    let signer_address = poseidon_hash_gadget(
+        poseidon_config,
+        layouter.namespace(|| "derive signer address"),
+        [pk.inner().x(), pk.inner().y()],
+    )?;
+    layouter.assign_region(
+        || "check owner is signer",
+        |mut region| region.constrain_equal(signer_address.cell(), owner.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_owner_is_signer_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{arithmetic::Field, dev::MockProver};
+    use pasta_curves::group::Group;
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        signature: SchnorrSignature,
+        owner: pallas::Base,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let binding_message = assign_free_instance(
+                layouter.namespace(|| "public binding message"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+            let owner = assign_free_advice(
+                layouter.namespace(|| "witness owner"),
+                config.advices[0],
+                Value::known(self.owner),
+            )?;
+
+            owner_is_signer_gadget(
+                layouter.namespace(|| "owner is signer"),
+                EccChip::construct(config.ecc_config),
+                config.poseidon_config,
+                &self.signature,
+                &binding_message,
+                &owner,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let binding_message = pallas::Base::random(&mut rng);
+    let signer_sk = pallas::Scalar::random(&mut rng);
+    let signature = SchnorrSignature::sign(&mut rng, signer_sk, binding_message);
+
+    // The resource is owned by the signer
+    let circuit = GadgetTestCircuit(MyCircuit {
+        signature,
+        owner: owner_address(&signature.pk),
+    });
+    let prover = MockProver::run(12, &circuit, vec![vec![binding_message]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The resource is owned by someone who didn't sign
+    let other_pk = pallas::Point::random(&mut rng);
+    let circuit = GadgetTestCircuit(MyCircuit {
+        signature,
+        owner: owner_address(&other_pk),
+    });
+    let prover = MockProver::run(12, &circuit, vec![vec![binding_message]]).unwrap();
+    assert!(prover.verify().is_err());
+}
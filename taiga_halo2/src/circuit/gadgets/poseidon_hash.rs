@@ -22,3 +22,137 @@ pub fn poseidon_hash_gadget<const L: usize>(
 
     poseidon_hasher.hash(layouter.namespace(|| "poseidon hash"), messages)
 }
+
+// The arity-2 poseidon hash, whose native counterpart is `utils::poseidon_hash`. It's the hash of the
+// merkle tree internal nodes.
+pub fn poseidon_hash_two_gadget(
+    config: PoseidonConfig<pallas::Base, 3, 2>,
+    layouter: impl Layouter<pallas::Base>,
+    left: &AssignedCell<pallas::Base, pallas::Base>,
+    right: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<AssignedCell<pallas::Base, pallas::Base>, Error> {
+    poseidon_hash_gadget(config, layouter, [left.clone(), right.clone()])
+}
+
+#[test]
+fn test_halo2_poseidon_hash_two_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::merkle_tree::Node;
+    use crate::utils::poseidon_hash;
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        left: pallas::Base,
+        right: pallas::Base,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        #[allow(clippy::type_complexity)]
+        type Config = (
+            [Column<Advice>; 4],
+            Column<Instance>,
+            PoseidonConfig<pallas::Base, 3, 2>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let lagrange_coeffs = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let poseidon_config = PoseidonChip::configure::<poseidon::P128Pow5T3>(
+                meta,
+                advices[1..4].try_into().unwrap(),
+                advices[0],
+                lagrange_coeffs[0..3].try_into().unwrap(),
+                lagrange_coeffs[3..6].try_into().unwrap(),
+            );
+            (advices, instance, poseidon_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, poseidon_config) = config;
+            let left = assign_free_advice(
+                layouter.namespace(|| "witness left"),
+                advices[0],
+                Value::known(self.left),
+            )?;
+            let right = assign_free_advice(
+                layouter.namespace(|| "witness right"),
+                advices[0],
+                Value::known(self.right),
+            )?;
+            let expected_hash = assign_free_instance(
+                layouter.namespace(|| "public hash"),
+                instance,
+                0,
+                advices[0],
+            )?;
+
+            let hash = poseidon_hash_two_gadget(
+                poseidon_config,
+                layouter.namespace(|| "poseidon hash two"),
+                &left,
+                &right,
+            )?;
+            layouter.assign_region(
+                || "check hash",
+                |mut region| region.constrain_equal(hash.cell(), expected_hash.cell()),
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let left = pallas::Base::random(&mut rng);
+    let right = pallas::Base::random(&mut rng);
+    let hash = poseidon_hash(left, right);
+    // The merkle tree internal nodes use the same hash
+    assert_eq!(
+        Node::combine(&Node::from(left), &Node::from(right)).inner(),
+        hash
+    );
+
+    let circuit = MyCircuit { left, right };
+    let prover = MockProver::run(8, &circuit, vec![vec![hash]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The hash is not commutative
+    let prover = MockProver::run(8, &circuit, vec![vec![poseidon_hash(right, left)]]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,146 @@
+/// Constrain a chosen field of the spent resource equals the same field of its replacement
+use crate::circuit::vp_circuit::ResourceVariables;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+/// The fields of the resource variables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceField {
+    Logic,
+    Label,
+    Quantity,
+    IsEphemeral,
+    Value,
+    Nonce,
+    Npk,
+    Rseed,
+}
+
+impl ResourceField {
+    pub fn get<'a>(
+        &self,
+        resource_variables: &'a ResourceVariables,
+    ) -> &'a AssignedCell<pallas::Base, pallas::Base> {
+        match self {
+            ResourceField::Logic => &resource_variables.logic,
+            ResourceField::Label => &resource_variables.label,
+            ResourceField::Quantity => &resource_variables.quantity,
+            ResourceField::IsEphemeral => &resource_variables.is_ephemeral,
+            ResourceField::Value => &resource_variables.value,
+            ResourceField::Nonce => &resource_variables.nonce,
+            ResourceField::Npk => &resource_variables.npk,
+            ResourceField::Rseed => &resource_variables.rseed,
+        }
+    }
+}
+
+// Check an upgrade of the resource keeps the invariant field, e.g. a serial number encoded in the
+// value, while the other fields may change
+pub fn preserve_field_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    field: ResourceField,
+    input_resource: &ResourceVariables,
+    output_resource: &ResourceVariables,
+) -> Result<(), Error> {
+    layouter.assign_region(
+        || "check preserved field",
+        |mut region| {
+            region.constrain_equal(
+                field.get(input_resource).cell(),
+                field.get(output_resource).cell(),
+            )
+        },
+    )
+}
+
+#[test]
+fn test_halo2_preserve_field_gadget() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::resource::{tests::random_resource, Resource};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        input_resource: Resource,
+        output_resource: Resource,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advice = meta.advice_column();
+            meta.enable_equality(advice);
+            advice
+        }
+
+        fn synthesize(
+            &self,
+            advice: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let mut witness = |name: &'static str, value: pallas::Base| {
+                assign_free_advice(layouter.namespace(|| name), advice, Value::known(value))
+            };
+            let mut witness_resource = |resource: &Resource| -> Result<ResourceVariables, Error> {
+                Ok(ResourceVariables {
+                    logic: witness("logic", resource.get_logic())?,
+                    label: witness("label", resource.get_label())?,
+                    quantity: witness("quantity", pallas::Base::from(resource.quantity))?,
+                    is_ephemeral: witness(
+                        "is_ephemeral",
+                        pallas::Base::from(resource.is_ephemeral),
+                    )?,
+                    value: witness("value", resource.value)?,
+                    nonce: witness("nonce", resource.nonce.inner())?,
+                    npk: witness("npk", resource.get_npk())?,
+                    rseed: witness("rseed", resource.rseed)?,
+                })
+            };
+            let input_resource = witness_resource(&self.input_resource)?;
+            let output_resource = witness_resource(&self.output_resource)?;
+
+            preserve_field_gadget(
+                layouter.namespace(|| "preserve serial number"),
+                ResourceField::Value,
+                &input_resource,
+                &output_resource,
+            )
+        }
+    }
+
+    // The replacement keeps the serial number in the value
+    let mut rng = OsRng;
+    let input_resource = random_resource(&mut rng);
+    let mut output_resource = random_resource(&mut rng);
+    output_resource.value = input_resource.value;
+    let circuit = MyCircuit {
+        input_resource,
+        output_resource,
+    };
+    let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The serial number is changed
+    output_resource.value = pallas::Base::random(&mut rng);
+    let circuit = MyCircuit {
+        input_resource,
+        output_resource,
+    };
+    let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
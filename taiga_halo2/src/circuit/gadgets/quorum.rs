@@ -0,0 +1,200 @@
+/// Constrain K distinct members of the approver tree signed the action
+use crate::circuit::gadgets::{
+    assign_free_advice,
+    mul::{MulChip, MulInstructions},
+    poseidon_hash::poseidon_hash_gadget,
+    schnorr_verify::{schnorr_verify_gadget, SchnorrSignature},
+    sub::{SubChip, SubInstructions},
+};
+use crate::circuit::merkle_circuit::{merkle_poseidon_gadget, MerklePoseidonChip};
+use crate::constant::TaigaFixedBases;
+use crate::merkle_tree::MerklePath;
+use halo2_gadgets::ecc::{chip::EccChip, NonIdentityPoint};
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::{group::Curve, pallas};
+
+/// The signature of an approver on the action and the path of the approver address in the
+/// approver tree. The leaves of the tree are the `owner_address` of the approver keys.
+#[derive(Clone, Debug, Default)]
+pub struct QuorumApproval {
+    pub signature: SchnorrSignature,
+    pub merkle_path: MerklePath,
+}
+
+// Check a quorum of K approvals. Each signer address is a member of the approver tree, and the
+// addresses are pairwise distinct so that one approver can't be counted twice.
+#[allow(clippy::too_many_arguments)]
+pub fn quorum_gadget<const K: usize>(
+    mut layouter: impl Layouter<pallas::Base>,
+    ecc_chip: EccChip<TaigaFixedBases>,
+    merkle_chip: MerklePoseidonChip,
+    sub_chip: SubChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    advice: Column<Advice>,
+    approvals: &[QuorumApproval; K],
+    action: &AssignedCell<pallas::Base, pallas::Base>,
+    approver_root: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    assert!(K > 0);
+    let poseidon_config = merkle_chip.get_poseidon_config();
+    let mut addresses: Vec<AssignedCell<pallas::Base, pallas::Base>> = Vec::with_capacity(K);
+    for approval in approvals.iter() {
+        let pk = NonIdentityPoint::new(
+            ecc_chip.clone(),
+            layouter.namespace(|| "witness approver pk"),
+            Value::known(approval.signature.pk.to_affine()),
+        )?;
+        schnorr_verify_gadget(
+            layouter.namespace(|| "verify approval"),
+            ecc_chip.clone(),
+            poseidon_config.clone(),
+            &pk,
+            &approval.signature,
+            action.clone(),
+        )?;
+
+        let address = poseidon_hash_gadget(
+            poseidon_config.clone(),
+            layouter.namespace(|| "derive approver address"),
+            [pk.inner().x(), pk.inner().y()],
+        )?;
+        let root = merkle_poseidon_gadget(
+            layouter.namespace(|| "approver membership"),
+            merkle_chip.clone(),
+            address.clone(),
+            &approval.merkle_path.get_path(),
+        )?;
+        layouter.assign_region(
+            || "check approver root",
+            |mut region| region.constrain_equal(root.cell(), approver_root.cell()),
+        )?;
+
+        // (address - prev_address) * inv = 1 for all the previous addresses
+        for prev_address in addresses.iter() {
+            let diff = sub_chip.sub(
+                layouter.namespace(|| "address - prev_address"),
+                &address,
+                prev_address,
+            )?;
+            let inv = assign_free_advice(
+                layouter.namespace(|| "witness diff inverse"),
+                advice,
+                diff.value()
+                    .map(|diff| diff.invert().unwrap_or(pallas::Base::zero())),
+            )?;
+            let product = mul_chip.mul(layouter.namespace(|| "diff * inv"), &diff, &inv)?;
+            layouter.assign_region(
+                || "check distinct approvers",
+                |mut region| region.constrain_constant(product.cell(), pallas::Base::one()),
+            )?;
+        }
+        addresses.push(address);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_halo2_quorum_gadget() {
+    use crate::circuit::gadgets::{assign_free_instance, owner_is_signer::owner_address};
+    use crate::circuit::merkle_circuit::MerklePoseidonConfig;
+    use crate::circuit::vp_circuit::{
+        testing::{MerkleGadgetTest, MerkleGadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::merkle_tree::{Node, LR};
+    use halo2_proofs::dev::MockProver;
+    use pasta_curves::group::Group;
+    use rand::rngs::OsRng;
+
+    const QUORUM: usize = 3;
+    const APPROVER_TREE_DEPTH: usize = 3;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        approvals: [QuorumApproval; QUORUM],
+    }
+
+    impl MerkleGadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            merkle_config: MerklePoseidonConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let action = assign_free_instance(
+                layouter.namespace(|| "public action"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+            let approver_root = assign_free_instance(
+                layouter.namespace(|| "public approver root"),
+                config.instances,
+                1,
+                config.advices[0],
+            )?;
+
+            quorum_gadget(
+                layouter.namespace(|| "quorum"),
+                EccChip::construct(config.ecc_config),
+                MerklePoseidonChip::construct(merkle_config),
+                SubChip::construct(config.sub_config, ()),
+                MulChip::construct(config.mul_config),
+                config.advices[0],
+                &self.approvals,
+                &action,
+                &approver_root,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let action = pallas::Base::random(&mut rng);
+    // Five approvers, with the tree padded to 2^3 leaves
+    let approver_sks: Vec<pallas::Scalar> =
+        (0..5).map(|_| pallas::Scalar::random(&mut rng)).collect();
+    let mut levels = vec![approver_sks
+        .iter()
+        .map(|sk| Node::from(owner_address(&(pallas::Point::generator() * sk))))
+        .collect::<Vec<_>>()];
+    levels[0].resize(1 << APPROVER_TREE_DEPTH, Node::from(pallas::Base::zero()));
+    for height in 0..APPROVER_TREE_DEPTH {
+        let parents = levels[height]
+            .chunks(2)
+            .map(|pair| Node::combine(&pair[0], &pair[1]))
+            .collect();
+        levels.push(parents);
+    }
+    let approver_root = levels[APPROVER_TREE_DEPTH][0].inner();
+    let approve = |idx: usize| {
+        let path = (0..APPROVER_TREE_DEPTH)
+            .map(|height| {
+                let pos = idx >> height;
+                let sibling = levels[height][pos ^ 1];
+                // The sibling of a left node is on the right
+                (sibling, if pos & 1 == 0 { LR::R } else { LR::L })
+            })
+            .collect();
+        QuorumApproval {
+            signature: SchnorrSignature::sign(OsRng, approver_sks[idx], action),
+            merkle_path: MerklePath::from_path(path),
+        }
+    };
+    let quorum = |approvers: [usize; QUORUM]| {
+        let circuit = MerkleGadgetTestCircuit(MyCircuit {
+            approvals: approvers.map(&approve),
+        });
+        MockProver::run(13, &circuit, vec![vec![action, approver_root]])
+            .unwrap()
+            .verify()
+    };
+
+    // 3 of 5 distinct approvers
+    assert_eq!(quorum([0, 2, 4]), Ok(()));
+    // 2 distinct approvers and a duplicate
+    assert!(quorum([0, 2, 0]).is_err());
+}
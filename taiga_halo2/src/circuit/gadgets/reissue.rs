@@ -0,0 +1,145 @@
+/// Constrain minted <= remaining and updated supply = remaining - minted, with the supply resource preserved
+use crate::circuit::gadgets::{
+    coin_selection::u64_range_check,
+    sub::{SubChip, SubInstructions},
+};
+use crate::circuit::vp_circuit::ResourceVariables;
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{circuit::Layouter, plonk::Error};
+use pasta_curves::pallas;
+
+// Check a re-issuance of an asset capped by a supply resource. The value of the supply resource is
+// the label of the asset it caps, and its quantity is the remaining supply. The supply resource is
+// consumed and recreated with the remaining supply decremented by the minted quantity.
+pub fn reissue_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    sub_chip: SubChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    supply_input: &ResourceVariables,
+    supply_output: &ResourceVariables,
+    minted: &ResourceVariables,
+) -> Result<(), Error> {
+    layouter.assign_region(
+        || "check supply resource",
+        |mut region| {
+            region.constrain_equal(supply_input.logic.cell(), supply_output.logic.cell())?;
+            region.constrain_equal(supply_input.label.cell(), supply_output.label.cell())?;
+            region.constrain_equal(supply_input.value.cell(), supply_output.value.cell())?;
+            region.constrain_equal(supply_input.value.cell(), minted.label.cell())
+        },
+    )?;
+
+    for quantity in [&supply_input.quantity, &minted.quantity] {
+        u64_range_check(
+            layouter.namespace(|| "quantity range check"),
+            lookup_config,
+            quantity,
+        )?;
+    }
+    // remaining - minted doesn't wrap around, so minted <= remaining
+    let remaining = sub_chip.sub(
+        layouter.namespace(|| "remaining - minted"),
+        &supply_input.quantity,
+        &minted.quantity,
+    )?;
+    u64_range_check(
+        layouter.namespace(|| "minted <= remaining"),
+        lookup_config,
+        &remaining,
+    )?;
+    layouter.assign_region(
+        || "check updated supply",
+        |mut region| region.constrain_equal(remaining.cell(), supply_output.quantity.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_reissue_gadget() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::resource::{tests::random_resource, Resource};
+    use halo2_proofs::{arithmetic::Field, circuit::Value, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        supply_input: Resource,
+        supply_output: Resource,
+        minted: Resource,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let advice = config.advices[0];
+            let mut witness = |resource: &Resource| -> Result<ResourceVariables, Error> {
+                let mut witness = |name: &'static str, value: pallas::Base| {
+                    assign_free_advice(layouter.namespace(|| name), advice, Value::known(value))
+                };
+                Ok(ResourceVariables {
+                    logic: witness("logic", resource.get_logic())?,
+                    label: witness("label", resource.get_label())?,
+                    quantity: witness("quantity", pallas::Base::from(resource.quantity))?,
+                    is_ephemeral: witness(
+                        "is_ephemeral",
+                        pallas::Base::from(resource.is_ephemeral),
+                    )?,
+                    value: witness("value", resource.value)?,
+                    nonce: witness("nonce", resource.nonce.inner())?,
+                    npk: witness("npk", resource.get_npk())?,
+                    rseed: witness("rseed", resource.rseed)?,
+                })
+            };
+            let supply_input = witness(&self.supply_input)?;
+            let supply_output = witness(&self.supply_output)?;
+            let minted = witness(&self.minted)?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            reissue_gadget(
+                layouter.namespace(|| "reissue"),
+                SubChip::construct(config.sub_config, ()),
+                resource_commit_chip.get_lookup_config(),
+                &supply_input,
+                &supply_output,
+                &minted,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let asset_label = pallas::Base::random(&mut rng);
+    let mut supply_input = random_resource(&mut rng);
+    supply_input.value = asset_label;
+    supply_input.quantity = 100;
+    let mut minted = random_resource(&mut rng);
+    minted.kind.label = asset_label;
+    let reissue = |minted_quantity: u64, remaining: u64| {
+        let mut supply_output = supply_input;
+        supply_output.quantity = remaining;
+        let mut minted = minted;
+        minted.quantity = minted_quantity;
+        let circuit = GadgetTestCircuit(MyCircuit {
+            supply_input,
+            supply_output,
+            minted,
+        });
+        MockProver::run(11, &circuit, vec![]).unwrap().verify()
+    };
+
+    // 30 of the remaining 100 are minted
+    assert_eq!(reissue(30, 70), Ok(()));
+    // The whole remaining supply is minted
+    assert_eq!(reissue(100, 0), Ok(()));
+    // The supply isn't decremented
+    assert!(reissue(30, 100).is_err());
+    // An over-issuance beyond the remaining supply
+    assert!(reissue(120, 0).is_err());
+    assert!(reissue(120, u64::MAX - 19).is_err());
+}
@@ -0,0 +1,86 @@
+/// Constrain version = expected_version, where expected_version is a circuit constant
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// Reject resources whose encoding version differs from the one fixed in the circuit
+pub fn resource_version_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    version: &AssignedCell<pallas::Base, pallas::Base>,
+    expected_version: u64,
+) -> Result<(), Error> {
+    layouter.assign_region(
+        || "check resource version",
+        |mut region| {
+            region.constrain_constant(version.cell(), pallas::Base::from(expected_version))
+        },
+    )
+}
+
+#[test]
+fn test_halo2_resource_version_gadget() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem},
+    };
+
+    const CURRENT_VERSION: u64 = 2;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        version: u64,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advice = meta.advice_column();
+            meta.enable_equality(advice);
+
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            advice
+        }
+
+        fn synthesize(
+            &self,
+            advice: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let version = assign_free_advice(
+                layouter.namespace(|| "witness version"),
+                advice,
+                Value::known(pallas::Base::from(self.version)),
+            )?;
+
+            resource_version_gadget(
+                layouter.namespace(|| "resource version"),
+                &version,
+                CURRENT_VERSION,
+            )
+        }
+    }
+
+    // The current version
+    let circuit = MyCircuit {
+        version: CURRENT_VERSION,
+    };
+    let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // An old version is rejected
+    let circuit = MyCircuit { version: 1 };
+    let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,158 @@
+/// Constrain each party of the ring forwards its spent resource to the next party: logic, label and
+/// quantity are kept and value(outputs[i]) = value(inputs[i + 1 mod N])
+use crate::circuit::vp_circuit::ResourceVariables;
+use halo2_proofs::{circuit::Layouter, plonk::Error};
+use pasta_curves::pallas;
+
+// Check the ring of N parties clears atomically. Party i spends inputs[i] and creates outputs[i] for
+// the next party, which is owned by the owner of inputs[i + 1]. Every party forwards what it spent,
+// so no party skims and the whole ring conserves the quantities.
+pub fn ring_swap_gadget<const N: usize>(
+    mut layouter: impl Layouter<pallas::Base>,
+    inputs: &[ResourceVariables; N],
+    outputs: &[ResourceVariables; N],
+) -> Result<(), Error> {
+    assert!(N >= 2);
+    layouter.assign_region(
+        || "check ring swap",
+        |mut region| {
+            for (i, (input, output)) in inputs.iter().zip(outputs.iter()).enumerate() {
+                region.constrain_equal(output.logic.cell(), input.logic.cell())?;
+                region.constrain_equal(output.label.cell(), input.label.cell())?;
+                region.constrain_equal(output.quantity.cell(), input.quantity.cell())?;
+                region.constrain_equal(output.value.cell(), inputs[(i + 1) % N].value.cell())?;
+            }
+            Ok(())
+        },
+    )
+}
+
+#[test]
+fn test_halo2_ring_swap_gadget() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::resource::{tests::random_resource, Resource};
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem},
+    };
+    use rand::rngs::OsRng;
+
+    const RING_SIZE: usize = 3;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        inputs: [Resource; RING_SIZE],
+        outputs: [Resource; RING_SIZE],
+    }
+
+    fn assign_resource(
+        mut layouter: impl Layouter<pallas::Base>,
+        advice: Column<Advice>,
+        resource: &Resource,
+    ) -> Result<ResourceVariables, Error> {
+        let mut witness = |name: &'static str, value: pallas::Base| {
+            assign_free_advice(layouter.namespace(|| name), advice, Value::known(value))
+        };
+        Ok(ResourceVariables {
+            logic: witness("logic", resource.get_logic())?,
+            label: witness("label", resource.get_label())?,
+            quantity: witness("quantity", pallas::Base::from(resource.quantity))?,
+            is_ephemeral: witness("is_ephemeral", pallas::Base::from(resource.is_ephemeral))?,
+            value: witness("value", resource.value)?,
+            nonce: witness("nonce", resource.nonce.inner())?,
+            npk: witness("npk", resource.get_npk())?,
+            rseed: witness("rseed", resource.rseed)?,
+        })
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advice = meta.advice_column();
+            meta.enable_equality(advice);
+            advice
+        }
+
+        fn synthesize(
+            &self,
+            advice: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let mut inputs = Vec::with_capacity(RING_SIZE);
+            let mut outputs = Vec::with_capacity(RING_SIZE);
+            for (input, output) in self.inputs.iter().zip(self.outputs.iter()) {
+                inputs.push(assign_resource(
+                    layouter.namespace(|| "input"),
+                    advice,
+                    input,
+                )?);
+                outputs.push(assign_resource(
+                    layouter.namespace(|| "output"),
+                    advice,
+                    output,
+                )?);
+            }
+            ring_swap_gadget::<RING_SIZE>(
+                layouter.namespace(|| "ring swap"),
+                &inputs.try_into().unwrap(),
+                &outputs.try_into().unwrap(),
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    // Each party spends its own asset and receives the asset of the previous party
+    let mut inputs = [(); RING_SIZE].map(|_| random_resource(&mut rng));
+    let mut outputs = [(); RING_SIZE].map(|_| random_resource(&mut rng));
+    for (i, input) in inputs.iter_mut().enumerate() {
+        input.quantity = 100 * (i as u64 + 1);
+    }
+    for (i, output) in outputs.iter_mut().enumerate() {
+        output.kind = inputs[i].kind;
+        output.quantity = inputs[i].quantity;
+        output.value = inputs[(i + 1) % RING_SIZE].value;
+    }
+
+    // The ring balances
+    let circuit = MyCircuit { inputs, outputs };
+    let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The second party skims part of its asset
+    let mut skimmed = outputs;
+    skimmed[1].quantity -= 1;
+    let circuit = MyCircuit {
+        inputs,
+        outputs: skimmed,
+    };
+    let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The second party keeps its asset instead of forwarding it
+    let mut kept = outputs;
+    kept[1].value = inputs[1].value;
+    let circuit = MyCircuit {
+        inputs,
+        outputs: kept,
+    };
+    let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The second party forwards another asset of the same label and quantity
+    let mut other_asset = outputs;
+    other_asset[1].kind.logic = pallas::Base::one();
+    assert_ne!(other_asset[1].kind.logic, inputs[1].kind.logic);
+    let circuit = MyCircuit {
+        inputs,
+        outputs: other_asset,
+    };
+    let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
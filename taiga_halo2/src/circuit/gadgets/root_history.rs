@@ -0,0 +1,192 @@
+/// Constrain new_root descends from old_root through a chain of insertions into empty leaves
+use crate::circuit::gadgets::assign_free_constant;
+use crate::circuit::merkle_circuit::{merkle_poseidon_gadget, MerklePoseidonChip};
+use crate::merkle_tree::LR;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// Check every insertion fills an empty leaf of the current root, and the last one yields new_root.
+// An insertion is the inserted leaf and its merkle path, which is the same before and after the insertion.
+pub fn root_history_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    merkle_chip: MerklePoseidonChip,
+    advice: Column<Advice>,
+    old_root: &AssignedCell<pallas::Base, pallas::Base>,
+    new_root: &AssignedCell<pallas::Base, pallas::Base>,
+    insertions: &[(
+        AssignedCell<pallas::Base, pallas::Base>,
+        Vec<(pallas::Base, LR)>,
+    )],
+) -> Result<(), Error> {
+    let empty_leaf = assign_free_constant(
+        layouter.namespace(|| "empty leaf"),
+        advice,
+        pallas::Base::zero(),
+    )?;
+
+    let mut cur_root = old_root.clone();
+    for (leaf, merkle_path) in insertions.iter() {
+        // The leaf is empty in the current tree
+        let root_before = merkle_poseidon_gadget(
+            layouter.namespace(|| "root before insertion"),
+            merkle_chip.clone(),
+            empty_leaf.clone(),
+            merkle_path,
+        )?;
+        layouter.assign_region(
+            || "check root before insertion",
+            |mut region| region.constrain_equal(root_before.cell(), cur_root.cell()),
+        )?;
+
+        cur_root = merkle_poseidon_gadget(
+            layouter.namespace(|| "root after insertion"),
+            merkle_chip.clone(),
+            leaf.clone(),
+            merkle_path,
+        )?;
+    }
+
+    layouter.assign_region(
+        || "check new root",
+        |mut region| region.constrain_equal(cur_root.cell(), new_root.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_root_history_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::merkle_circuit::MerklePoseidonConfig;
+    use crate::merkle_tree::{compute_anchor, MerklePath, Node};
+    use halo2_gadgets::poseidon::{primitives as poseidon, Pow5Chip as PoseidonChip};
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        insertions: Vec<(pallas::Base, MerklePath)>,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = ([Column<Advice>; 5], Column<Instance>, MerklePoseidonConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let state = (0..3).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let partial_sbox = meta.advice_column();
+            let rc_a = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            meta.enable_constant(rc_b[0]);
+            let poseidon_config = PoseidonChip::configure::<poseidon::P128Pow5T3>(
+                meta,
+                state.try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            );
+
+            let merkle_config = MerklePoseidonChip::configure(meta, advices, poseidon_config);
+            (advices, instance, merkle_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, merkle_config) = config;
+            let old_root =
+                assign_free_instance(layouter.namespace(|| "old root"), instance, 0, advices[0])?;
+            let new_root =
+                assign_free_instance(layouter.namespace(|| "new root"), instance, 1, advices[0])?;
+            let insertions = self
+                .insertions
+                .iter()
+                .map(|(leaf, merkle_path)| {
+                    let leaf = assign_free_advice(
+                        layouter.namespace(|| "witness inserted leaf"),
+                        advices[0],
+                        Value::known(*leaf),
+                    )?;
+                    Ok((leaf, merkle_path.get_path()))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            root_history_gadget(
+                layouter.namespace(|| "root history"),
+                MerklePoseidonChip::construct(merkle_config),
+                advices[0],
+                &old_root,
+                &new_root,
+                &insertions,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let empty = Node::from(pallas::Base::zero());
+    let leaves: Vec<Node> = (0..3).map(|_| Node::rand(&mut rng)).collect();
+
+    // The old tree holds the first leaf, the new tree holds all three
+    let old_root = compute_anchor(&[leaves[0], empty, empty, empty]).inner();
+    let new_root = compute_anchor(&[leaves[0], leaves[1], leaves[2], empty]).inner();
+
+    // Insert the second leaf at index 1, then the third one at index 2
+    let first_insertion = (
+        leaves[1].inner(),
+        MerklePath::from_path(vec![
+            (leaves[0], LR::L),
+            (Node::combine(&empty, &empty), LR::R),
+        ]),
+    );
+    let second_insertion = (
+        leaves[2].inner(),
+        MerklePath::from_path(vec![
+            (empty, LR::R),
+            (Node::combine(&leaves[0], &leaves[1]), LR::L),
+        ]),
+    );
+    let circuit = MyCircuit {
+        insertions: vec![first_insertion.clone(), second_insertion],
+    };
+    let prover = MockProver::run(11, &circuit, vec![vec![old_root, new_root]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A bogus chain skipping the second leaf
+    let bogus_insertion = (
+        leaves[2].inner(),
+        MerklePath::from_path(vec![
+            (empty, LR::R),
+            (Node::combine(&leaves[0], &empty), LR::L),
+        ]),
+    );
+    let circuit = MyCircuit {
+        insertions: vec![first_insertion, bogus_insertion],
+    };
+    let prover = MockProver::run(11, &circuit, vec![vec![old_root, new_root]]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,263 @@
+/// Constrain the outputs split the total by shares in basis points, the last one taking the remainder
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_advice, assign_free_constant,
+    coin_selection::u64_range_check,
+    mul::{MulChip, MulInstructions},
+    sub::{SubChip, SubInstructions},
+};
+use ff::PrimeField;
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+pub const BPS_DENOMINATOR: u64 = 10000;
+
+// The royalty split of the total, the last share getting the rounding remainder
+pub fn royalty_split(total: u64, shares_bps: &[u64]) -> Vec<u64> {
+    assert!(!shares_bps.is_empty());
+    assert_eq!(shares_bps.iter().sum::<u64>(), BPS_DENOMINATOR);
+    let mut split: Vec<u64> = shares_bps[..shares_bps.len() - 1]
+        .iter()
+        .map(|bps| (total as u128 * *bps as u128 / BPS_DENOMINATOR as u128) as u64)
+        .collect();
+    split.push(total - split.iter().sum::<u64>());
+    split
+}
+
+// Check the cell is a 14-bit value
+fn u14_range_check(
+    mut layouter: impl Layouter<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    value: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let zs = lookup_config.copy_check(
+        layouter.namespace(|| "K(10) bits range check"),
+        value.clone(),
+        1,
+        false,
+    )?;
+    lookup_config.copy_short_check(
+        layouter.namespace(|| "4 bits range check"),
+        zs[1].clone(),
+        4,
+    )
+}
+
+// Check the split of the total quantity by the public shares in basis points. The total and the
+// outputs are 64-bit quantities and the shares are range checked to 14 bits, so
+// output * 10000 + rem = total * bps can't wrap around and fixes the output.
+#[allow(clippy::too_many_arguments)]
+pub fn royalty_split_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    sub_chip: SubChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    advice: Column<Advice>,
+    total: &AssignedCell<pallas::Base, pallas::Base>,
+    shares_bps: &[AssignedCell<pallas::Base, pallas::Base>],
+    output_quantities: &[AssignedCell<pallas::Base, pallas::Base>],
+) -> Result<(), Error> {
+    assert!(!shares_bps.is_empty());
+    assert_eq!(shares_bps.len(), output_quantities.len());
+    let denominator = assign_free_constant(
+        layouter.namespace(|| "constant 10000"),
+        advice,
+        pallas::Base::from(BPS_DENOMINATOR),
+    )?;
+    // rem < 10000 iff rem + 2^14 - 10000 < 2^14
+    let rem_offset = assign_free_constant(
+        layouter.namespace(|| "constant 2^14 - 10000"),
+        advice,
+        pallas::Base::from((1 << 14) - BPS_DENOMINATOR),
+    )?;
+
+    u64_range_check(
+        layouter.namespace(|| "total range check"),
+        lookup_config,
+        total,
+    )?;
+    for output_quantity in output_quantities.iter() {
+        u64_range_check(
+            layouter.namespace(|| "output range check"),
+            lookup_config,
+            output_quantity,
+        )?;
+    }
+    for bps in shares_bps.iter() {
+        u14_range_check(layouter.namespace(|| "bps range check"), lookup_config, bps)?;
+    }
+
+    // sum(shares_bps) = 10000
+    let mut bps_sum = shares_bps[0].clone();
+    for bps in shares_bps.iter().skip(1) {
+        bps_sum = add_chip.add(layouter.namespace(|| "sum shares"), &bps_sum, bps)?;
+    }
+    layouter.assign_region(
+        || "check shares sum",
+        |mut region| region.constrain_constant(bps_sum.cell(), pallas::Base::from(BPS_DENOMINATOR)),
+    )?;
+
+    // output * 10000 + rem = total * bps, with rem < 10000
+    let last = shares_bps.len() - 1;
+    for (bps, output_quantity) in shares_bps[..last].iter().zip(output_quantities.iter()) {
+        let rem = assign_free_advice(
+            layouter.namespace(|| "witness rem"),
+            advice,
+            total.value().zip(bps.value()).map(|(total, bps)| {
+                let total = u128::from_le_bytes(total.to_repr()[..16].try_into().unwrap());
+                let bps = u128::from_le_bytes(bps.to_repr()[..16].try_into().unwrap());
+                pallas::Base::from_u128(total * bps % BPS_DENOMINATOR as u128)
+            }),
+        )?;
+        u14_range_check(
+            layouter.namespace(|| "rem range check"),
+            lookup_config,
+            &rem,
+        )?;
+        let shifted_rem = add_chip.add(
+            layouter.namespace(|| "rem + 2^14 - 10000"),
+            &rem,
+            &rem_offset,
+        )?;
+        u14_range_check(
+            layouter.namespace(|| "shifted rem range check"),
+            lookup_config,
+            &shifted_rem,
+        )?;
+
+        let scaled_output = mul_chip.mul(
+            layouter.namespace(|| "output * 10000"),
+            output_quantity,
+            &denominator,
+        )?;
+        let lhs = add_chip.add(
+            layouter.namespace(|| "output * 10000 + rem"),
+            &scaled_output,
+            &rem,
+        )?;
+        let rhs = mul_chip.mul(layouter.namespace(|| "total * bps"), total, bps)?;
+        layouter.assign_region(
+            || "check share",
+            |mut region| region.constrain_equal(lhs.cell(), rhs.cell()),
+        )?;
+    }
+
+    // The last output takes what's left of the total
+    let mut remainder = total.clone();
+    for output_quantity in output_quantities[..last].iter() {
+        remainder = sub_chip.sub(
+            layouter.namespace(|| "remainder - output"),
+            &remainder,
+            output_quantity,
+        )?;
+    }
+    layouter.assign_region(
+        || "check last share",
+        |mut region| region.constrain_equal(remainder.cell(), output_quantities[last].cell()),
+    )
+}
+
+#[test]
+fn test_halo2_royalty_split_gadget() {
+    use crate::circuit::gadgets::assign_free_instance;
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+
+    #[derive(Default)]
+    struct MyCircuit {
+        total: u64,
+        output_quantities: [u64; 2],
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let total = assign_free_advice(
+                layouter.namespace(|| "witness total"),
+                config.advices[0],
+                Value::known(pallas::Base::from(self.total)),
+            )?;
+            let shares_bps = (0..2)
+                .map(|i| {
+                    assign_free_instance(
+                        layouter.namespace(|| "public share"),
+                        config.instances,
+                        i,
+                        config.advices[0],
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let output_quantities = self
+                .output_quantities
+                .iter()
+                .map(|quantity| {
+                    assign_free_advice(
+                        layouter.namespace(|| "witness output quantity"),
+                        config.advices[0],
+                        Value::known(pallas::Base::from(*quantity)),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            royalty_split_gadget(
+                layouter.namespace(|| "royalty split"),
+                AddChip::construct(config.add_config, ()),
+                SubChip::construct(config.sub_config, ()),
+                MulChip::construct(config.mul_config),
+                resource_commit_chip.get_lookup_config(),
+                config.advices[0],
+                &total,
+                &shares_bps,
+                &output_quantities,
+            )
+        }
+    }
+
+    // A 90/10 split of 1005: 904 and 101
+    let shares_bps = vec![pallas::Base::from(9000u64), pallas::Base::from(1000u64)];
+    let split = royalty_split(1005, &[9000, 1000]);
+    assert_eq!(split, vec![904, 101]);
+    let circuit = GadgetTestCircuit(MyCircuit {
+        total: 1005,
+        output_quantities: split.try_into().unwrap(),
+    });
+    let prover = MockProver::run(11, &circuit, vec![shares_bps.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A mis-split rounding up the first share
+    let circuit = GadgetTestCircuit(MyCircuit {
+        total: 1005,
+        output_quantities: [905, 100],
+    });
+    let prover = MockProver::run(11, &circuit, vec![shares_bps]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The shares don't sum to 10000
+    let circuit = GadgetTestCircuit(MyCircuit {
+        total: 1000,
+        output_quantities: [900, 100],
+    });
+    let prover = MockProver::run(
+        11,
+        &circuit,
+        vec![vec![
+            pallas::Base::from(9000u64),
+            pallas::Base::from(900u64),
+        ]],
+    )
+    .unwrap();
+    assert!(prover.verify().is_err());
+}
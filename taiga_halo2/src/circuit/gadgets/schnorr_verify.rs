@@ -0,0 +1,208 @@
+/// Constrain s * G = R + poseidon_hash(R || P || m) * P
+use crate::circuit::gadgets::poseidon_hash::poseidon_hash_gadget;
+use crate::constant::{TaigaFixedBases, TaigaFixedBasesFull};
+use crate::utils::{mod_r_p, poseidon_hash_n};
+use halo2_gadgets::{
+    ecc::{chip::EccChip, FixedPoint, NonIdentityPoint, ScalarFixed, ScalarVar},
+    poseidon::Pow5Config as PoseidonConfig,
+};
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::Error,
+};
+use pasta_curves::{
+    arithmetic::CurveAffine,
+    group::{Curve, Group},
+    pallas,
+};
+use rand::RngCore;
+
+#[cfg(feature = "borsh")]
+use crate::utils::{read_point, read_scalar_field};
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+#[cfg(feature = "borsh")]
+use pasta_curves::group::{ff::PrimeField, GroupEncoding};
+
+/// A schnorr signature over a field element message, or an array of them.
+#[derive(Clone, Copy, Debug)]
+pub struct SchnorrSignature {
+    // public key
+    pub pk: pallas::Point,
+    // signature (r,s)
+    pub r: pallas::Point,
+    pub s: pallas::Scalar,
+}
+
+impl Default for SchnorrSignature {
+    fn default() -> Self {
+        Self {
+            pk: pallas::Point::generator(),
+            r: pallas::Point::generator(),
+            s: pallas::Scalar::one(),
+        }
+    }
+}
+
+impl SchnorrSignature {
+    pub fn sign<R: RngCore>(rng: R, sk: pallas::Scalar, message: pallas::Base) -> Self {
+        Self::sign_array::<R, 5>(rng, sk, &[message])
+    }
+
+    pub fn verify(&self, message: pallas::Base) -> bool {
+        self.verify_array::<5>(&[message])
+    }
+
+    // Sign the message array, L is the length of the challenge hash R || P || m, i.e. the
+    // message length plus four
+    pub fn sign_array<R: RngCore, const L: usize>(
+        mut rng: R,
+        sk: pallas::Scalar,
+        message: &[pallas::Base],
+    ) -> Self {
+        let generator = pallas::Point::generator();
+        let pk = generator * sk;
+        // Generate a random number: z
+        let z = pallas::Scalar::random(&mut rng);
+        // Compute: R = z*G
+        let r = generator * z;
+        // Compute: s = z + Hash(r||P||m)*sk
+        let s = z + challenge::<L>(&r, &pk, message) * sk;
+        Self { pk, r, s }
+    }
+
+    pub fn verify_array<const L: usize>(&self, message: &[pallas::Base]) -> bool {
+        pallas::Point::generator() * self.s
+            == self.r + self.pk * challenge::<L>(&self.r, &self.pk, message)
+    }
+}
+
+fn challenge<const L: usize>(
+    r: &pallas::Point,
+    pk: &pallas::Point,
+    message: &[pallas::Base],
+) -> pallas::Scalar {
+    assert_eq!(message.len() + 4, L);
+    let r_coord = r.to_affine().coordinates().unwrap();
+    let pk_coord = pk.to_affine().coordinates().unwrap();
+    let mut hash_input = [pallas::Base::zero(); L];
+    hash_input[..4].copy_from_slice(&[*r_coord.x(), *r_coord.y(), *pk_coord.x(), *pk_coord.y()]);
+    hash_input[4..].copy_from_slice(message);
+    mod_r_p(poseidon_hash_n(hash_input))
+}
+
+#[cfg(feature = "borsh")]
+impl BorshSerialize for SchnorrSignature {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.pk.to_bytes())?;
+        writer.write_all(&self.r.to_bytes())?;
+        writer.write_all(&self.s.to_repr())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl BorshDeserialize for SchnorrSignature {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let pk = read_point(reader)?;
+        let r = read_point(reader)?;
+        let s = read_scalar_field(reader)?;
+        Ok(Self { pk, r, s })
+    }
+}
+
+// Verify the signature on message under the witnessed public key
+pub fn schnorr_verify_gadget(
+    layouter: impl Layouter<pallas::Base>,
+    ecc_chip: EccChip<TaigaFixedBases>,
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    pk: &NonIdentityPoint<pallas::Affine, EccChip<TaigaFixedBases>>,
+    signature: &SchnorrSignature,
+    message: AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    schnorr_verify_array_gadget::<5>(
+        layouter,
+        ecc_chip,
+        poseidon_config,
+        pk,
+        signature,
+        &[message],
+    )
+}
+
+// Verify the signature on the message array under the witnessed public key, see
+// `SchnorrSignature::sign_array` for L
+pub fn schnorr_verify_array_gadget<const L: usize>(
+    mut layouter: impl Layouter<pallas::Base>,
+    ecc_chip: EccChip<TaigaFixedBases>,
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    pk: &NonIdentityPoint<pallas::Affine, EccChip<TaigaFixedBases>>,
+    signature: &SchnorrSignature,
+    message: &[AssignedCell<pallas::Base, pallas::Base>],
+) -> Result<(), Error> {
+    assert_eq!(message.len() + 4, L);
+    let r = NonIdentityPoint::new(
+        ecc_chip.clone(),
+        layouter.namespace(|| "witness r"),
+        Value::known(signature.r.to_affine()),
+    )?;
+    let s_scalar = ScalarFixed::new(
+        ecc_chip.clone(),
+        layouter.namespace(|| "witness s"),
+        Value::known(signature.s),
+    )?;
+
+    // s*G
+    let generator = FixedPoint::from_inner(ecc_chip.clone(), TaigaFixedBasesFull::BaseGenerator);
+    let (s_g, _) = generator.mul(layouter.namespace(|| "s_scalar * generator"), &s_scalar)?;
+
+    // Hash(r||P||m)
+    let h_scalar = {
+        let mut hash_input = vec![r.inner().x(), r.inner().y(), pk.inner().x(), pk.inner().y()];
+        hash_input.extend_from_slice(message);
+        let h = poseidon_hash_gadget::<L>(
+            poseidon_config,
+            layouter.namespace(|| "Poseidon_hash(r, P, m)"),
+            hash_input.try_into().unwrap(),
+        )?;
+
+        ScalarVar::from_base(ecc_chip, layouter.namespace(|| "ScalarVar from_base"), &h)?
+    };
+
+    // Hash(r||P||m)*P
+    let (h_p, _) = pk.mul(layouter.namespace(|| "hP"), h_scalar)?;
+
+    // R + Hash(r||P||m)*P
+    let rhs = r.add(layouter.namespace(|| "R + Hash(r||P||m)*P"), &h_p)?;
+
+    s_g.constrain_equal(layouter.namespace(|| "s*G = R + Hash(r||P||m)*P"), &rhs)
+}
+
+#[test]
+fn test_native_schnorr_signature() {
+    use rand::rngs::OsRng;
+
+    let mut rng = OsRng;
+    let sk = pallas::Scalar::random(&mut rng);
+    let message = pallas::Base::random(&mut rng);
+    let signature = SchnorrSignature::sign(&mut rng, sk, message);
+    assert!(signature.verify(message));
+    assert!(!signature.verify(message + pallas::Base::one()));
+}
+
+#[test]
+fn test_native_schnorr_signature_array() {
+    use rand::rngs::OsRng;
+
+    let mut rng = OsRng;
+    let sk = pallas::Scalar::random(&mut rng);
+    let message = [(); 4].map(|_| pallas::Base::random(&mut rng));
+    let signature = SchnorrSignature::sign_array::<_, 8>(&mut rng, sk, &message);
+    assert!(signature.verify_array::<8>(&message));
+
+    let mut other_message = message;
+    other_message.swap(0, 1);
+    assert!(!signature.verify_array::<8>(&other_message));
+}
@@ -0,0 +1,169 @@
+/// Constrain the leaf is in the subtree of shard s, and the shard root is the leaf s of the global tree
+use crate::circuit::{
+    gadgets::{add::AddChip, conditional_select::ConditionalSelectConfig, mul::MulChip},
+    merkle_circuit::{
+        merkle_poseidon_gadget, merkle_poseidon_gadget_with_index, MerklePoseidonChip,
+    },
+};
+use crate::merkle_tree::LR;
+use crate::utils::to_u64;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// Check the membership in the shard and bind the shard index. The path from the shard root to the
+// global root is derived from the bits of the shard index, so the proof can't claim another shard.
+#[allow(clippy::too_many_arguments)]
+pub fn shard_membership_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    merkle_chip: MerklePoseidonChip,
+    conditional_select_config: ConditionalSelectConfig,
+    add_chip: AddChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    advice: Column<Advice>,
+    leaf: AssignedCell<pallas::Base, pallas::Base>,
+    shard_path: &[(pallas::Base, LR)],
+    shard_siblings: &[pallas::Base],
+    shard: &AssignedCell<pallas::Base, pallas::Base>,
+    root: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    assert!(shard_siblings.len() < 64);
+    let shard_root = merkle_poseidon_gadget(
+        layouter.namespace(|| "shard membership"),
+        merkle_chip.clone(),
+        leaf,
+        shard_path,
+    )?;
+
+    // The node is the right child, i.e. its sibling is on the left, if the level bit of the shard
+    // index is set
+    let global_path: Vec<_> = shard_siblings
+        .iter()
+        .enumerate()
+        .map(|(level, sibling)| {
+            let lr = shard.value().map(|shard| {
+                if (to_u64(shard) >> level) & 1 == 1 {
+                    LR::L
+                } else {
+                    LR::R
+                }
+            });
+            (*sibling, lr)
+        })
+        .collect();
+    let (global_root, shard_index) = merkle_poseidon_gadget_with_index(
+        layouter.namespace(|| "shard root membership"),
+        &merkle_chip,
+        conditional_select_config,
+        add_chip,
+        mul_chip,
+        advice,
+        shard_root,
+        &global_path,
+    )?;
+
+    layouter.assign_region(
+        || "check shard index",
+        |mut region| region.constrain_equal(shard_index.cell(), shard.cell()),
+    )?;
+
+    layouter.assign_region(
+        || "check global root",
+        |mut region| region.constrain_equal(global_root.cell(), root.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_shard_membership_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::merkle_circuit::MerklePoseidonConfig;
+    use crate::circuit::vp_circuit::{
+        testing::{MerkleGadgetTest, MerkleGadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::merkle_tree::{compute_anchor, MerklePath, Node};
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        leaf: pallas::Base,
+        shard_path: MerklePath,
+        shard_siblings: Vec<pallas::Base>,
+    }
+
+    impl MerkleGadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            merkle_config: MerklePoseidonConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let leaf = assign_free_advice(
+                layouter.namespace(|| "witness leaf"),
+                config.advices[0],
+                Value::known(self.leaf),
+            )?;
+            let shard = assign_free_instance(
+                layouter.namespace(|| "public shard"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+            let root = assign_free_instance(
+                layouter.namespace(|| "public root"),
+                config.instances,
+                1,
+                config.advices[0],
+            )?;
+
+            shard_membership_gadget(
+                layouter.namespace(|| "shard membership"),
+                MerklePoseidonChip::construct(merkle_config),
+                config.conditional_select_config,
+                AddChip::construct(config.add_config, ()),
+                MulChip::construct(config.mul_config),
+                config.advices[0],
+                leaf,
+                &self.shard_path.get_path(),
+                &self.shard_siblings,
+                &shard,
+                &root,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+
+    // Four shards of depth 2, and the global root over the shard roots
+    let shard_leaves: Vec<Vec<Node>> = (0..4)
+        .map(|_| (0..4).map(|_| Node::rand(&mut rng)).collect())
+        .collect();
+    let shard_roots: Vec<Node> = shard_leaves
+        .iter()
+        .map(|leaves| Node::from(compute_anchor(leaves).inner()))
+        .collect();
+    let root = compute_anchor(&shard_roots).inner();
+
+    // The second leaf of shard 2
+    let leaves = &shard_leaves[2];
+    let circuit = MerkleGadgetTestCircuit(MyCircuit {
+        leaf: leaves[1].inner(),
+        shard_path: MerklePath::from_path(vec![
+            (leaves[0], LR::L),
+            (Node::combine(&leaves[2], &leaves[3]), LR::R),
+        ]),
+        shard_siblings: vec![
+            shard_roots[3].inner(),
+            Node::combine(&shard_roots[0], &shard_roots[1]).inner(),
+        ],
+    });
+    let prover = MockProver::run(11, &circuit, vec![vec![pallas::Base::from(2u64), root]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Claim the membership in shard 0
+    let prover = MockProver::run(11, &circuit, vec![vec![pallas::Base::zero(), root]]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,111 @@
+/// Constrain exactly one of the output resources is not ephemeral: sum(is_ephemeral) = n - 1
+use crate::circuit::gadgets::add::{AddChip, AddInstructions};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// Check a single-recipient transfer where all but one output are dummies. The is_ephemeral flags are
+// bool-checked in the resource commitment, so the count of real outputs can't wrap around.
+pub fn single_output_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    output_is_ephemeral: &[AssignedCell<pallas::Base, pallas::Base>],
+) -> Result<(), Error> {
+    assert!(!output_is_ephemeral.is_empty());
+    let mut ephemeral_num = output_is_ephemeral[0].clone();
+    for is_ephemeral in output_is_ephemeral.iter().skip(1) {
+        ephemeral_num = add_chip.add(
+            layouter.namespace(|| "count ephemeral outputs"),
+            &ephemeral_num,
+            is_ephemeral,
+        )?;
+    }
+
+    let expected_num = pallas::Base::from((output_is_ephemeral.len() - 1) as u64);
+    layouter.assign_region(
+        || "check single output",
+        |mut region| region.constrain_constant(ephemeral_num.cell(), expected_num),
+    )
+}
+
+#[test]
+fn test_halo2_single_output_gadget() {
+    use crate::circuit::gadgets::{add::AddConfig, assign_free_advice};
+    use crate::constant::NUM_RESOURCE;
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem},
+    };
+
+    #[derive(Default)]
+    struct MyCircuit {
+        output_is_ephemeral: [bool; NUM_RESOURCE],
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = ([Column<Advice>; 2], AddConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [meta.advice_column(), meta.advice_column()];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            let add_config = AddChip::configure(meta, advices);
+            (advices, add_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, add_config) = config;
+            let output_is_ephemeral = self
+                .output_is_ephemeral
+                .iter()
+                .map(|is_ephemeral| {
+                    assign_free_advice(
+                        layouter.namespace(|| "witness is_ephemeral"),
+                        advices[0],
+                        Value::known(pallas::Base::from(*is_ephemeral)),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            single_output_gadget(
+                layouter.namespace(|| "single output"),
+                AddChip::construct(add_config, ()),
+                &output_is_ephemeral,
+            )
+        }
+    }
+
+    // One real output and the rest are dummies
+    let mut output_is_ephemeral = [true; NUM_RESOURCE];
+    output_is_ephemeral[0] = false;
+    let circuit = MyCircuit {
+        output_is_ephemeral,
+    };
+    let prover = MockProver::run(5, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Two real outputs
+    let mut output_is_ephemeral = [true; NUM_RESOURCE];
+    output_is_ephemeral[0] = false;
+    output_is_ephemeral[1] = false;
+    let circuit = MyCircuit {
+        output_is_ephemeral,
+    };
+    let prover = MockProver::run(5, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
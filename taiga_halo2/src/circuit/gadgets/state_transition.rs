@@ -0,0 +1,167 @@
+/// Constrain (from, to) is a leaf of the committed transition table
+use crate::circuit::gadgets::poseidon_hash::poseidon_hash_gadget;
+use crate::circuit::merkle_circuit::{merkle_poseidon_gadget, MerklePoseidonChip};
+use crate::merkle_tree::LR;
+use crate::utils::poseidon_hash;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// The leaf of an allowed transition in the transition table
+pub fn transition_leaf(from: pallas::Base, to: pallas::Base) -> pallas::Base {
+    poseidon_hash(from, to)
+}
+
+// Check the state transition is allowed by the table committed to transition_table_root
+pub fn state_transition_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    merkle_chip: MerklePoseidonChip,
+    from: &AssignedCell<pallas::Base, pallas::Base>,
+    to: &AssignedCell<pallas::Base, pallas::Base>,
+    transition_table_root: &AssignedCell<pallas::Base, pallas::Base>,
+    merkle_path: &[(pallas::Base, LR)],
+) -> Result<(), Error> {
+    let leaf = poseidon_hash_gadget(
+        merkle_chip.get_poseidon_config(),
+        layouter.namespace(|| "transition leaf"),
+        [from.clone(), to.clone()],
+    )?;
+
+    let root = merkle_poseidon_gadget(
+        layouter.namespace(|| "transition table membership"),
+        merkle_chip,
+        leaf,
+        merkle_path,
+    )?;
+
+    layouter.assign_region(
+        || "check transition table root",
+        |mut region| region.constrain_equal(root.cell(), transition_table_root.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_state_transition_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::merkle_circuit::MerklePoseidonConfig;
+    use crate::merkle_tree::{MerklePath, Node};
+    use halo2_gadgets::poseidon::{primitives as poseidon, Pow5Chip as PoseidonChip};
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Instance},
+    };
+
+    #[derive(Default)]
+    struct MyCircuit {
+        from: pallas::Base,
+        to: pallas::Base,
+        merkle_path: MerklePath,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = ([Column<Advice>; 5], Column<Instance>, MerklePoseidonConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let state = (0..3).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let partial_sbox = meta.advice_column();
+            let rc_a = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = (0..3).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            meta.enable_constant(rc_b[0]);
+            let poseidon_config = PoseidonChip::configure::<poseidon::P128Pow5T3>(
+                meta,
+                state.try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            );
+
+            let merkle_config = MerklePoseidonChip::configure(meta, advices, poseidon_config);
+            (advices, instance, merkle_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, merkle_config) = config;
+            let from = assign_free_advice(
+                layouter.namespace(|| "witness from state"),
+                advices[0],
+                Value::known(self.from),
+            )?;
+            let to = assign_free_advice(
+                layouter.namespace(|| "witness to state"),
+                advices[1],
+                Value::known(self.to),
+            )?;
+            let transition_table_root = assign_free_instance(
+                layouter.namespace(|| "transition table root"),
+                instance,
+                0,
+                advices[2],
+            )?;
+
+            state_transition_gadget(
+                layouter.namespace(|| "state transition"),
+                MerklePoseidonChip::construct(merkle_config),
+                &from,
+                &to,
+                &transition_table_root,
+                &self.merkle_path.get_path(),
+            )
+        }
+    }
+
+    // A transition table with four allowed transitions: 0 -> 1, 1 -> 2, 2 -> 3 and 3 -> 0
+    let states: Vec<pallas::Base> = (0..4u64).map(pallas::Base::from).collect();
+    let leaves: Vec<Node> = (0..4)
+        .map(|i| Node::from(transition_leaf(states[i], states[(i + 1) % 4])))
+        .collect();
+    // The merkle path of the 1 -> 2 transition
+    let merkle_path = MerklePath::from_path(vec![
+        (leaves[0], LR::L),
+        (Node::combine(&leaves[2], &leaves[3]), LR::R),
+    ]);
+    let transition_table_root = merkle_path.root(leaves[1]).inner();
+
+    // An allowed transition
+    let circuit = MyCircuit {
+        from: states[1],
+        to: states[2],
+        merkle_path: merkle_path.clone(),
+    };
+    let prover = MockProver::run(11, &circuit, vec![vec![transition_table_root]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A forbidden transition
+    let circuit = MyCircuit {
+        from: states[2],
+        to: states[1],
+        merkle_path,
+    };
+    let prover = MockProver::run(11, &circuit, vec![vec![transition_table_root]]).unwrap();
+    assert!(prover.verify().is_err());
+}
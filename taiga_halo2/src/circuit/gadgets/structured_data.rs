@@ -0,0 +1,222 @@
+/// Constrain data = sum(fields[i] * 2^offset[i]), where each field is in the bits declared by the schema
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_advice, assign_free_constant,
+    decompose_128::range_check,
+    mul::{MulChip, MulInstructions},
+};
+use ff::{Field, PrimeField};
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+/// A field of structured data. The fields of a schema are packed in order from the lowest bits.
+#[derive(Clone, Copy, Debug)]
+pub struct DataField {
+    pub name: &'static str,
+    pub bits: usize,
+}
+
+// The bit offsets of the fields, checking the schema fits in the field
+fn field_offsets(schema: &[DataField]) -> Vec<usize> {
+    let mut offset = 0;
+    let offsets = schema
+        .iter()
+        .map(|field| {
+            assert!(field.bits > 0, "empty field {}", field.name);
+            let field_offset = offset;
+            offset += field.bits;
+            field_offset
+        })
+        .collect();
+    assert!(offset <= pallas::Base::CAPACITY as usize);
+    offsets
+}
+
+fn extract_bits(value: &pallas::Base, offset: usize, bits: usize) -> pallas::Base {
+    let repr = value.to_repr();
+    (offset..offset + bits)
+        .rev()
+        .fold(pallas::Base::zero(), |acc, i| {
+            acc.double() + pallas::Base::from(((repr[i / 8] >> (i % 8)) & 1) as u64)
+        })
+}
+
+// Pack the field values into data as described by the schema
+pub fn pack_structured_data(schema: &[DataField], values: &[pallas::Base]) -> pallas::Base {
+    assert_eq!(schema.len(), values.len());
+    field_offsets(schema)
+        .into_iter()
+        .zip(schema.iter().zip(values.iter()))
+        .fold(pallas::Base::zero(), |data, (offset, (field, value))| {
+            assert_eq!(
+                extract_bits(value, 0, field.bits),
+                *value,
+                "field {} exceeds {} bits",
+                field.name,
+                field.bits
+            );
+            data + *value * pallas::Base::from(2).pow([offset as u64])
+        })
+}
+
+// Decompose the data into the fields of the schema and return them in the schema order. The fields
+// take at most 254 bits in total, so the decomposition is unique.
+pub fn structured_data_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    advice: Column<Advice>,
+    schema: &[DataField],
+    data: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<Vec<AssignedCell<pallas::Base, pallas::Base>>, Error> {
+    assert!(!schema.is_empty());
+    let offsets = field_offsets(schema);
+    let mut fields = Vec::with_capacity(schema.len());
+    let mut packed: Option<AssignedCell<pallas::Base, pallas::Base>> = None;
+    for (field, offset) in schema.iter().zip(offsets) {
+        let value = assign_free_advice(
+            layouter.namespace(|| format!("witness {}", field.name)),
+            advice,
+            data.value()
+                .map(|data| extract_bits(data, offset, field.bits)),
+        )?;
+        if field.bits < 10 {
+            lookup_config.copy_short_check(
+                layouter.namespace(|| format!("{} range check", field.name)),
+                value.clone(),
+                field.bits,
+            )?;
+        } else {
+            range_check(
+                layouter.namespace(|| format!("{} range check", field.name)),
+                lookup_config,
+                &value,
+                field.bits / 10,
+                field.bits % 10,
+            )?;
+        }
+
+        let shift = assign_free_constant(
+            layouter.namespace(|| format!("{} shift", field.name)),
+            advice,
+            pallas::Base::from(2).pow([offset as u64]),
+        )?;
+        let shifted = mul_chip.mul(
+            layouter.namespace(|| format!("shift {}", field.name)),
+            &value,
+            &shift,
+        )?;
+        packed = Some(match packed {
+            Some(packed) => add_chip.add(
+                layouter.namespace(|| format!("pack {}", field.name)),
+                &packed,
+                &shifted,
+            )?,
+            None => shifted,
+        });
+        fields.push(value);
+    }
+
+    let packed = packed.unwrap();
+    layouter.assign_region(
+        || "check structured data",
+        |mut region| region.constrain_equal(packed.cell(), data.cell()),
+    )?;
+    Ok(fields)
+}
+
+#[test]
+fn test_halo2_structured_data_gadget() {
+    use crate::circuit::gadgets::assign_free_instance;
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::dev::MockProver;
+
+    const SCHEMA: &[DataField] = &[
+        DataField {
+            name: "version",
+            bits: 4,
+        },
+        DataField {
+            name: "kind",
+            bits: 16,
+        },
+        DataField {
+            name: "amount",
+            bits: 64,
+        },
+        DataField {
+            name: "expiry",
+            bits: 32,
+        },
+    ];
+
+    #[derive(Default)]
+    struct MyCircuit {
+        data: pallas::Base,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let data = assign_free_advice(
+                layouter.namespace(|| "witness data"),
+                config.advices[0],
+                Value::known(self.data),
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            let fields = structured_data_gadget(
+                layouter.namespace(|| "structured data"),
+                AddChip::construct(config.add_config, ()),
+                MulChip::construct(config.mul_config),
+                resource_commit_chip.get_lookup_config(),
+                config.advices[0],
+                SCHEMA,
+                &data,
+            )?;
+
+            // The fields are public
+            for (i, field) in fields.iter().enumerate() {
+                let expected = assign_free_instance(
+                    layouter.namespace(|| "public field"),
+                    config.instances,
+                    i,
+                    config.advices[0],
+                )?;
+                layouter.assign_region(
+                    || "check field",
+                    |mut region| region.constrain_equal(field.cell(), expected.cell()),
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    let values = [3u64, 0xbeef, 1_000_000, 1_700_000_000].map(pallas::Base::from);
+    let data = pack_structured_data(SCHEMA, &values);
+
+    // The packed struct decomposes into its fields
+    let circuit = GadgetTestCircuit(MyCircuit { data });
+    let prover = MockProver::run(11, &circuit, vec![values.to_vec()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The expiry exceeds its 32 bits
+    let overflow = pallas::Base::from(1 << 32);
+    let circuit = GadgetTestCircuit(MyCircuit {
+        data: data + overflow * pallas::Base::from(2).pow([84]),
+    });
+    let prover = MockProver::run(11, &circuit, vec![values.to_vec()]).unwrap();
+    assert!(prover.verify().is_err());
+}
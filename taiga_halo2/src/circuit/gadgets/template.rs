@@ -0,0 +1,122 @@
+/// Constrain resource_commit(resource) = cm with the logic, label and value of the resource fixed by the template
+use crate::circuit::{
+    gadgets::commitment_matches::witness_resource_commitment,
+    resource_commitment::ResourceCommitChip,
+};
+use crate::resource::Resource;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// The fixed fields of the resources a mint VP creates, e.g. a fixed token and data with a free
+// quantity and owner.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResourceTemplate {
+    pub logic: pallas::Base,
+    pub label: pallas::Base,
+    pub value: pallas::Base,
+}
+
+impl ResourceTemplate {
+    pub fn matches(&self, resource: &Resource) -> bool {
+        resource.get_logic() == self.logic
+            && resource.get_label() == self.label
+            && resource.value == self.value
+    }
+}
+
+// Recompute the commitment of a resource following the template. The template fields are circuit
+// constants while the others are witnessed freely.
+pub fn template_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    advice: Column<Advice>,
+    resource_commit_chip: ResourceCommitChip,
+    template: &ResourceTemplate,
+    resource: &Resource,
+    cm: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let expected_cm = witness_resource_commitment(
+        layouter.namespace(|| "witness resource"),
+        advice,
+        resource_commit_chip,
+        resource,
+        Some(template.logic),
+        Some(template.label),
+        Some(template.value),
+    )?;
+
+    layouter.assign_region(
+        || "check resource commitment",
+        |mut region| region.constrain_equal(expected_cm.cell(), cm.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_template_gadget() {
+    use crate::circuit::gadgets::assign_free_instance;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::resource::tests::random_resource;
+    use halo2_proofs::dev::MockProver;
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        template: ResourceTemplate,
+        resource: Resource,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let cm = assign_free_instance(
+                layouter.namespace(|| "public cm"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+
+            template_gadget(
+                layouter.namespace(|| "template"),
+                config.advices[0],
+                ResourceCommitChip::construct(config.resource_commit_config),
+                &self.template,
+                &self.resource,
+                &cm,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let template_resource = random_resource(&mut rng);
+    let template = ResourceTemplate {
+        logic: template_resource.get_logic(),
+        label: template_resource.get_label(),
+        value: template_resource.value,
+    };
+
+    // A minted resource with the template token and data, and its own quantity and owner
+    let mut resource = random_resource(&mut rng);
+    resource.kind = template_resource.kind;
+    resource.value = template.value;
+    assert!(template.matches(&resource));
+    let circuit = GadgetTestCircuit(MyCircuit { template, resource });
+    let cm = resource.commitment().inner();
+    let prover = MockProver::run(11, &circuit, vec![vec![cm]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The minted resource has another token
+    resource.kind.logic = random_resource(&mut rng).get_logic();
+    assert!(!template.matches(&resource));
+    let circuit = GadgetTestCircuit(MyCircuit { template, resource });
+    let cm = resource.commitment().inner();
+    let prover = MockProver::run(11, &circuit, vec![vec![cm]]).unwrap();
+    assert!(prover.verify().is_err());
+}
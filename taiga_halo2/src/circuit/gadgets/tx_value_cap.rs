@@ -0,0 +1,112 @@
+/// Constrain sum(output_quantities) <= cap, with cap - sum(output_quantities) in 64 bits
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    coin_selection::u64_range_check,
+    sub::{SubChip, SubInstructions},
+};
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// Check the total quantity of the transaction outputs doesn't exceed the public cap. The output
+// quantities are 64-bit values from the resource commitments, so the sum can't wrap around and a
+// total over the cap makes the difference negative, i.e. far out of 64 bits.
+pub fn tx_value_cap_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    sub_chip: SubChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    output_quantities: &[AssignedCell<pallas::Base, pallas::Base>],
+    cap: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    assert!(!output_quantities.is_empty());
+    let mut total = output_quantities[0].clone();
+    for quantity in output_quantities.iter().skip(1) {
+        total = add_chip.add(
+            layouter.namespace(|| "sum output quantities"),
+            &total,
+            quantity,
+        )?;
+    }
+
+    // cap < 2^64 and cap - total < 2^64
+    u64_range_check(layouter.namespace(|| "cap range check"), lookup_config, cap)?;
+    let headroom = sub_chip.sub(layouter.namespace(|| "cap - total"), cap, &total)?;
+    u64_range_check(
+        layouter.namespace(|| "headroom range check"),
+        lookup_config,
+        &headroom,
+    )
+}
+
+#[test]
+fn test_halo2_tx_value_cap_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::constant::NUM_RESOURCE;
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+
+    #[derive(Default)]
+    struct MyCircuit {
+        output_quantities: [u64; NUM_RESOURCE],
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let output_quantities = self
+                .output_quantities
+                .iter()
+                .map(|quantity| {
+                    assign_free_advice(
+                        layouter.namespace(|| "witness output quantity"),
+                        config.advices[0],
+                        Value::known(pallas::Base::from(*quantity)),
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let cap = assign_free_instance(
+                layouter.namespace(|| "public cap"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            tx_value_cap_gadget(
+                layouter.namespace(|| "tx value cap"),
+                AddChip::construct(config.add_config, ()),
+                SubChip::construct(config.sub_config, ()),
+                resource_commit_chip.get_lookup_config(),
+                &output_quantities,
+                &cap,
+            )
+        }
+    }
+
+    let cap = pallas::Base::from(1000u64);
+
+    // 600 + 400 is at the cap
+    let circuit = GadgetTestCircuit(MyCircuit {
+        output_quantities: [600, 400],
+    });
+    let prover = MockProver::run(11, &circuit, vec![vec![cap]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // 600 + 401 exceeds the cap
+    let circuit = GadgetTestCircuit(MyCircuit {
+        output_quantities: [600, 401],
+    });
+    let prover = MockProver::run(11, &circuit, vec![vec![cap]]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,174 @@
+/// Constrain bounds[bucket_idx] <= quantity < bounds[bucket_idx + 1] for the public bucket boundaries
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_advice, assign_free_constant,
+    coin_selection::u64_range_check,
+    mul::{MulChip, MulInstructions},
+    sub::{SubChip, SubInstructions},
+};
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// The index of the bucket the quantity falls in, where the bucket i is [bounds[i], bounds[i + 1])
+pub fn value_bucket(bounds: &[u64], quantity: u64) -> Option<usize> {
+    bounds
+        .windows(2)
+        .position(|bucket| bucket[0] <= quantity && quantity < bucket[1])
+}
+
+// Check the quantity falls in the claimed bucket without revealing it. The bucket is selected by
+// one-hot flags, and the boundaries are fixed in the circuit.
+#[allow(clippy::too_many_arguments)]
+pub fn value_bucket_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    add_chip: AddChip<pallas::Base>,
+    sub_chip: SubChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    advice: Column<Advice>,
+    bounds: &[u64],
+    bucket_idx: &AssignedCell<pallas::Base, pallas::Base>,
+    quantity: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    assert!(bounds.len() > 1 && bounds.windows(2).all(|bucket| bucket[0] < bucket[1]));
+    u64_range_check(
+        layouter.namespace(|| "quantity range check"),
+        lookup_config,
+        quantity,
+    )?;
+    let zero = assign_free_constant(
+        layouter.namespace(|| "constant zero"),
+        advice,
+        pallas::Base::zero(),
+    )?;
+
+    // flag_count = sum(flags), idx = sum(flags[i] * i), low = sum(flags[i] * bounds[i]) and
+    // high = sum(flags[i] * bounds[i + 1])
+    let mut flag_count = zero.clone();
+    let mut idx = zero.clone();
+    let mut low = zero.clone();
+    let mut high = zero;
+    for (i, bucket) in bounds.windows(2).enumerate() {
+        let flag = assign_free_advice(
+            layouter.namespace(|| "witness bucket flag"),
+            advice,
+            bucket_idx
+                .value()
+                .map(|bucket_idx| pallas::Base::from(*bucket_idx == pallas::Base::from(i as u64))),
+        )?;
+        let flag_square = mul_chip.mul(layouter.namespace(|| "flag * flag"), &flag, &flag)?;
+        layouter.assign_region(
+            || "bool_check bucket flag",
+            |mut region| region.constrain_equal(flag_square.cell(), flag.cell()),
+        )?;
+        flag_count = add_chip.add(layouter.namespace(|| "count flags"), &flag_count, &flag)?;
+
+        for (acc, constant) in [
+            (&mut idx, i as u64),
+            (&mut low, bucket[0]),
+            (&mut high, bucket[1]),
+        ] {
+            let constant = assign_free_constant(
+                layouter.namespace(|| "bucket constant"),
+                advice,
+                pallas::Base::from(constant),
+            )?;
+            let term = mul_chip.mul(layouter.namespace(|| "flag * constant"), &flag, &constant)?;
+            *acc = add_chip.add(layouter.namespace(|| "select bucket"), acc, &term)?;
+        }
+    }
+    layouter.assign_region(
+        || "check bucket selection",
+        |mut region| {
+            region.constrain_constant(flag_count.cell(), pallas::Base::one())?;
+            region.constrain_equal(idx.cell(), bucket_idx.cell())
+        },
+    )?;
+
+    // quantity - low and high - 1 - quantity are in 64 bits
+    let above_low = sub_chip.sub(layouter.namespace(|| "quantity - low"), quantity, &low)?;
+    u64_range_check(
+        layouter.namespace(|| "low <= quantity"),
+        lookup_config,
+        &above_low,
+    )?;
+    let below_high = {
+        let gap = sub_chip.sub(layouter.namespace(|| "high - quantity"), &high, quantity)?;
+        let one = assign_free_constant(
+            layouter.namespace(|| "constant one"),
+            advice,
+            pallas::Base::one(),
+        )?;
+        sub_chip.sub(layouter.namespace(|| "high - quantity - 1"), &gap, &one)?
+    };
+    u64_range_check(
+        layouter.namespace(|| "quantity < high"),
+        lookup_config,
+        &below_high,
+    )
+}
+
+#[test]
+fn test_halo2_value_bucket_gadget() {
+    use crate::circuit::gadgets::assign_free_instance;
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+
+    const BOUNDS: [u64; 5] = [0, 10, 100, 1000, 10000];
+
+    #[derive(Default)]
+    struct MyCircuit {
+        quantity: u64,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let quantity = assign_free_advice(
+                layouter.namespace(|| "witness quantity"),
+                config.advices[0],
+                Value::known(pallas::Base::from(self.quantity)),
+            )?;
+            let bucket_idx = assign_free_instance(
+                layouter.namespace(|| "public bucket index"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            value_bucket_gadget(
+                layouter.namespace(|| "value bucket"),
+                AddChip::construct(config.add_config, ()),
+                SubChip::construct(config.sub_config, ()),
+                MulChip::construct(config.mul_config),
+                resource_commit_chip.get_lookup_config(),
+                config.advices[0],
+                &BOUNDS,
+                &bucket_idx,
+                &quantity,
+            )
+        }
+    }
+
+    // 500 is in the bucket 2, [100, 1000)
+    assert_eq!(value_bucket(&BOUNDS, 500), Some(2));
+    let circuit = GadgetTestCircuit(MyCircuit { quantity: 500 });
+    let prover = MockProver::run(11, &circuit, vec![vec![pallas::Base::from(2u64)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A claim of the bucket 1
+    let prover = MockProver::run(11, &circuit, vec![vec![pallas::Base::from(1u64)]]).unwrap();
+    assert!(prover.verify().is_err());
+}
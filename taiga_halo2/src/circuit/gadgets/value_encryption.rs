@@ -0,0 +1,176 @@
+/// Constrain the public ciphertext encrypts the resource quantity to the viewing key
+use crate::circuit::{
+    gadgets::add::AddChip, resource_encryption_circuit::resource_encryption_gadget,
+};
+use crate::constant::TaigaFixedBases;
+use crate::resource_encryption::{ResourceCiphertext, ResourcePlaintext, SecretKey};
+use crate::utils::mod_r_p;
+use halo2_gadgets::{
+    ecc::{chip::EccChip, NonIdentityPoint},
+    poseidon::Pow5Config as PoseidonConfig,
+};
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error, Instance},
+};
+use pasta_curves::{
+    group::{Curve, Group},
+    pallas,
+};
+
+// The resource encryption public inputs of the quantity encrypted to the viewing key: the
+// ciphertext followed by the sender's pk. The auditor decrypts it with the viewing sk and the
+// sender's pk.
+pub fn encrypt_value(
+    quantity: u64,
+    viewing_pk: &pallas::Point,
+    sender_sk: pallas::Base,
+    encrypt_nonce: pallas::Base,
+) -> Vec<pallas::Base> {
+    let plaintext = ResourcePlaintext::padding(&vec![pallas::Base::from(quantity)]);
+    let key = SecretKey::from_dh_exchange(viewing_pk, &mod_r_p(sender_sk));
+    let cipher = ResourceCiphertext::encrypt(&plaintext, &key, &encrypt_nonce);
+
+    let sender_pk = pallas::Point::generator() * mod_r_p(sender_sk);
+    let sender_pk_coord = sender_pk.to_affine().coordinates().unwrap();
+    let mut public_inputs = cipher.inner().to_vec();
+    public_inputs.push(*sender_pk_coord.x());
+    public_inputs.push(*sender_pk_coord.y());
+    public_inputs
+}
+
+// Check the ciphertext in the resource encryption public inputs encrypts the quantity. The quantity
+// is expected to be the one of the resource variables, so a spender can't encrypt another value.
+#[allow(clippy::too_many_arguments)]
+pub fn value_encryption_gadget(
+    layouter: impl Layouter<pallas::Base>,
+    advice: Column<Advice>,
+    instances: Column<Instance>,
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    add_chip: AddChip<pallas::Base>,
+    ecc_chip: EccChip<TaigaFixedBases>,
+    encrypt_nonce: AssignedCell<pallas::Base, pallas::Base>,
+    sender_sk: AssignedCell<pallas::Base, pallas::Base>,
+    viewing_pk: NonIdentityPoint<pallas::Affine, EccChip<TaigaFixedBases>>,
+    quantity: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    resource_encryption_gadget(
+        layouter,
+        advice,
+        instances,
+        poseidon_config,
+        add_chip,
+        ecc_chip,
+        encrypt_nonce,
+        sender_sk,
+        viewing_pk,
+        &mut vec![quantity.clone()],
+    )
+}
+
+#[test]
+fn test_halo2_value_encryption_gadget() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::constant::{
+        VP_CIRCUIT_PUBLIC_INPUT_NUM, VP_CIRCUIT_RESOURCE_ENCRYPTION_PUBLIC_INPUT_BEGIN_IDX,
+    };
+    use halo2_proofs::{arithmetic::Field, circuit::Value, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        quantity: u64,
+        viewing_pk: pallas::Point,
+        sender_sk: pallas::Base,
+        encrypt_nonce: pallas::Base,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let quantity = assign_free_advice(
+                layouter.namespace(|| "witness quantity"),
+                config.advices[0],
+                Value::known(pallas::Base::from(self.quantity)),
+            )?;
+            let encrypt_nonce = assign_free_advice(
+                layouter.namespace(|| "witness encrypt nonce"),
+                config.advices[0],
+                Value::known(self.encrypt_nonce),
+            )?;
+            let sender_sk = assign_free_advice(
+                layouter.namespace(|| "witness sender sk"),
+                config.advices[0],
+                Value::known(self.sender_sk),
+            )?;
+            let ecc_chip = EccChip::construct(config.ecc_config);
+            let viewing_pk = NonIdentityPoint::new(
+                ecc_chip.clone(),
+                layouter.namespace(|| "witness viewing pk"),
+                Value::known(self.viewing_pk.to_affine()),
+            )?;
+
+            value_encryption_gadget(
+                layouter.namespace(|| "value encryption"),
+                config.advices[0],
+                config.instances,
+                config.poseidon_config,
+                AddChip::construct(config.add_config, ()),
+                ecc_chip,
+                encrypt_nonce,
+                sender_sk,
+                viewing_pk,
+                &quantity,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let viewing_sk = pallas::Scalar::random(&mut rng);
+    let viewing_pk = pallas::Point::generator() * viewing_sk;
+    let sender_sk = pallas::Base::random(&mut rng);
+    let encrypt_nonce = pallas::Base::random(&mut rng);
+    let circuit = GadgetTestCircuit(MyCircuit {
+        quantity: 5000,
+        viewing_pk,
+        sender_sk,
+        encrypt_nonce,
+    });
+    let public_inputs = |cipher: Vec<pallas::Base>| {
+        let mut public_inputs = vec![pallas::Base::zero(); VP_CIRCUIT_PUBLIC_INPUT_NUM];
+        public_inputs[VP_CIRCUIT_RESOURCE_ENCRYPTION_PUBLIC_INPUT_BEGIN_IDX..]
+            .copy_from_slice(&cipher);
+        public_inputs
+    };
+
+    // The ciphertext encrypts the quantity, and the auditor can decrypt it
+    let cipher = encrypt_value(5000, &viewing_pk, sender_sk, encrypt_nonce);
+    let sender_pk = pallas::Point::generator() * mod_r_p(sender_sk);
+    let auditor_key = SecretKey::from_dh_exchange(&sender_pk, &viewing_sk);
+    let ciphertext = ResourceCiphertext::from(cipher[..cipher.len() - 2].to_vec());
+    assert_eq!(
+        ciphertext.decrypt(&auditor_key).unwrap()[0],
+        pallas::Base::from(5000u64)
+    );
+    let prover = MockProver::run(12, &circuit, vec![public_inputs(cipher)]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The ciphertext encrypts another value
+    let cipher = encrypt_value(4000, &viewing_pk, sender_sk, encrypt_nonce);
+    let prover = MockProver::run(12, &circuit, vec![public_inputs(cipher)]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The ciphertext is encrypted to another viewing key
+    let other_pk = pallas::Point::random(&mut rng);
+    let cipher = encrypt_value(5000, &other_pk, sender_sk, encrypt_nonce);
+    let prover = MockProver::run(12, &circuit, vec![public_inputs(cipher)]).unwrap();
+    assert!(prover.verify().is_err());
+}
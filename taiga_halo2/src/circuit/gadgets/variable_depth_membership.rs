@@ -0,0 +1,214 @@
+/// Constrain the leaf is a member of the tree of the witnessed depth: the first `depth` levels of the path are hashed and the rest are skipped
+use crate::circuit::{
+    gadgets::{
+        add::{AddChip, AddInstructions},
+        assign_free_advice, assign_free_constant,
+        conditional_select::ConditionalSelectConfig,
+        mul::{MulChip, MulInstructions},
+    },
+    merkle_circuit::{merkle_poseidon_gadget, MerklePoseidonChip},
+};
+use crate::merkle_tree::{MerklePath, Node, LR};
+use crate::utils::to_u64;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, Error},
+};
+use pasta_curves::pallas;
+
+// Pad the path of a depth-d tree up to max_depth with the empty-node hashes above the current
+// frontier. The padded path is also the path of the leaf in the tree deepened to max_depth,
+// where the depth-d tree is the leftmost subtree.
+pub fn pad_merkle_path(merkle_path: &MerklePath, max_depth: usize) -> MerklePath {
+    let mut path = merkle_path.get_path();
+    assert!(path.len() <= max_depth);
+    let mut empty_node = Node::from(pallas::Base::zero());
+    for _ in 0..path.len() {
+        empty_node = Node::combine(&empty_node, &empty_node);
+    }
+    while path.len() < max_depth {
+        path.push((empty_node.inner(), LR::R));
+        empty_node = Node::combine(&empty_node, &empty_node);
+    }
+    MerklePath::from_path(
+        path.into_iter()
+            .map(|(node, lr)| (Node::from(node), lr))
+            .collect(),
+    )
+}
+
+// Check the membership against the root of the tree of the witnessed depth. The path must be padded to the fixed max depth of the circuit.
+#[allow(clippy::too_many_arguments)]
+pub fn variable_depth_membership_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    merkle_chip: MerklePoseidonChip,
+    conditional_select_config: ConditionalSelectConfig,
+    add_chip: AddChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    advice: Column<Advice>,
+    leaf: AssignedCell<pallas::Base, pallas::Base>,
+    depth: &AssignedCell<pallas::Base, pallas::Base>,
+    merkle_path: &[(pallas::Base, LR)],
+    root: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let mut cur = leaf;
+    let mut hashed_levels = assign_free_constant(
+        layouter.namespace(|| "constant zero"),
+        advice,
+        pallas::Base::zero(),
+    )?;
+    for (level, sibling) in merkle_path.iter().enumerate() {
+        // flag = 1 if the level is below the depth
+        let flag = assign_free_advice(
+            layouter.namespace(|| "witness level flag"),
+            advice,
+            depth
+                .value()
+                .map(|depth| pallas::Base::from((level as u64) < to_u64(depth))),
+        )?;
+        let flag_square = mul_chip.mul(layouter.namespace(|| "flag * flag"), &flag, &flag)?;
+        layouter.assign_region(
+            || "bool_check level flag",
+            |mut region| region.constrain_equal(flag_square.cell(), flag.cell()),
+        )?;
+        hashed_levels = add_chip.add(
+            layouter.namespace(|| "count hashed levels"),
+            &hashed_levels,
+            &flag,
+        )?;
+
+        // Hash the node with the sibling if the flag is set, otherwise carry the node over
+        let parent = merkle_poseidon_gadget(
+            layouter.namespace(|| "merkle level"),
+            merkle_chip.clone(),
+            cur.clone(),
+            &[*sibling],
+        )?;
+        cur = layouter.assign_region(
+            || "select node",
+            |mut region| {
+                conditional_select_config.assign_region(&flag, &parent, &cur, 0, &mut region)
+            },
+        )?;
+    }
+
+    // The number of hashed levels is the depth
+    layouter.assign_region(
+        || "check depth",
+        |mut region| region.constrain_equal(hashed_levels.cell(), depth.cell()),
+    )?;
+
+    layouter.assign_region(
+        || "check root",
+        |mut region| region.constrain_equal(cur.cell(), root.cell()),
+    )
+}
+
+#[test]
+fn test_halo2_variable_depth_membership_gadget() {
+    use crate::circuit::gadgets::assign_free_instance;
+    use crate::circuit::merkle_circuit::MerklePoseidonConfig;
+    use crate::circuit::vp_circuit::{
+        testing::{MerkleGadgetTest, MerkleGadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::merkle_tree::compute_anchor;
+    use halo2_proofs::{arithmetic::Field, circuit::Value, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    const MAX_DEPTH: usize = 4;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        leaf: pallas::Base,
+        depth: u64,
+        merkle_path: MerklePath,
+    }
+
+    impl MerkleGadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            merkle_config: MerklePoseidonConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let leaf = assign_free_advice(
+                layouter.namespace(|| "witness leaf"),
+                config.advices[0],
+                Value::known(self.leaf),
+            )?;
+            let depth = assign_free_advice(
+                layouter.namespace(|| "witness depth"),
+                config.advices[0],
+                Value::known(pallas::Base::from(self.depth)),
+            )?;
+            let root = assign_free_instance(
+                layouter.namespace(|| "public root"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+
+            variable_depth_membership_gadget(
+                layouter.namespace(|| "variable depth membership"),
+                MerklePoseidonChip::construct(merkle_config),
+                config.conditional_select_config,
+                AddChip::construct(config.add_config, ()),
+                MulChip::construct(config.mul_config),
+                config.advices[0],
+                leaf,
+                &depth,
+                &self.merkle_path.get_path(),
+                &root,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+
+    // A depth-2 tree
+    let leaves: Vec<Node> = (0..4).map(|_| Node::rand(&mut rng)).collect();
+    let path_2 = MerklePath::from_path(vec![
+        (leaves[1], LR::R),
+        (Node::combine(&leaves[2], &leaves[3]), LR::R),
+    ]);
+    let root_2 = compute_anchor(&leaves);
+    assert_eq!(path_2.root(leaves[0]), root_2);
+
+    // The padded path proves the membership in the tree deepened to the max depth
+    let padded_path_2 = pad_merkle_path(&path_2, MAX_DEPTH);
+    let deepened_leaves = [leaves.clone(), vec![Node::from(pallas::Base::zero()); 12]].concat();
+    assert_eq!(
+        padded_path_2.root(leaves[0]),
+        compute_anchor(&deepened_leaves)
+    );
+
+    let circuit = MerkleGadgetTestCircuit(MyCircuit {
+        leaf: leaves[0].inner(),
+        depth: 2,
+        merkle_path: padded_path_2.clone(),
+    });
+    let prover = MockProver::run(11, &circuit, vec![vec![root_2.inner()]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A depth-3 tree
+    let leaf = pallas::Base::random(&mut rng);
+    let path_3 = MerklePath::random(&mut rng, 3);
+    let root_3 = path_3.root(Node::from(leaf));
+    let circuit = MerkleGadgetTestCircuit(MyCircuit {
+        leaf,
+        depth: 3,
+        merkle_path: pad_merkle_path(&path_3, MAX_DEPTH),
+    });
+    let prover = MockProver::run(11, &circuit, vec![vec![root_3.inner()]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The depth doesn't match the root
+    let circuit = MerkleGadgetTestCircuit(MyCircuit {
+        leaf: leaves[0].inner(),
+        depth: 3,
+        merkle_path: padded_path_2,
+    });
+    let prover = MockProver::run(11, &circuit, vec![vec![root_2.inner()]]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,189 @@
+/// Constrain the wrapped resource mints the quantity of the native resource under the wrapped kind
+use crate::circuit::gadgets::poseidon_hash::poseidon_hash_gadget;
+use crate::circuit::vp_circuit::ResourceVariables;
+use crate::utils::poseidon_hash;
+use halo2_gadgets::poseidon::Pow5Config as PoseidonConfig;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+use pasta_curves::pallas;
+
+// The label of the wrapped asset. The wrapped resource is controlled by the wrapper logic and its
+// label commits to the kind of the native asset, so each native asset has exactly one wrapped asset.
+pub fn wrapped_label(native_logic: pallas::Base, native_label: pallas::Base) -> pallas::Base {
+    poseidon_hash(native_logic, native_label)
+}
+
+// Check a wrap or unwrap pair. Wrapping burns the native resource and mints the wrapped one,
+// unwrapping does the reverse, and in both cases the quantities must be equal.
+pub fn wrap_unwrap_gadget(
+    poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
+    mut layouter: impl Layouter<pallas::Base>,
+    native: &ResourceVariables,
+    wrapped: &ResourceVariables,
+    wrapper_logic: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    let expected_label = poseidon_hash_gadget(
+        poseidon_config,
+        layouter.namespace(|| "wrapped label"),
+        [native.logic.clone(), native.label.clone()],
+    )?;
+
+    layouter.assign_region(
+        || "check wrap pair",
+        |mut region| {
+            region.constrain_equal(wrapped.logic.cell(), wrapper_logic.cell())?;
+            region.constrain_equal(wrapped.label.cell(), expected_label.cell())?;
+            region.constrain_equal(wrapped.quantity.cell(), native.quantity.cell())
+        },
+    )
+}
+
+#[test]
+fn test_halo2_wrap_unwrap_gadget() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::resource::{tests::random_resource, Resource};
+    use halo2_gadgets::poseidon::{primitives as poseidon, Pow5Chip as PoseidonChip};
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        native: Resource,
+        wrapped: Resource,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        #[allow(clippy::type_complexity)]
+        type Config = (
+            [Column<Advice>; 4],
+            Column<Instance>,
+            PoseidonConfig<pallas::Base, 3, 2>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let lagrange_coeffs = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let poseidon_config = PoseidonChip::configure::<poseidon::P128Pow5T3>(
+                meta,
+                advices[1..4].try_into().unwrap(),
+                advices[0],
+                lagrange_coeffs[0..3].try_into().unwrap(),
+                lagrange_coeffs[3..6].try_into().unwrap(),
+            );
+            (advices, instance, poseidon_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, poseidon_config) = config;
+            let mut witness = |name: &'static str, value: pallas::Base| {
+                assign_free_advice(layouter.namespace(|| name), advices[0], Value::known(value))
+            };
+            let mut witness_resource = |resource: &Resource| -> Result<ResourceVariables, Error> {
+                Ok(ResourceVariables {
+                    logic: witness("logic", resource.get_logic())?,
+                    label: witness("label", resource.get_label())?,
+                    quantity: witness("quantity", pallas::Base::from(resource.quantity))?,
+                    is_ephemeral: witness(
+                        "is_ephemeral",
+                        pallas::Base::from(resource.is_ephemeral),
+                    )?,
+                    value: witness("value", resource.value)?,
+                    nonce: witness("nonce", resource.nonce.inner())?,
+                    npk: witness("npk", resource.get_npk())?,
+                    rseed: witness("rseed", resource.rseed)?,
+                })
+            };
+            let native = witness_resource(&self.native)?;
+            let wrapped = witness_resource(&self.wrapped)?;
+            let wrapper_logic = assign_free_instance(
+                layouter.namespace(|| "public wrapper logic"),
+                instance,
+                0,
+                advices[0],
+            )?;
+
+            wrap_unwrap_gadget(
+                poseidon_config,
+                layouter.namespace(|| "wrap unwrap"),
+                &native,
+                &wrapped,
+                &wrapper_logic,
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let wrapper_logic = pallas::Base::from(0x77726170u64);
+    let mut native = random_resource(&mut rng);
+    native.quantity = 100;
+    let mut wrapped = random_resource(&mut rng);
+    wrapped.kind.logic = wrapper_logic;
+    wrapped.kind.label = wrapped_label(native.get_logic(), native.get_label());
+    wrapped.quantity = native.quantity;
+
+    // Wrap the native resource
+    let circuit = MyCircuit { native, wrapped };
+    let prover = MockProver::run(8, &circuit, vec![vec![wrapper_logic]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The wrapped resource skims part of the native quantity
+    let circuit = MyCircuit {
+        native,
+        wrapped: Resource {
+            quantity: 90,
+            ..wrapped
+        },
+    };
+    let prover = MockProver::run(8, &circuit, vec![vec![wrapper_logic]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The wrapped resource is not the wrapped asset of the native resource
+    let other_native = random_resource(&mut rng);
+    let circuit = MyCircuit {
+        native: Resource {
+            quantity: native.quantity,
+            ..other_native
+        },
+        wrapped,
+    };
+    let prover = MockProver::run(8, &circuit, vec![vec![wrapper_logic]]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -41,6 +41,50 @@ pub fn nullifier_circuit(
     )
 }
 
+/// The constants of the resource integrity checks. They're assigned once per circuit and shared by
+/// all the resource checks instead of being reassigned for every resource.
+#[derive(Clone, Debug)]
+pub struct ResourceCheckConstants {
+    pub zero: AssignedCell<pallas::Base, pallas::Base>,
+    pub prf_expand_personalization: AssignedCell<pallas::Base, pallas::Base>,
+    pub prf_expand_rcm: AssignedCell<pallas::Base, pallas::Base>,
+    pub prf_expand_psi: AssignedCell<pallas::Base, pallas::Base>,
+}
+
+impl ResourceCheckConstants {
+    pub fn assign(
+        mut layouter: impl Layouter<pallas::Base>,
+        advice: Column<Advice>,
+    ) -> Result<Self, Error> {
+        let zero = assign_free_constant(
+            layouter.namespace(|| "constant zero"),
+            advice,
+            pallas::Base::zero(),
+        )?;
+        let prf_expand_personalization = assign_free_constant(
+            layouter.namespace(|| "constant PRF_EXPAND_PERSONALIZATION_TO_FIELD"),
+            advice,
+            *PRF_EXPAND_PERSONALIZATION_TO_FIELD,
+        )?;
+        let prf_expand_rcm = assign_free_constant(
+            layouter.namespace(|| "constant PRF_EXPAND_RCM"),
+            advice,
+            pallas::Base::from(PRF_EXPAND_RCM as u64),
+        )?;
+        let prf_expand_psi = assign_free_constant(
+            layouter.namespace(|| "constant PRF_EXPAND_PSI"),
+            advice,
+            pallas::Base::from(PRF_EXPAND_PSI as u64),
+        )?;
+        Ok(Self {
+            zero,
+            prf_expand_personalization,
+            prf_expand_rcm,
+            prf_expand_psi,
+        })
+    }
+}
+
 // Check input resource integrity and return the input resource variables and the nullifier
 #[allow(clippy::too_many_arguments)]
 pub fn check_input_resource(
@@ -48,6 +92,7 @@ pub fn check_input_resource(
     advices: [Column<Advice>; 10],
     instances: Column<Instance>,
     resource_commit_chip: ResourceCommitChip,
+    constants: &ResourceCheckConstants,
     input_resource: Resource,
     nf_row_idx: usize,
 ) -> Result<InputResourceVariables, Error> {
@@ -59,17 +104,11 @@ pub fn check_input_resource(
         Value::known(nk),
     )?;
 
-    let zero_constant = assign_free_constant(
-        layouter.namespace(|| "constant zero"),
-        advices[0],
-        pallas::Base::zero(),
-    )?;
-
     // npk = Com_r(nk, zero)
     let npk = poseidon_hash_gadget(
         resource_commit_chip.get_poseidon_config(),
         layouter.namespace(|| "npk encoding"),
-        [nk_var.clone(), zero_constant],
+        [nk_var.clone(), constants.zero.clone()],
     )?;
 
     // Witness value
@@ -153,6 +192,9 @@ pub fn check_input_resource(
         rcm.clone(),
     )?;
 
+    // A zero nk would make the nullifier predictable
+    resource_commit_chip.check_non_zero(layouter.namespace(|| "nk != 0"), &nk_var)?;
+
     // Generate nullifier
     let nf = nullifier_circuit(
         layouter.namespace(|| "Generate nullifier"),
@@ -190,6 +232,7 @@ pub fn check_output_resource(
     advices: [Column<Advice>; 10],
     instances: Column<Instance>,
     resource_commit_chip: ResourceCommitChip,
+    constants: &ResourceCheckConstants,
     output_resource: Resource,
     old_nf: AssignedCell<pallas::Base, pallas::Base>,
     cm_row_idx: usize,
@@ -237,24 +280,12 @@ pub fn check_output_resource(
     )?;
 
     // Witness rcm
-    let prf_expand_personalization = assign_free_constant(
-        layouter.namespace(|| "constant PRF_EXPAND_PERSONALIZATION_TO_FIELD"),
-        advices[0],
-        *PRF_EXPAND_PERSONALIZATION_TO_FIELD,
-    )?;
-    let rcm_message = {
-        let prf_expand_rcm = assign_free_constant(
-            layouter.namespace(|| "constant PRF_EXPAND_RCM"),
-            advices[0],
-            pallas::Base::from(PRF_EXPAND_RCM as u64),
-        )?;
-        [
-            prf_expand_personalization.clone(),
-            prf_expand_rcm,
-            rseed.clone(),
-            old_nf.clone(),
-        ]
-    };
+    let rcm_message = [
+        constants.prf_expand_personalization.clone(),
+        constants.prf_expand_rcm.clone(),
+        rseed.clone(),
+        old_nf.clone(),
+    ];
     let rcm = poseidon_hash_gadget(
         resource_commit_chip.get_poseidon_config(),
         layouter.namespace(|| "derive the rcm"),
@@ -262,19 +293,12 @@ pub fn check_output_resource(
     )?;
 
     // Witness psi
-    let psi_message = {
-        let prf_expand_psi = assign_free_constant(
-            layouter.namespace(|| "constant PRF_EXPAND_PSI"),
-            advices[0],
-            pallas::Base::from(PRF_EXPAND_PSI as u64),
-        )?;
-        [
-            prf_expand_personalization,
-            prf_expand_psi,
-            rseed.clone(),
-            old_nf.clone(),
-        ]
-    };
+    let psi_message = [
+        constants.prf_expand_personalization.clone(),
+        constants.prf_expand_psi.clone(),
+        rseed.clone(),
+        old_nf.clone(),
+    ];
     let psi = poseidon_hash_gadget(
         resource_commit_chip.get_poseidon_config(),
         layouter.namespace(|| "derive the psi"),
@@ -442,7 +466,7 @@ pub fn compute_delta_commitment(
     commitment_v.add(layouter.namespace(|| "delta commitment"), &blind)
 }
 
-fn quantity_range_check<const K: usize>(
+pub(crate) fn quantity_range_check<const K: usize>(
     mut layouter: impl Layouter<pallas::Base>,
     lookup_config: &LookupRangeCheckConfig<pallas::Base, K>,
     quantity: u64,
@@ -610,3 +634,228 @@ fn test_halo2_nullifier_circuit() {
     let prover = MockProver::run(11, &circuit, vec![]).unwrap();
     assert_eq!(prover.verify(), Ok(()))
 }
+
+#[test]
+fn test_halo2_native_nullifier_matches_circuit() {
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::nullifier::NullifierKeyContainer;
+    use crate::resource::tests::random_resource;
+    use halo2_proofs::{arithmetic::Field, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        input_resource: Resource,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let constants = ResourceCheckConstants::assign(
+                layouter.namespace(|| "resource check constants"),
+                config.advices[0],
+            )?;
+            check_input_resource(
+                layouter.namespace(|| "check input resource"),
+                config.advices,
+                config.instances,
+                ResourceCommitChip::construct(config.resource_commit_config),
+                &constants,
+                self.input_resource,
+                0,
+            )?;
+            Ok(())
+        }
+    }
+
+    let mut rng = OsRng;
+    let nk = pallas::Base::random(&mut rng);
+    let mut input_resource = random_resource(&mut rng);
+    input_resource.nk_container = NullifierKeyContainer::from_key(nk);
+
+    // The wallet only knows the npk of the resource and holds the nk separately
+    let mut watched_resource = input_resource;
+    watched_resource.nk_container = input_resource.nk_container.to_commitment();
+    let nf = watched_resource.nullifier(nk);
+    assert_eq!(nf, input_resource.get_nf().unwrap());
+
+    let circuit = GadgetTestCircuit(MyCircuit { input_resource });
+    let prover = MockProver::run(11, &circuit, vec![vec![nf.inner()]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()))
+}
+
+#[test]
+fn test_halo2_shared_resource_check_constants() {
+    use crate::circuit::vp_circuit::{testing::circuit_usage, ValidityPredicateConfig};
+    use crate::constant::NUM_RESOURCE;
+    use crate::resource::tests::random_resource;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use rand::rngs::OsRng;
+
+    // Checks NUM_RESOURCE pairs of input and output resources, assigning the constants once or for
+    // every pair as before.
+    #[derive(Default)]
+    struct MyCircuit {
+        input_resources: [Resource; NUM_RESOURCE],
+        output_resources: [Resource; NUM_RESOURCE],
+        shared_constants: bool,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = ValidityPredicateConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            Self::Config::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let resource_commit_chip =
+                ResourceCommitChip::construct(config.resource_commit_config.clone());
+            let shared_constants = if self.shared_constants {
+                Some(ResourceCheckConstants::assign(
+                    layouter.namespace(|| "resource check constants"),
+                    config.advices[0],
+                )?)
+            } else {
+                None
+            };
+            for (i, (input_resource, output_resource)) in self
+                .input_resources
+                .iter()
+                .zip(self.output_resources.iter())
+                .enumerate()
+            {
+                let constants = match &shared_constants {
+                    Some(constants) => constants.clone(),
+                    None => ResourceCheckConstants::assign(
+                        layouter.namespace(|| "resource check constants"),
+                        config.advices[0],
+                    )?,
+                };
+                let input_resource_variables = check_input_resource(
+                    layouter.namespace(|| "check input resource"),
+                    config.advices,
+                    config.instances,
+                    resource_commit_chip.clone(),
+                    &constants,
+                    *input_resource,
+                    i * 2,
+                )?;
+                check_output_resource(
+                    layouter.namespace(|| "check output resource"),
+                    config.advices,
+                    config.instances,
+                    resource_commit_chip.clone(),
+                    &constants,
+                    *output_resource,
+                    input_resource_variables.nf,
+                    i * 2 + 1,
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    let mut rng = OsRng;
+    let input_resources = [(); NUM_RESOURCE].map(|_| random_resource(&mut rng));
+    let output_resources = [(); NUM_RESOURCE].map(|_| random_resource(&mut rng));
+    let public_inputs = vec![vec![pallas::Base::zero(); NUM_RESOURCE * 2]];
+
+    let shared = circuit_usage(
+        &MyCircuit {
+            input_resources,
+            output_resources,
+            shared_constants: true,
+        },
+        public_inputs.clone(),
+    );
+    let per_resource = circuit_usage(
+        &MyCircuit {
+            input_resources,
+            output_resources,
+            shared_constants: false,
+        },
+        public_inputs,
+    );
+
+    // Sharing saves the 4 constants of every resource pair but one
+    assert_eq!(
+        per_resource.advice_cells - shared.advice_cells,
+        4 * (NUM_RESOURCE - 1)
+    );
+    assert!(shared.rows <= per_resource.rows);
+}
+
+#[test]
+fn test_halo2_non_zero_nk() {
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::nullifier::NullifierKeyContainer;
+    use crate::resource::tests::random_resource;
+    use halo2_proofs::{arithmetic::Field, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        input_resource: Resource,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let constants = ResourceCheckConstants::assign(
+                layouter.namespace(|| "resource check constants"),
+                config.advices[0],
+            )?;
+            check_input_resource(
+                layouter.namespace(|| "check input resource"),
+                config.advices,
+                config.instances,
+                ResourceCommitChip::construct(config.resource_commit_config),
+                &constants,
+                self.input_resource,
+                0,
+            )?;
+            Ok(())
+        }
+    }
+
+    let mut rng = OsRng;
+    let run = |nk: pallas::Base| {
+        let mut input_resource = random_resource(OsRng);
+        input_resource.nk_container = NullifierKeyContainer::from_key(nk);
+        let nf = input_resource.get_nf().unwrap();
+        let circuit = GadgetTestCircuit(MyCircuit { input_resource });
+        MockProver::run(11, &circuit, vec![vec![nf.inner()]])
+            .unwrap()
+            .verify()
+    };
+
+    // A nonzero nk
+    assert_eq!(run(pallas::Base::random(&mut rng)), Ok(()));
+    // The zero nk
+    assert!(run(pallas::Base::zero()).is_err());
+}
@@ -1,12 +1,22 @@
-use crate::circuit::gadgets::poseidon_hash::poseidon_hash_gadget;
+use crate::circuit::gadgets::{
+    add::{AddChip, AddInstructions},
+    assign_free_advice, assign_free_constant,
+    conditional_select::ConditionalSelectConfig,
+    decompose_128::{decompose_128_gadget, less_than_gadget},
+    mul::{MulChip, MulInstructions},
+    poseidon_hash::poseidon_hash_gadget,
+};
 use crate::merkle_tree::{is_left, LR};
 use halo2_gadgets::{
-    poseidon::Pow5Config as PoseidonConfig,
-    utilities::cond_swap::{CondSwapChip, CondSwapConfig, CondSwapInstructions},
+    poseidon::{primitives as poseidon, Pow5Chip as PoseidonChip, Pow5Config as PoseidonConfig},
+    utilities::{
+        cond_swap::{CondSwapChip, CondSwapConfig, CondSwapInstructions},
+        lookup_range_check::LookupRangeCheckConfig,
+    },
 };
 use halo2_proofs::{
     circuit::{AssignedCell, Chip, Layouter, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed},
 };
 use pasta_curves::pallas;
 
@@ -51,18 +61,71 @@ impl MerklePoseidonChip {
         }
     }
 
+    // Configure the poseidon chip on the cond-swap advice columns, so that the merkle chip
+    // only takes five advice columns in total.
+    pub fn configure_with_shared_advices(
+        meta: &mut ConstraintSystem<pallas::Base>,
+        advices: [Column<Advice>; 5],
+        rc_a: [Column<Fixed>; 3],
+        rc_b: [Column<Fixed>; 3],
+    ) -> MerklePoseidonConfig {
+        let poseidon_config = PoseidonChip::configure::<poseidon::P128Pow5T3>(
+            meta,
+            advices[0..3].try_into().unwrap(),
+            advices[3],
+            rc_a,
+            rc_b,
+        );
+
+        Self::configure(meta, advices, poseidon_config)
+    }
+
     pub fn construct(config: MerklePoseidonConfig) -> Self {
         MerklePoseidonChip { config }
     }
+
+    pub fn get_poseidon_config(&self) -> PoseidonConfig<pallas::Base, 3, 2> {
+        self.config.poseidon_config.clone()
+    }
 }
 
-#[allow(clippy::type_complexity)]
 pub fn merkle_poseidon_gadget(
-    mut layouter: impl Layouter<pallas::Base>,
+    layouter: impl Layouter<pallas::Base>,
     chip: MerklePoseidonChip,
     resource: AssignedCell<pallas::Base, pallas::Base>,
     merkle_path: &[(pallas::Base, LR)],
 ) -> Result<AssignedCell<pallas::Base, pallas::Base>, Error> {
+    merkle_poseidon_gadget_with_trace(layouter, chip, resource, merkle_path).map(|(root, _)| root)
+}
+
+// Hash the first depth levels of the path only, which returns the root of the subtree of that
+// depth the resource is in. The path can be longer than the subtree, e.g. a path of the full
+// commitment tree depth whose upper levels are ignored.
+pub fn merkle_poseidon_gadget_with_depth(
+    layouter: impl Layouter<pallas::Base>,
+    chip: MerklePoseidonChip,
+    resource: AssignedCell<pallas::Base, pallas::Base>,
+    merkle_path: &[(pallas::Base, LR)],
+    depth: usize,
+) -> Result<AssignedCell<pallas::Base, pallas::Base>, Error> {
+    assert!(depth <= merkle_path.len());
+    merkle_poseidon_gadget(layouter, chip, resource, &merkle_path[..depth])
+}
+
+// Return the root along with the parent computed at each level, the last one being the root
+#[allow(clippy::type_complexity)]
+pub fn merkle_poseidon_gadget_with_trace(
+    mut layouter: impl Layouter<pallas::Base>,
+    chip: MerklePoseidonChip,
+    resource: AssignedCell<pallas::Base, pallas::Base>,
+    merkle_path: &[(pallas::Base, LR)],
+) -> Result<
+    (
+        AssignedCell<pallas::Base, pallas::Base>,
+        Vec<AssignedCell<pallas::Base, pallas::Base>>,
+    ),
+    Error,
+> {
     fn swap(
         merkle_chip: &MerklePoseidonChip,
         layouter: impl Layouter<pallas::Base>,
@@ -84,6 +147,7 @@ pub fn merkle_poseidon_gadget(
     }
 
     let mut cur = resource;
+    let mut trace = Vec::with_capacity(merkle_path.len());
     for e in merkle_path.iter() {
         let pair = {
             let pair = (cur, Value::known(e.0));
@@ -100,17 +164,193 @@ pub fn merkle_poseidon_gadget(
             layouter.namespace(|| "merkle poseidon hash"),
             [pair.0, pair.1],
         )?;
+        trace.push(cur.clone());
+    }
+
+    Ok((cur, trace))
+}
+
+// Hash the path with the position bits witnessed, and return the root and the index of the leaf,
+// i.e. the sum of bit_i * 2^i where bit_i = 1 if the node of level i is the right child. The
+// positions are witnesses, so that the caller can derive them from an assigned index.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn merkle_poseidon_gadget_with_index(
+    mut layouter: impl Layouter<pallas::Base>,
+    chip: &MerklePoseidonChip,
+    conditional_select_config: ConditionalSelectConfig,
+    add_chip: AddChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    advice: Column<Advice>,
+    leaf: AssignedCell<pallas::Base, pallas::Base>,
+    merkle_path: &[(pallas::Base, Value<LR>)],
+) -> Result<
+    (
+        AssignedCell<pallas::Base, pallas::Base>,
+        AssignedCell<pallas::Base, pallas::Base>,
+    ),
+    Error,
+> {
+    let mut cur = leaf;
+    let mut bits = Vec::with_capacity(merkle_path.len());
+    for (sibling, lr) in merkle_path.iter() {
+        let bit = assign_free_advice(
+            layouter.namespace(|| "witness position bit"),
+            advice,
+            lr.map(|lr| pallas::Base::from(is_left(lr))),
+        )?;
+        let bit_square = mul_chip.mul(layouter.namespace(|| "bit * bit"), &bit, &bit)?;
+        layouter.assign_region(
+            || "bool_check position bit",
+            |mut region| region.constrain_equal(bit_square.cell(), bit.cell()),
+        )?;
+        let sibling = assign_free_advice(
+            layouter.namespace(|| "witness sibling"),
+            advice,
+            Value::known(*sibling),
+        )?;
+
+        let (left, right) = layouter.assign_region(
+            || "order children",
+            |mut region| {
+                let left = conditional_select_config.assign_region(
+                    &bit,
+                    &sibling,
+                    &cur,
+                    0,
+                    &mut region,
+                )?;
+                let right = conditional_select_config.assign_region(
+                    &bit,
+                    &cur,
+                    &sibling,
+                    2,
+                    &mut region,
+                )?;
+                Ok((left, right))
+            },
+        )?;
+        cur = poseidon_hash_gadget(
+            chip.get_poseidon_config(),
+            layouter.namespace(|| "merkle poseidon hash"),
+            [left, right],
+        )?;
+        bits.push(bit);
+    }
+
+    // index = sum(bits[i] * 2^i)
+    let two = assign_free_constant(
+        layouter.namespace(|| "constant two"),
+        advice,
+        pallas::Base::from(2u64),
+    )?;
+    let mut index = assign_free_constant(
+        layouter.namespace(|| "constant zero"),
+        advice,
+        pallas::Base::zero(),
+    )?;
+    for bit in bits.iter().rev() {
+        let shifted = mul_chip.mul(layouter.namespace(|| "index * 2"), &index, &two)?;
+        index = add_chip.add(layouter.namespace(|| "index + bit"), &shifted, bit)?;
+    }
+
+    Ok((cur, index))
+}
+
+fn witness_positions(merkle_path: &[(pallas::Base, LR)]) -> Vec<(pallas::Base, Value<LR>)> {
+    merkle_path
+        .iter()
+        .map(|(sibling, lr)| (*sibling, Value::known(*lr)))
+        .collect()
+}
+
+// Check the target is absent from a sorted tree: lo and hi are adjacent leaves of the tree and
+// lo < target < hi. The comparisons are on the 128-bit halves of the values, which leaves out the
+// values in [2^254, p).
+#[allow(clippy::too_many_arguments)]
+pub fn merkle_non_membership_gadget(
+    mut layouter: impl Layouter<pallas::Base>,
+    chip: MerklePoseidonChip,
+    conditional_select_config: ConditionalSelectConfig,
+    add_chip: AddChip<pallas::Base>,
+    mul_chip: MulChip<pallas::Base>,
+    lookup_config: &LookupRangeCheckConfig<pallas::Base, 10>,
+    advice: Column<Advice>,
+    target: &AssignedCell<pallas::Base, pallas::Base>,
+    lo: AssignedCell<pallas::Base, pallas::Base>,
+    lo_path: &[(pallas::Base, LR)],
+    hi: AssignedCell<pallas::Base, pallas::Base>,
+    hi_path: &[(pallas::Base, LR)],
+    root: &AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<(), Error> {
+    assert_eq!(lo_path.len(), hi_path.len());
+    let one = assign_free_constant(
+        layouter.namespace(|| "constant one"),
+        advice,
+        pallas::Base::one(),
+    )?;
+
+    // lo < target < hi
+    let mut halves = Vec::with_capacity(3);
+    for value in [&lo, target, &hi] {
+        halves.push(decompose_128_gadget(
+            layouter.namespace(|| "decompose 128"),
+            add_chip.clone(),
+            mul_chip.clone(),
+            lookup_config,
+            advice,
+            value,
+        )?);
+    }
+    for pair in halves.windows(2) {
+        less_than_gadget(
+            layouter.namespace(|| "lower < upper"),
+            add_chip.clone(),
+            mul_chip.clone(),
+            lookup_config,
+            advice,
+            &pair[0],
+            &pair[1],
+        )?;
     }
 
-    Ok(cur)
+    // Both leaves are in the tree, at adjacent positions
+    let (lo_root, lo_index) = merkle_poseidon_gadget_with_index(
+        layouter.namespace(|| "lo membership"),
+        &chip,
+        conditional_select_config,
+        add_chip.clone(),
+        mul_chip.clone(),
+        advice,
+        lo,
+        &witness_positions(lo_path),
+    )?;
+    let (hi_root, hi_index) = merkle_poseidon_gadget_with_index(
+        layouter.namespace(|| "hi membership"),
+        &chip,
+        conditional_select_config,
+        add_chip.clone(),
+        mul_chip,
+        advice,
+        hi,
+        &witness_positions(hi_path),
+    )?;
+    let next_index = add_chip.add(layouter.namespace(|| "lo_index + 1"), &lo_index, &one)?;
+    layouter.assign_region(
+        || "check non-membership",
+        |mut region| {
+            region.constrain_equal(lo_root.cell(), root.cell())?;
+            region.constrain_equal(hi_root.cell(), root.cell())?;
+            region.constrain_equal(next_index.cell(), hi_index.cell())
+        },
+    )
 }
 
 #[test]
 fn test_halo2_merkle_circuit() {
     use crate::circuit::gadgets::assign_free_advice;
+    use crate::circuit::vp_circuit::testing::{min_k, mock_prove_with_witnesses};
     use crate::constant::TAIGA_COMMITMENT_TREE_DEPTH;
     use crate::merkle_tree::{MerklePath, Node};
-    use halo2_gadgets::poseidon::{primitives as poseidon, Pow5Chip as PoseidonChip};
     use halo2_proofs::{
         arithmetic::Field,
         circuit::{Layouter, SimpleFloorPlanner, Value},
@@ -190,7 +430,7 @@ fn test_halo2_merkle_circuit() {
             let expected_root = {
                 let root = self.merkle_path.root(Node::from(self.leaf));
                 assign_free_advice(
-                    layouter.namespace(|| "witness leaf"),
+                    layouter.namespace(|| "witness root"),
                     config.advices[0],
                     Value::known(root.inner()),
                 )?
@@ -209,6 +449,527 @@ fn test_halo2_merkle_circuit() {
 
     let circuit = MyCircuit { leaf, merkle_path };
 
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // 11 is the smallest k that fits the circuit
+    assert_eq!(min_k(&circuit, vec![]), 11);
+
+    // The witnessed leaf and root
+    let (prover, witnesses) = mock_prove_with_witnesses(11, &circuit, vec![]);
+    assert_eq!(prover.verify(), Ok(()));
+    assert_eq!(witnesses["witness leaf/load private"], vec![leaf]);
+    let root = circuit.merkle_path.root(Node::from(leaf)).inner();
+    assert_eq!(witnesses["witness root/load private"], vec![root]);
+}
+
+#[test]
+fn test_halo2_cond_swap_matches_native() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::merkle_tree::cond_swap;
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        plonk::{Circuit, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        a: pallas::Base,
+        b: pallas::Base,
+        swap: bool,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = ([Column<Advice>; 5], Column<Instance>, CondSwapConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let cond_swap_config = CondSwapChip::configure(meta, advices);
+            (advices, instance, cond_swap_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, cond_swap_config) = config;
+            let a = assign_free_advice(
+                layouter.namespace(|| "witness a"),
+                advices[0],
+                Value::known(self.a),
+            )?;
+            let chip = CondSwapChip::<pallas::Base>::construct(cond_swap_config);
+            let (left, right) = chip.swap(
+                layouter.namespace(|| "cond swap"),
+                (a, Value::known(self.b)),
+                Value::known(self.swap),
+            )?;
+            layouter.constrain_instance(left.cell(), instance, 0)?;
+            layouter.constrain_instance(right.cell(), instance, 1)
+        }
+    }
+
+    let mut rng = OsRng;
+    let a = pallas::Base::random(&mut rng);
+    let b = pallas::Base::random(&mut rng);
+    for swap in [false, true] {
+        let (left, right) = cond_swap(a, b, swap);
+        let circuit = MyCircuit { a, b, swap };
+        let prover = MockProver::run(4, &circuit, vec![vec![left, right]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
+
+#[test]
+fn test_halo2_merkle_circuit_with_shared_advices() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::constant::TAIGA_COMMITMENT_TREE_DEPTH;
+    use crate::merkle_tree::{MerklePath, Node};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        leaf: pallas::Base,
+        merkle_path: MerklePath,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = MerklePoseidonConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+            let rc_a = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+            let rc_b = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+            meta.enable_constant(rc_b[0]);
+
+            MerklePoseidonChip::configure_with_shared_advices(meta, advices, rc_a, rc_b)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let leaf = assign_free_advice(
+                layouter.namespace(|| "witness leaf"),
+                config.advices[0],
+                Value::known(self.leaf),
+            )?;
+
+            let root = merkle_poseidon_gadget(
+                layouter.namespace(|| "poseidon merkle"),
+                MerklePoseidonChip::construct(config.clone()),
+                leaf,
+                &self.merkle_path.get_path(),
+            )?;
+
+            let expected_root = assign_free_advice(
+                layouter.namespace(|| "witness expected root"),
+                config.advices[0],
+                Value::known(self.merkle_path.root(Node::from(self.leaf)).inner()),
+            )?;
+            layouter.assign_region(
+                || "constrain result",
+                |mut region| region.constrain_equal(root.cell(), expected_root.cell()),
+            )
+        }
+    }
+
+    // The merkle chip only takes the five shared advice columns
+    let mut cs = ConstraintSystem::<pallas::Base>::default();
+    MyCircuit::configure(&mut cs);
+    assert_eq!(cs.num_advice_columns(), 5);
+
+    let mut rng = OsRng;
+    let leaf = pallas::Base::random(rng);
+    let merkle_path = MerklePath::random(&mut rng, TAIGA_COMMITMENT_TREE_DEPTH);
+    let circuit = MyCircuit { leaf, merkle_path };
+
     let prover = MockProver::run(11, &circuit, vec![]).unwrap();
     assert_eq!(prover.verify(), Ok(()))
 }
+
+#[test]
+fn test_halo2_merkle_circuit_with_depth() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::constant::TAIGA_COMMITMENT_TREE_DEPTH;
+    use crate::merkle_tree::{MerklePath, Node};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error, Instance},
+    };
+    use rand::rngs::OsRng;
+
+    const SUBTREE_DEPTH: usize = 4;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        leaf: pallas::Base,
+        merkle_path: MerklePath,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = (MerklePoseidonConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let rc_a = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+            let rc_b = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+            meta.enable_constant(rc_b[0]);
+
+            (
+                MerklePoseidonChip::configure_with_shared_advices(meta, advices, rc_a, rc_b),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (merkle_config, instance) = config;
+            let leaf = assign_free_advice(
+                layouter.namespace(|| "witness leaf"),
+                merkle_config.advices[0],
+                Value::known(self.leaf),
+            )?;
+
+            // The path has the full depth, and only the subtree levels are hashed
+            let subtree_root = merkle_poseidon_gadget_with_depth(
+                layouter.namespace(|| "poseidon merkle"),
+                MerklePoseidonChip::construct(merkle_config),
+                leaf,
+                &self.merkle_path.get_path(),
+                SUBTREE_DEPTH,
+            )?;
+            layouter.constrain_instance(subtree_root.cell(), instance, 0)
+        }
+    }
+
+    let mut rng = OsRng;
+    let leaf = pallas::Base::random(rng);
+    let merkle_path = MerklePath::random(&mut rng, TAIGA_COMMITMENT_TREE_DEPTH);
+    let subtree_path = MerklePath::from_path(
+        merkle_path.get_path()[..SUBTREE_DEPTH]
+            .iter()
+            .map(|(node, lr)| (Node::from(*node), *lr))
+            .collect(),
+    );
+    let subtree_root = subtree_path.root(Node::from(leaf)).inner();
+    let root = merkle_path.root(Node::from(leaf)).inner();
+    let circuit = MyCircuit { leaf, merkle_path };
+
+    let prover = MockProver::run(11, &circuit, vec![vec![subtree_root]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The root of the full tree isn't the root of the subtree
+    let prover = MockProver::run(11, &circuit, vec![vec![root]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_halo2_merkle_non_membership_gadget() {
+    use crate::circuit::gadgets::{assign_free_instance, decompose_128::two_pow_128};
+    use crate::circuit::resource_commitment::ResourceCommitChip;
+    use crate::circuit::vp_circuit::{
+        testing::{MerkleGadgetTest, MerkleGadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::merkle_tree::{MerkleTreeLeafs, Node};
+    use ff::PrimeField;
+    use halo2_proofs::{circuit::Layouter, dev::MockProver, plonk::Error};
+
+    const DEPTH: usize = 3;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        lo: pallas::Base,
+        lo_path: Vec<(pallas::Base, LR)>,
+        hi: pallas::Base,
+        hi_path: Vec<(pallas::Base, LR)>,
+        root: pallas::Base,
+    }
+
+    impl MerkleGadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            merkle_config: MerklePoseidonConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let target = assign_free_instance(
+                layouter.namespace(|| "target"),
+                config.instances,
+                0,
+                config.advices[0],
+            )?;
+            let lo = assign_free_advice(
+                layouter.namespace(|| "witness lo"),
+                config.advices[0],
+                Value::known(self.lo),
+            )?;
+            let hi = assign_free_advice(
+                layouter.namespace(|| "witness hi"),
+                config.advices[0],
+                Value::known(self.hi),
+            )?;
+            let root = assign_free_advice(
+                layouter.namespace(|| "witness root"),
+                config.advices[0],
+                Value::known(self.root),
+            )?;
+
+            let resource_commit_chip = ResourceCommitChip::construct(config.resource_commit_config);
+            merkle_non_membership_gadget(
+                layouter.namespace(|| "non-membership"),
+                MerklePoseidonChip::construct(merkle_config),
+                config.conditional_select_config,
+                AddChip::construct(config.add_config, ()),
+                MulChip::construct(config.mul_config),
+                resource_commit_chip.get_lookup_config(),
+                config.advices[0],
+                &target,
+                lo,
+                &self.lo_path,
+                hi,
+                &self.hi_path,
+                &root,
+            )
+        }
+    }
+
+    let circuit_for = |tree: &MerkleTreeLeafs, lo_idx: usize, hi_idx: usize| {
+        MerkleGadgetTestCircuit(MyCircuit {
+            lo: tree.leaves()[lo_idx].inner(),
+            lo_path: tree.merkle_path(lo_idx).get_path(),
+            hi: tree.leaves()[hi_idx].inner(),
+            hi_path: tree.merkle_path(hi_idx).get_path(),
+            root: tree.root().inner(),
+        })
+    };
+    let run = |circuit: &MerkleGadgetTestCircuit<MyCircuit>, target: pallas::Base| {
+        MockProver::run(11, circuit, vec![vec![target]])
+            .unwrap()
+            .verify()
+    };
+
+    // A full sorted tree of 10, 20, ..., 80
+    let leaves: Vec<Node> = (1..=(1u64 << DEPTH))
+        .map(|i| Node::from(pallas::Base::from(i * 10)))
+        .collect();
+    let tree = MerkleTreeLeafs::new(DEPTH, &leaves);
+
+    // 35 falls in the gap between 30 and 40
+    let circuit = circuit_for(&tree, 2, 3);
+    assert_eq!(run(&circuit, pallas::Base::from(35u64)), Ok(()));
+
+    // 40 is a leaf, so it's in no gap
+    assert!(run(&circuit, pallas::Base::from(40u64)).is_err());
+    let circuit = circuit_for(&tree, 3, 4);
+    assert!(run(&circuit, pallas::Base::from(40u64)).is_err());
+
+    // 30 < 35 < 50, but 30 and 50 aren't adjacent leaves
+    let circuit = circuit_for(&tree, 2, 4);
+    assert!(run(&circuit, pallas::Base::from(35u64)).is_err());
+
+    // A sorted tree with leaves above 2^253 and near p
+    let two_pow_253 = pallas::Base::from_u128(1 << 125) * two_pow_128();
+    let leaves: Vec<Node> = [
+        pallas::Base::from(10u64),
+        pallas::Base::from(20u64),
+        two_pow_253 + pallas::Base::from(10u64),
+        two_pow_253 + pallas::Base::from(30u64),
+        -pallas::Base::from(30u64),
+        -pallas::Base::from(10u64),
+    ]
+    .into_iter()
+    .map(Node::from)
+    .collect();
+    let tree = MerkleTreeLeafs::new(DEPTH, &leaves);
+
+    // A target above 2^253
+    let circuit = circuit_for(&tree, 2, 3);
+    assert_eq!(
+        run(&circuit, two_pow_253 + pallas::Base::from(20u64)),
+        Ok(())
+    );
+
+    // lo + 1 + gap wraps around p to a small target
+    let circuit = circuit_for(&tree, 4, 5);
+    assert!(run(&circuit, pallas::Base::from(5u64)).is_err());
+
+    // The values in [2^254, p) are left out, even in a gap of the tree
+    assert!(run(&circuit, -pallas::Base::from(20u64)).is_err());
+}
+
+#[test]
+fn test_halo2_merkle_circuit_with_trace() {
+    use crate::circuit::gadgets::assign_free_advice;
+    use crate::constant::TAIGA_COMMITMENT_TREE_DEPTH;
+    use crate::merkle_tree::{cond_swap, MerklePath, Node};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        leaf: pallas::Base,
+        merkle_path: MerklePath,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = MerklePoseidonConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+            let rc_a = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+            let rc_b = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+            meta.enable_constant(rc_b[0]);
+
+            MerklePoseidonChip::configure_with_shared_advices(meta, advices, rc_a, rc_b)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let leaf = assign_free_advice(
+                layouter.namespace(|| "witness leaf"),
+                config.advices[0],
+                Value::known(self.leaf),
+            )?;
+
+            let path = self.merkle_path.get_path();
+            let (root, trace) = merkle_poseidon_gadget_with_trace(
+                layouter.namespace(|| "poseidon merkle"),
+                MerklePoseidonChip::construct(config),
+                leaf,
+                &path,
+            )?;
+            assert_eq!(trace.len(), path.len());
+            assert_eq!(trace.last().unwrap().cell(), root.cell());
+
+            // Each level of the trace is the native parent
+            let mut node = Node::from(self.leaf);
+            for ((sibling, lr), parent) in path.iter().zip(trace.iter()) {
+                let (left, right) = cond_swap(node, Node::from(*sibling), is_left(*lr));
+                node = Node::combine(&left, &right);
+                layouter.assign_region(
+                    || "check parent",
+                    |mut region| region.constrain_constant(parent.cell(), node.inner()),
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    let mut rng = OsRng;
+    let leaf = pallas::Base::random(rng);
+    let merkle_path = MerklePath::random(&mut rng, TAIGA_COMMITMENT_TREE_DEPTH);
+    let circuit = MyCircuit { leaf, merkle_path };
+
+    let prover = MockProver::run(11, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
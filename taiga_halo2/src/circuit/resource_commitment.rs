@@ -1,12 +1,12 @@
-use crate::circuit::gadgets::poseidon_hash::poseidon_hash_gadget;
-use group::ff::PrimeField;
+use crate::circuit::gadgets::{decompose_128::two_pow_128, poseidon_hash::poseidon_hash_gadget};
 use halo2_gadgets::{
     poseidon::Pow5Config as PoseidonConfig,
     utilities::{bool_check, lookup_range_check::LookupRangeCheckConfig},
 };
 use halo2_proofs::{
+    arithmetic::Field,
     circuit::{AssignedCell, Layouter},
-    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
     poly::Rotation,
 };
 use pasta_curves::pallas;
@@ -69,13 +69,10 @@ impl ComposeIsEphemeralQuantity {
             |mut region| {
                 self.q_compose.enable(&mut region, 0)?;
 
-                let compose =
-                    is_ephemeral
-                        .value()
-                        .zip(quantity.value())
-                        .map(|(is_ephemeral, quantity)| {
-                            quantity + is_ephemeral * pallas::Base::from_u128(1 << 64).square()
-                        });
+                let compose = is_ephemeral
+                    .value()
+                    .zip(quantity.value())
+                    .map(|(is_ephemeral, quantity)| quantity + is_ephemeral * two_pow_128());
                 is_ephemeral.copy_advice(|| "is_ephemeral", &mut region, self.col_m, 0)?;
                 quantity.copy_advice(|| "quantity", &mut region, self.col_r, 0)?;
 
@@ -85,9 +82,65 @@ impl ComposeIsEphemeralQuantity {
     }
 }
 
+/// value * inv = 1, i.e. value != 0
+#[derive(Clone, Debug)]
+struct NonZeroCheck {
+    q_non_zero: Selector,
+    col_value: Column<Advice>,
+    col_inv: Column<Advice>,
+}
+
+impl NonZeroCheck {
+    fn configure(
+        meta: &mut ConstraintSystem<pallas::Base>,
+        col_value: Column<Advice>,
+        col_inv: Column<Advice>,
+    ) -> Self {
+        let q_non_zero = meta.selector();
+
+        meta.create_gate("Non-zero check", |meta| {
+            let q_non_zero = meta.query_selector(q_non_zero);
+
+            let value = meta.query_advice(col_value, Rotation::cur());
+            let inv = meta.query_advice(col_inv, Rotation::cur());
+            let one = Expression::Constant(pallas::Base::one());
+
+            Constraints::with_selector(q_non_zero, [("value * inv = 1", value * inv - one)])
+        });
+
+        Self {
+            q_non_zero,
+            col_value,
+            col_inv,
+        }
+    }
+
+    fn assign(
+        &self,
+        layouter: &mut impl Layouter<pallas::Base>,
+        value: &AssignedCell<pallas::Base, pallas::Base>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "Non-zero check",
+            |mut region| {
+                self.q_non_zero.enable(&mut region, 0)?;
+
+                value.copy_advice(|| "value", &mut region, self.col_value, 0)?;
+                // The inverse of zero is witnessed as zero, which fails the check
+                let inv = value
+                    .value()
+                    .map(|value| value.invert().unwrap_or(pallas::Base::zero()));
+                region.assign_advice(|| "inv", self.col_inv, 0, || inv)?;
+                Ok(())
+            },
+        )
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ResourceCommitConfig {
     compose_config: ComposeIsEphemeralQuantity,
+    non_zero_config: NonZeroCheck,
     poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
     lookup_config: LookupRangeCheckConfig<pallas::Base, 10>,
 }
@@ -98,23 +151,32 @@ pub struct ResourceCommitChip {
 }
 
 impl ResourceCommitChip {
+    // The composition gate takes the first three advice columns. With five columns or more, the
+    // non-zero check gets its own columns instead of sharing the first two.
     pub fn configure(
         meta: &mut ConstraintSystem<pallas::Base>,
-        advices: [Column<Advice>; 3],
+        advices: &[Column<Advice>],
         poseidon_config: PoseidonConfig<pallas::Base, 3, 2>,
         lookup_config: LookupRangeCheckConfig<pallas::Base, 10>,
     ) -> ResourceCommitConfig {
-        let two_pow_128 = pallas::Base::from_u128(1 << 64).square();
+        assert!(advices.len() >= 3);
         let compose_config = ComposeIsEphemeralQuantity::configure(
             meta,
             advices[0],
             advices[1],
             advices[2],
-            two_pow_128,
+            two_pow_128(),
         );
 
+        let non_zero_config = if advices.len() >= 5 {
+            NonZeroCheck::configure(meta, advices[3], advices[4])
+        } else {
+            NonZeroCheck::configure(meta, advices[0], advices[1])
+        };
+
         ResourceCommitConfig {
             compose_config,
+            non_zero_config,
             poseidon_config,
             lookup_config,
         }
@@ -131,6 +193,15 @@ impl ResourceCommitChip {
     pub fn get_lookup_config(&self) -> &LookupRangeCheckConfig<pallas::Base, 10> {
         &self.config.lookup_config
     }
+
+    // Check the value is not zero by witnessing its inverse
+    pub fn check_non_zero(
+        &self,
+        mut layouter: impl Layouter<pallas::Base>,
+        value: &AssignedCell<pallas::Base, pallas::Base>,
+    ) -> Result<(), Error> {
+        self.config.non_zero_config.assign(&mut layouter, value)
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -170,3 +241,313 @@ pub fn resource_commit(
         poseidon_message,
     )
 }
+
+/// Both the hiding resource commitment and a binding commitment of the resource.
+///
+/// The hiding commitment is the one of `resource_commit`. The binding commitment leaves out
+/// rcm, so it's the same for two resources differing only in rcm. It still hides the resource
+/// as long as psi is secret, but it's linkable across the two. The native counterpart is
+/// `Resource::binding_commitment`.
+#[allow(clippy::too_many_arguments)]
+pub fn resource_commit_dual(
+    mut layouter: impl Layouter<pallas::Base>,
+    chip: ResourceCommitChip,
+    app_vp: AssignedCell<pallas::Base, pallas::Base>,
+    label: AssignedCell<pallas::Base, pallas::Base>,
+    value: AssignedCell<pallas::Base, pallas::Base>,
+    npk: AssignedCell<pallas::Base, pallas::Base>,
+    nonce: AssignedCell<pallas::Base, pallas::Base>,
+    psi: AssignedCell<pallas::Base, pallas::Base>,
+    quantity: AssignedCell<pallas::Base, pallas::Base>,
+    is_ephemeral: AssignedCell<pallas::Base, pallas::Base>,
+    rcm: AssignedCell<pallas::Base, pallas::Base>,
+) -> Result<
+    (
+        AssignedCell<pallas::Base, pallas::Base>,
+        AssignedCell<pallas::Base, pallas::Base>,
+    ),
+    Error,
+> {
+    let compose_is_ephemeral_and_quantity =
+        chip.config
+            .compose_config
+            .assign(&mut layouter, &is_ephemeral, &quantity)?;
+
+    let binding_message = [
+        app_vp,
+        label,
+        value,
+        npk,
+        nonce,
+        psi,
+        compose_is_ephemeral_and_quantity,
+    ];
+    let binding_cm = poseidon_hash_gadget(
+        chip.config.poseidon_config.clone(),
+        layouter.namespace(|| "binding resource commitment"),
+        binding_message.clone(),
+    )?;
+
+    let [app_vp, label, value, npk, nonce, psi, compose_is_ephemeral_and_quantity] =
+        binding_message;
+    let hiding_message = [
+        app_vp,
+        label,
+        value,
+        npk,
+        nonce,
+        psi,
+        compose_is_ephemeral_and_quantity,
+        rcm,
+    ];
+    let hiding_cm = poseidon_hash_gadget(
+        chip.config.poseidon_config,
+        layouter.namespace(|| "resource commitment"),
+        hiding_message,
+    )?;
+
+    Ok((hiding_cm, binding_cm))
+}
+
+#[test]
+fn test_halo2_resource_commit_dual() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::circuit::vp_circuit::{
+        testing::{GadgetTest, GadgetTestCircuit},
+        ValidityPredicateConfig,
+    };
+    use crate::resource::{tests::random_resource, Resource};
+    use crate::utils::poseidon_hash_n;
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        resource: Resource,
+        rcm: pallas::Base,
+    }
+
+    impl GadgetTest for MyCircuit {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let advice = config.advices[0];
+            let mut witness = |name: &'static str, value: pallas::Base| {
+                assign_free_advice(layouter.namespace(|| name), advice, Value::known(value))
+            };
+            let logic = witness("logic", self.resource.get_logic())?;
+            let label = witness("label", self.resource.get_label())?;
+            let value = witness("value", self.resource.value)?;
+            let npk = witness("npk", self.resource.get_npk())?;
+            let nonce = witness("nonce", self.resource.nonce.inner())?;
+            let psi = witness("psi", self.resource.get_psi())?;
+            let quantity = witness("quantity", pallas::Base::from(self.resource.quantity))?;
+            let is_ephemeral = witness(
+                "is_ephemeral",
+                pallas::Base::from(self.resource.is_ephemeral),
+            )?;
+            let rcm = witness("rcm", self.rcm)?;
+
+            let (hiding_cm, binding_cm) = resource_commit_dual(
+                layouter.namespace(|| "resource commitment dual"),
+                ResourceCommitChip::construct(config.resource_commit_config),
+                logic,
+                label,
+                value,
+                npk,
+                nonce,
+                psi,
+                quantity,
+                is_ephemeral,
+                rcm,
+            )?;
+
+            let expected_hiding_cm = assign_free_instance(
+                layouter.namespace(|| "public hiding cm"),
+                config.instances,
+                0,
+                advice,
+            )?;
+            let expected_binding_cm = assign_free_instance(
+                layouter.namespace(|| "public binding cm"),
+                config.instances,
+                1,
+                advice,
+            )?;
+            layouter.assign_region(
+                || "check resource commitments",
+                |mut region| {
+                    region.constrain_equal(hiding_cm.cell(), expected_hiding_cm.cell())?;
+                    region.constrain_equal(binding_cm.cell(), expected_binding_cm.cell())
+                },
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let mut resource = random_resource(&mut rng);
+    resource.is_ephemeral = false;
+    let cm = resource.commitment().inner();
+    let binding_cm = resource.binding_commitment();
+
+    // The hiding commitment is the resource commitment
+    let circuit = GadgetTestCircuit(MyCircuit {
+        resource,
+        rcm: resource.get_rcm(),
+    });
+    let prover = MockProver::run(11, &circuit, vec![vec![cm, binding_cm]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Another rcm changes the hiding commitment only
+    let other_rcm = pallas::Base::random(&mut rng);
+    let other_cm = poseidon_hash_n([
+        resource.get_logic(),
+        resource.get_label(),
+        resource.value,
+        resource.get_npk(),
+        resource.nonce.inner(),
+        resource.get_psi(),
+        pallas::Base::from(resource.quantity),
+        other_rcm,
+    ]);
+    assert_ne!(other_cm, cm);
+    let circuit = GadgetTestCircuit(MyCircuit {
+        resource,
+        rcm: other_rcm,
+    });
+    let prover = MockProver::run(11, &circuit, vec![vec![other_cm, binding_cm]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    let prover = MockProver::run(11, &circuit, vec![vec![cm, binding_cm]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_halo2_resource_commit_shared_advices() {
+    use crate::circuit::gadgets::{assign_free_advice, assign_free_instance};
+    use crate::resource::{tests::random_resource, Resource};
+    use halo2_gadgets::poseidon::{primitives::P128Pow5T3, Pow5Chip as PoseidonChip};
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, Instance, TableColumn},
+    };
+    use rand::rngs::OsRng;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        resource: Resource,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        #[allow(clippy::type_complexity)]
+        type Config = (
+            [Column<Advice>; 5],
+            Column<Instance>,
+            TableColumn,
+            ResourceCommitConfig,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [(); 5].map(|_| meta.advice_column());
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let table_idx = meta.lookup_table_column();
+            let rc_a = [(); 3].map(|_| meta.fixed_column());
+            let rc_b = [(); 3].map(|_| meta.fixed_column());
+            meta.enable_constant(rc_b[0]);
+
+            // The Poseidon chip, the range check and the resource commitment share the columns
+            let poseidon_config = PoseidonChip::configure::<P128Pow5T3>(
+                meta,
+                advices[1..4].try_into().unwrap(),
+                advices[0],
+                rc_a,
+                rc_b,
+            );
+            let lookup_config = LookupRangeCheckConfig::configure(meta, advices[4], table_idx);
+            let resource_commit_config =
+                ResourceCommitChip::configure(meta, &advices, poseidon_config, lookup_config);
+            (advices, instance, table_idx, resource_commit_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (advices, instance, table_idx, resource_commit_config) = config;
+            layouter.assign_table(
+                || "table_idx",
+                |mut table| {
+                    for index in 0..(1 << 10) {
+                        table.assign_cell(
+                            || "table_idx",
+                            table_idx,
+                            index,
+                            || Value::known(pallas::Base::from(index as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+
+            let mut witness = |name: &'static str, value: pallas::Base| {
+                assign_free_advice(layouter.namespace(|| name), advices[0], Value::known(value))
+            };
+            let logic = witness("logic", self.resource.get_logic())?;
+            let label = witness("label", self.resource.get_label())?;
+            let value = witness("value", self.resource.value)?;
+            let npk = witness("npk", self.resource.get_npk())?;
+            let nonce = witness("nonce", self.resource.nonce.inner())?;
+            let psi = witness("psi", self.resource.get_psi())?;
+            let quantity = witness("quantity", pallas::Base::from(self.resource.quantity))?;
+            let is_ephemeral = witness(
+                "is_ephemeral",
+                pallas::Base::from(self.resource.is_ephemeral),
+            )?;
+            let rcm = witness("rcm", self.resource.get_rcm())?;
+
+            let chip = ResourceCommitChip::construct(resource_commit_config);
+            chip.check_non_zero(layouter.namespace(|| "non-zero npk"), &npk)?;
+            let cm = resource_commit(
+                layouter.namespace(|| "resource commitment"),
+                chip,
+                logic,
+                label,
+                value,
+                npk,
+                nonce,
+                psi,
+                quantity,
+                is_ephemeral,
+                rcm,
+            )?;
+            let expected_cm =
+                assign_free_instance(layouter.namespace(|| "public cm"), instance, 0, advices[0])?;
+            layouter.assign_region(
+                || "check resource commitment",
+                |mut region| region.constrain_equal(cm.cell(), expected_cm.cell()),
+            )
+        }
+    }
+
+    let mut rng = OsRng;
+    let resource = random_resource(&mut rng);
+    let circuit = MyCircuit { resource };
+    let prover = MockProver::run(11, &circuit, vec![vec![resource.commitment().inner()]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let other = random_resource(&mut rng);
+    let prover = MockProver::run(11, &circuit, vec![vec![other.commitment().inner()]]).unwrap();
+    assert!(prover.verify().is_err());
+}
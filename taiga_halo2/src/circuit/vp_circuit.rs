@@ -14,18 +14,21 @@ use crate::{
                 GetIsInputResourceFlagConfig, GetOwnedResourceVariableConfig,
             },
         },
-        integrity::{check_input_resource, check_output_resource},
+        integrity::{check_input_resource, check_output_resource, ResourceCheckConstants},
         resource_commitment::{ResourceCommitChip, ResourceCommitConfig},
         vamp_ir_utils::{get_circuit_assignments, parse, VariableAssignmentError},
     },
     constant::{
         TaigaFixedBases, NUM_RESOURCE, RESOURCE_ENCRYPTION_CIPHERTEXT_NUM, SETUP_PARAMS_MAP,
-        VP_CIRCUIT_NULLIFIER_ONE_PUBLIC_INPUT_IDX, VP_CIRCUIT_NULLIFIER_TWO_PUBLIC_INPUT_IDX,
-        VP_CIRCUIT_OUTPUT_CM_ONE_PUBLIC_INPUT_IDX, VP_CIRCUIT_OUTPUT_CM_TWO_PUBLIC_INPUT_IDX,
-        VP_CIRCUIT_OWNED_RESOURCE_ID_PUBLIC_INPUT_IDX, VP_CIRCUIT_PARAMS_SIZE,
-        VP_CIRCUIT_PUBLIC_INPUT_NUM, VP_CIRCUIT_RESOURCE_ENCRYPTION_PK_X_IDX,
-        VP_CIRCUIT_RESOURCE_ENCRYPTION_PK_Y_IDX,
-        VP_CIRCUIT_RESOURCE_ENCRYPTION_PUBLIC_INPUT_BEGIN_IDX,
+        VP_CIRCUIT_CUSTOM_PUBLIC_INPUT_BEGIN_IDX, VP_CIRCUIT_FIRST_DYNAMIC_VP_CM_1,
+        VP_CIRCUIT_FIRST_DYNAMIC_VP_CM_2, VP_CIRCUIT_NULLIFIER_ONE_PUBLIC_INPUT_IDX,
+        VP_CIRCUIT_NULLIFIER_TWO_PUBLIC_INPUT_IDX, VP_CIRCUIT_OUTPUT_CM_ONE_PUBLIC_INPUT_IDX,
+        VP_CIRCUIT_OUTPUT_CM_TWO_PUBLIC_INPUT_IDX, VP_CIRCUIT_OWNED_RESOURCE_ID_PUBLIC_INPUT_IDX,
+        VP_CIRCUIT_PARAMS_SIZE, VP_CIRCUIT_PUBLIC_INPUT_NUM,
+        VP_CIRCUIT_RESOURCE_ENCRYPTION_MAC_IDX, VP_CIRCUIT_RESOURCE_ENCRYPTION_NONCE_IDX,
+        VP_CIRCUIT_RESOURCE_ENCRYPTION_PK_X_IDX, VP_CIRCUIT_RESOURCE_ENCRYPTION_PK_Y_IDX,
+        VP_CIRCUIT_RESOURCE_ENCRYPTION_PUBLIC_INPUT_BEGIN_IDX, VP_CIRCUIT_SECOND_DYNAMIC_VP_CM_1,
+        VP_CIRCUIT_SECOND_DYNAMIC_VP_CM_2,
     },
     error::TransactionError,
     proof::Proof,
@@ -45,9 +48,10 @@ use halo2_gadgets::{
 use halo2_proofs::{
     arithmetic::CurveAffine,
     circuit::{AssignedCell, Layouter, Value},
+    dev::CircuitCost,
     plonk::{
         keygen_pk, keygen_vk, Advice, Circuit, Column, ConstraintSystem, Error, Instance,
-        TableColumn, VerifyingKey,
+        ProvingKey, TableColumn, VerifyingKey,
     },
     poly::commitment::Params,
 };
@@ -222,6 +226,85 @@ impl BorshDeserialize for VPVerifyingInfo {
     }
 }
 
+/// The private and public inputs of a VP, to reproduce a failing VP outside of the transaction.
+#[derive(Debug, Clone)]
+pub struct WitnessBundle {
+    /// The serialized circuit, which holds all the private inputs
+    pub witness: Vec<u8>,
+    pub public_inputs: ValidityPredicatePublicInputs,
+}
+
+#[cfg(feature = "borsh")]
+impl BorshSerialize for WitnessBundle {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        use ff::PrimeField;
+        self.witness.serialize(writer)?;
+        for ele in self.public_inputs.inner().iter() {
+            writer.write_all(&ele.to_repr())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl BorshDeserialize for WitnessBundle {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let witness = Vec::<u8>::deserialize_reader(reader)?;
+        let public_inputs: Vec<_> = (0..VP_CIRCUIT_PUBLIC_INPUT_NUM)
+            .map(|_| read_base_field(reader))
+            .collect::<Result<_, _>>()?;
+        Ok(WitnessBundle {
+            witness,
+            public_inputs: public_inputs.into(),
+        })
+    }
+}
+
+/// The reasons a witness bundle fails to replay.
+#[cfg(feature = "borsh")]
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The witness is not a serialized VP of the replayed type.
+    Decode(std::io::Error),
+    /// The VP could not be synthesized.
+    Synthesis(Error),
+    /// The VP doesn't hold on the dumped public inputs.
+    Verify(Vec<halo2_proofs::dev::VerifyFailure>),
+}
+
+#[cfg(feature = "borsh")]
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Decode(e) => write!(f, "Error decoding the VP witness: {e}"),
+            ReplayError::Synthesis(e) => write!(f, "Error synthesizing the VP: {e}"),
+            ReplayError::Verify(failures) => {
+                write!(
+                    f,
+                    "The VP fails with {} constraint failures",
+                    failures.len()
+                )
+            }
+        }
+    }
+}
+
+// Reconstruct the VP from the bundle and run it on the dumped public inputs
+#[cfg(feature = "borsh")]
+pub fn replay<VP: ValidityPredicateCircuit + BorshDeserialize>(
+    bundle: &WitnessBundle,
+) -> Result<(), ReplayError> {
+    use halo2_proofs::dev::MockProver;
+    let vp = VP::try_from_slice(&bundle.witness).map_err(ReplayError::Decode)?;
+    let prover = MockProver::<pallas::Base>::run(
+        VP_CIRCUIT_PARAMS_SIZE,
+        &vp,
+        vec![bundle.public_inputs.to_vec()],
+    )
+    .map_err(ReplayError::Synthesis)?;
+    prover.verify().map_err(ReplayError::Verify)
+}
+
 #[cfg(feature = "serde")]
 fn serde_serialize_verifying_key<S>(
     x: &VerifyingKey<vesta::Affine>,
@@ -250,6 +333,39 @@ where
     Ok(vk)
 }
 
+/// Writes the verifying key of a compiled VP so that the VP compilation can be cached across
+/// runs.
+///
+/// The halo2 fork only serializes verifying keys, so this is not a full proving key cache:
+/// `keygen_pk_from_cached_vk` skips keygen_vk but still runs keygen_pk on load.
+pub fn serialize_vk_for_pk<W: std::io::Write>(
+    pk: &ProvingKey<vesta::Affine>,
+    writer: &mut W,
+) -> std::io::Result<()> {
+    pk.get_vk().write(writer)
+}
+
+/// Reads a VP verifying key written by `serialize_vk_for_pk` for the circuit type `C`, and
+/// generates the proving key from it.
+pub fn keygen_pk_from_cached_vk<C: Circuit<pallas::Base> + Default, R: std::io::Read>(
+    reader: &mut R,
+    params: &Params<vesta::Affine>,
+) -> std::io::Result<ProvingKey<vesta::Affine>> {
+    let vk = VerifyingKey::read::<_, C>(reader, params)?;
+    keygen_pk(params, vk, &C::default()).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Error generating ProvingKey: {:?}", e),
+        )
+    })
+}
+
+/// Derives the VP verifying key from a proving key, so that a verifier holding only a cached pk
+/// doesn't need to recompile the circuit.
+pub fn vk_from_pk(pk: &ProvingKey<vesta::Affine>) -> ValidityPredicateVerifyingKey {
+    ValidityPredicateVerifyingKey::from_vk(pk.get_vk().clone())
+}
+
 impl ValidityPredicatePublicInputs {
     pub fn inner(&self) -> &[pallas::Base; VP_CIRCUIT_PUBLIC_INPUT_NUM] {
         &self.0
@@ -397,7 +513,7 @@ impl ValidityPredicateConfig {
         let blake2s_config = Blake2sConfig::configure(meta, advices);
         let resource_commit_config = ResourceCommitChip::configure(
             meta,
-            advices[0..3].try_into().unwrap(),
+            &advices[0..3],
             poseidon_config.clone(),
             range_check,
         );
@@ -456,32 +572,45 @@ pub trait ValidityPredicateCircuit: Circuit<pallas::Base> + ValidityPredicateVer
         let resource_commit_chip =
             ResourceCommitChip::construct(config.resource_commit_config.clone());
 
+        // Assign the constants of the resource checks once for all the resources
+        let resource_check_constants = ResourceCheckConstants::assign(
+            layouter.namespace(|| "resource check constants"),
+            config.advices[0],
+        )?;
+
         let input_resources = self.get_input_resources();
         let output_resources = self.get_output_resources();
-        let mut input_resource_variables = vec![];
-        let mut output_resource_variables = vec![];
-        for i in 0..NUM_RESOURCE {
+        let mut input_resource_variables = Vec::with_capacity(NUM_RESOURCE);
+        let mut output_resource_variables = Vec::with_capacity(NUM_RESOURCE);
+        // The resource sets are arrays of NUM_RESOURCE, so there is no empty set to handle here
+        for (i, (input_resource, output_resource)) in input_resources
+            .iter()
+            .zip(output_resources.iter())
+            .enumerate()
+        {
             input_resource_variables.push(check_input_resource(
-                layouter.namespace(|| "check input resource"),
+                layouter.namespace(|| format!("check input resource {}", i)),
                 config.advices,
                 config.instances,
                 resource_commit_chip.clone(),
-                input_resources[i],
+                &resource_check_constants,
+                *input_resource,
                 i * 2,
             )?);
 
             // The old_nf may not be from above input resource
             let old_nf = assign_free_advice(
-                layouter.namespace(|| "old nf"),
+                layouter.namespace(|| format!("old nf {}", i)),
                 config.advices[0],
-                Value::known(output_resources[i].nonce.inner()),
+                Value::known(output_resource.nonce.inner()),
             )?;
             output_resource_variables.push(check_output_resource(
-                layouter.namespace(|| "check output resource"),
+                layouter.namespace(|| format!("check output resource {}", i)),
                 config.advices,
                 config.instances,
                 resource_commit_chip.clone(),
-                output_resources[i],
+                &resource_check_constants,
+                *output_resource,
                 old_nf,
                 i * 2 + 1,
             )?);
@@ -541,15 +670,111 @@ pub trait ValidityPredicateCircuit: Circuit<pallas::Base> + ValidityPredicateVer
         public_inputs.push(self.get_owned_resource_id());
         public_inputs
     }
+    // The names of the public inputs and their indices, so that generic tooling can interpret them.
+    // VP designers can rename the custom public inputs.
+    fn public_input_layout() -> Vec<(&'static str, usize)> {
+        default_public_input_layout()
+    }
     fn get_input_resources(&self) -> &[Resource; NUM_RESOURCE];
     fn get_output_resources(&self) -> &[Resource; NUM_RESOURCE];
     fn get_public_inputs(&self, rng: impl RngCore) -> ValidityPredicatePublicInputs;
+    // The prefix of the instance vector the VP constrains to its resources, in order. The rest
+    // (dynamic VP commitments, custom public inputs and the padding) is chosen by the prover in
+    // get_public_inputs, so verifiers only check the prefix of the instance against it.
+    fn expected_public_inputs(&self) -> Vec<pallas::Base> {
+        self.get_mandatory_public_inputs()
+    }
+    // The byte length of a proof of the VP, estimated from the circuit layout without proving so
+    // that operators can plan the bandwidth ahead
+    fn estimated_proof_size(&self) -> usize
+    where
+        Self: Sized,
+    {
+        CircuitCost::<vesta::Point, Self>::measure(VP_CIRCUIT_PARAMS_SIZE as usize, self)
+            .proof_size(1)
+            .into()
+    }
+    // Dump the VP together with the public inputs it was proven on, which replay runs again
+    #[cfg(feature = "borsh")]
+    fn dump_witness(&self, public_inputs: &ValidityPredicatePublicInputs) -> WitnessBundle
+    where
+        Self: BorshSerialize,
+    {
+        WitnessBundle {
+            witness: borsh::to_vec(self).unwrap(),
+            public_inputs: public_inputs.clone(),
+        }
+    }
     // The owned_resource_id is the input_resource_nf or the output_resource_cm_x
     // The owned_resource_id is the key to look up the target variables and
     // help determine whether the owned resource is the input resource or not in VP circuit.
     fn get_owned_resource_id(&self) -> pallas::Base;
 }
 
+/// The layout of the VP public inputs shared by all the VPs, with generic names for the custom
+/// public inputs.
+pub fn default_public_input_layout() -> Vec<(&'static str, usize)> {
+    assert_eq!(NUM_RESOURCE, 2);
+    let resource_ciphertext = [
+        "resource_ciphertext[0]",
+        "resource_ciphertext[1]",
+        "resource_ciphertext[2]",
+        "resource_ciphertext[3]",
+        "resource_ciphertext[4]",
+        "resource_ciphertext[5]",
+        "resource_ciphertext[6]",
+        "resource_ciphertext[7]",
+        "resource_ciphertext[8]",
+        "resource_ciphertext[9]",
+    ];
+    let mut layout = vec![
+        ("nullifier[0]", VP_CIRCUIT_NULLIFIER_ONE_PUBLIC_INPUT_IDX),
+        ("output_cm[0]", VP_CIRCUIT_OUTPUT_CM_ONE_PUBLIC_INPUT_IDX),
+        ("nullifier[1]", VP_CIRCUIT_NULLIFIER_TWO_PUBLIC_INPUT_IDX),
+        ("output_cm[1]", VP_CIRCUIT_OUTPUT_CM_TWO_PUBLIC_INPUT_IDX),
+        (
+            "owned_resource_id",
+            VP_CIRCUIT_OWNED_RESOURCE_ID_PUBLIC_INPUT_IDX,
+        ),
+        ("first_dynamic_vp_cm[0]", VP_CIRCUIT_FIRST_DYNAMIC_VP_CM_1),
+        ("first_dynamic_vp_cm[1]", VP_CIRCUIT_FIRST_DYNAMIC_VP_CM_2),
+        ("second_dynamic_vp_cm[0]", VP_CIRCUIT_SECOND_DYNAMIC_VP_CM_1),
+        ("second_dynamic_vp_cm[1]", VP_CIRCUIT_SECOND_DYNAMIC_VP_CM_2),
+        ("custom[0]", VP_CIRCUIT_CUSTOM_PUBLIC_INPUT_BEGIN_IDX),
+        ("custom[1]", VP_CIRCUIT_CUSTOM_PUBLIC_INPUT_BEGIN_IDX + 1),
+    ];
+    layout.extend(
+        resource_ciphertext
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| {
+                (
+                    name,
+                    VP_CIRCUIT_RESOURCE_ENCRYPTION_PUBLIC_INPUT_BEGIN_IDX + i,
+                )
+            }),
+    );
+    layout.extend([
+        (
+            "resource_encryption_nonce",
+            VP_CIRCUIT_RESOURCE_ENCRYPTION_NONCE_IDX,
+        ),
+        (
+            "resource_encryption_mac",
+            VP_CIRCUIT_RESOURCE_ENCRYPTION_MAC_IDX,
+        ),
+        (
+            "resource_encryption_pk_x",
+            VP_CIRCUIT_RESOURCE_ENCRYPTION_PK_X_IDX,
+        ),
+        (
+            "resource_encryption_pk_y",
+            VP_CIRCUIT_RESOURCE_ENCRYPTION_PK_Y_IDX,
+        ),
+    ]);
+    layout
+}
+
 /// BasicValidityPredicateVariables are generally constrained in ValidityPredicateCircuit::basic_constraints
 /// and will be used in ValidityPredicateCircuit::custom_constraints
 #[derive(Debug, Clone)]
@@ -941,6 +1166,558 @@ impl ValidityPredicateVerifyingInfo for VampIRValidityPredicateCircuit {
     }
 }
 
+#[cfg(test)]
+pub mod testing {
+    use super::{
+        BasicValidityPredicateVariables, InputResourceVariables, OutputResourceVariables,
+        ResourceVariables, ValidityPredicateCircuit, ValidityPredicateConfig,
+    };
+    use crate::{
+        circuit::{
+            gadgets::assign_free_advice,
+            merkle_circuit::{MerklePoseidonChip, MerklePoseidonConfig},
+        },
+        constant::{NUM_RESOURCE, SETUP_PARAMS_MAP, VP_CIRCUIT_PARAMS_SIZE},
+        proof::Proof,
+        resource::Resource,
+    };
+    use halo2_proofs::{
+        circuit::{floor_planner, Layouter, SimpleFloorPlanner, Value},
+        dev::{metadata, FailureLocation, MockProver, VerifyFailure},
+        plonk::{
+            keygen_pk, keygen_vk, Advice, Any, Assigned, Assignment, Circuit, Column,
+            ConstraintSystem, Error, Fixed, FloorPlanner, Instance, ProvingKey, Selector,
+        },
+        poly::commitment::Params,
+    };
+    use pasta_curves::{pallas, vesta};
+    use rand::rngs::OsRng;
+    use rayon::prelude::*;
+    use std::collections::BTreeMap;
+
+    /// Checks the VP is satisfied by MockProver, then compiles it and checks a proof of it verifies
+    /// against its public inputs. A new VP example gets the same coverage by calling it.
+    pub fn compile_and_verify_vp<VP: ValidityPredicateCircuit + Clone>(vp: &VP) {
+        let mut rng = OsRng;
+        let public_inputs = vp.get_public_inputs(&mut rng);
+        let prover =
+            MockProver::run(VP_CIRCUIT_PARAMS_SIZE, vp, vec![public_inputs.to_vec()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let (params, pk) = test_setup(vp);
+        let proof =
+            Proof::create(&pk, params, vp.clone(), &[public_inputs.inner()], &mut rng).unwrap();
+        proof
+            .verify(pk.get_vk(), params, &[public_inputs.inner()])
+            .unwrap();
+    }
+
+    /// The params of the VP circuit size and the proving key of the VP, the preamble of a test
+    /// proving a VP. The verifying key is `pk.get_vk()`.
+    pub fn test_setup<VP: ValidityPredicateCircuit>(
+        vp: &VP,
+    ) -> (&'static Params<vesta::Affine>, ProvingKey<vesta::Affine>) {
+        let params = SETUP_PARAMS_MAP.get(&VP_CIRCUIT_PARAMS_SIZE).unwrap();
+        let vk = keygen_vk(params, vp).expect("keygen_vk should not fail");
+        let pk = keygen_pk(params, vk, vp).expect("keygen_pk should not fail");
+        (params, pk)
+    }
+
+    /// The largest k probed by `min_k`.
+    pub const MIN_K_UPPER_BOUND: u32 = 17;
+
+    /// Returns the smallest k for which the circuit is satisfied by MockProver, probing upward.
+    /// A VP circuit passes its public inputs as the single instance column.
+    pub fn min_k<C: Circuit<pallas::Base>>(circuit: &C, instances: Vec<Vec<pallas::Base>>) -> u32 {
+        (1..=MIN_K_UPPER_BOUND)
+            .find(|k| {
+                MockProver::run(*k, circuit, instances.clone())
+                    .map(|prover| prover.verify().is_ok())
+                    .unwrap_or(false)
+            })
+            .expect("the circuit is not satisfied by any k up to MIN_K_UPPER_BOUND")
+    }
+
+    /// Runs MockProver on every circuit of the batch in parallel, with its public inputs. Returns the
+    /// index and the failures of each circuit that is not satisfied, in batch order.
+    pub fn mock_verify_batch<C: Circuit<pallas::Base> + Sync>(
+        k: u32,
+        batch: &[(C, Vec<pallas::Base>)],
+    ) -> Vec<(usize, Vec<VerifyFailure>)> {
+        batch
+            .par_iter()
+            .enumerate()
+            .filter_map(|(idx, (circuit, public_inputs))| {
+                let prover = MockProver::run(k, circuit, vec![public_inputs.clone()]).unwrap();
+                prover.verify().err().map(|failures| (idx, failures))
+            })
+            .collect()
+    }
+
+    /// Records the advice values assigned in each region, keyed by the namespaces and the name of
+    /// the region, e.g. "witness leaf/load private", along with the advice usage. The name and the
+    /// namespaced path of every region are kept in the order MockProver indexes them.
+    #[derive(Default)]
+    struct WitnessTracer {
+        instances: Vec<Vec<pallas::Base>>,
+        namespaces: Vec<String>,
+        region: Option<String>,
+        regions: Vec<(String, String)>,
+        witnesses: BTreeMap<String, Vec<pallas::Base>>,
+        usage: CircuitUsage,
+    }
+
+    /// The rows used by the advice assignments and the number of assigned advice cells.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct CircuitUsage {
+        pub rows: usize,
+        pub advice_cells: usize,
+    }
+
+    fn trace<C: Circuit<pallas::Base>>(
+        circuit: &C,
+        instances: Vec<Vec<pallas::Base>>,
+    ) -> WitnessTracer {
+        let mut cs = ConstraintSystem::default();
+        let config = C::configure(&mut cs);
+        let mut tracer = WitnessTracer {
+            instances,
+            ..Default::default()
+        };
+        C::FloorPlanner::synthesize(&mut tracer, circuit, config, cs.constants().clone()).unwrap();
+        tracer
+    }
+
+    impl Assignment<pallas::Base> for WitnessTracer {
+        fn enter_region<NR, N>(&mut self, name_fn: N)
+        where
+            NR: Into<String>,
+            N: FnOnce() -> NR,
+        {
+            let name = name_fn().into();
+            let mut path = self.namespaces.clone();
+            path.push(name.clone());
+            self.region = Some(path.join("/"));
+            self.regions.push((name, path.join("/")));
+        }
+
+        fn exit_region(&mut self) {
+            self.region = None;
+        }
+
+        fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
+        where
+            A: FnOnce() -> AR,
+            AR: Into<String>,
+        {
+            Ok(())
+        }
+
+        fn query_instance(
+            &self,
+            column: Column<Instance>,
+            row: usize,
+        ) -> Result<Value<pallas::Base>, Error> {
+            self.instances
+                .get(column.index())
+                .and_then(|column| column.get(row))
+                .map(|value| Value::known(*value))
+                .ok_or(Error::BoundsFailure)
+        }
+
+        fn assign_advice<V, VR, A, AR>(
+            &mut self,
+            _: A,
+            _: Column<Advice>,
+            row: usize,
+            to: V,
+        ) -> Result<(), Error>
+        where
+            V: FnOnce() -> Value<VR>,
+            VR: Into<Assigned<pallas::Base>>,
+            A: FnOnce() -> AR,
+            AR: Into<String>,
+        {
+            self.usage.rows = self.usage.rows.max(row + 1);
+            self.usage.advice_cells += 1;
+            if let Some(region) = self.region.clone() {
+                to().map(|value| {
+                    self.witnesses
+                        .entry(region)
+                        .or_default()
+                        .push(value.into().evaluate())
+                });
+            }
+            Ok(())
+        }
+
+        fn assign_fixed<V, VR, A, AR>(
+            &mut self,
+            _: A,
+            _: Column<Fixed>,
+            _: usize,
+            _: V,
+        ) -> Result<(), Error>
+        where
+            V: FnOnce() -> Value<VR>,
+            VR: Into<Assigned<pallas::Base>>,
+            A: FnOnce() -> AR,
+            AR: Into<String>,
+        {
+            Ok(())
+        }
+
+        fn copy(
+            &mut self,
+            _: Column<Any>,
+            _: usize,
+            _: Column<Any>,
+            _: usize,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn fill_from_row(
+            &mut self,
+            _: Column<Fixed>,
+            _: usize,
+            _: Value<Assigned<pallas::Base>>,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn push_namespace<NR, N>(&mut self, name_fn: N)
+        where
+            NR: Into<String>,
+            N: FnOnce() -> NR,
+        {
+            self.namespaces.push(name_fn().into());
+        }
+
+        fn pop_namespace(&mut self, _: Option<String>) {
+            self.namespaces.pop();
+        }
+    }
+
+    /// Runs MockProver on the circuit and returns it along with the advice values assigned in each
+    /// region, keyed by the namespaces and the name of the region.
+    pub fn mock_prove_with_witnesses<C: Circuit<pallas::Base>>(
+        k: u32,
+        circuit: &C,
+        instances: Vec<Vec<pallas::Base>>,
+    ) -> (
+        MockProver<pallas::Base>,
+        BTreeMap<String, Vec<pallas::Base>>,
+    ) {
+        let prover = MockProver::run(k, circuit, instances.clone()).unwrap();
+        let tracer = trace(circuit, instances);
+        (prover, tracer.witnesses)
+    }
+
+    /// Runs MockProver on the circuit and renders its failures with the namespaced path of the
+    /// failing regions instead of the bare region names, e.g. "check input resource 1/nk != 0/Non-zero
+    /// check". The VP resource checks are namespaced by the resource index and the field.
+    pub fn mock_verify_with_namespaces<C: Circuit<pallas::Base>>(
+        k: u32,
+        circuit: &C,
+        instances: Vec<Vec<pallas::Base>>,
+    ) -> Result<(), Vec<String>> {
+        let prover = MockProver::run(k, circuit, instances.clone()).unwrap();
+        let failures = match prover.verify() {
+            Ok(()) => return Ok(()),
+            Err(failures) => failures,
+        };
+        let regions = trace(circuit, instances).regions;
+        // MockProver reports a region by its index and name, which index the traced regions
+        let namespaced = |region: metadata::Region| {
+            regions
+                .iter()
+                .enumerate()
+                .find(|(index, (name, _))| {
+                    region == metadata::Region::from((*index, name.as_str()))
+                })
+                .map(|(index, (_, path))| metadata::Region::from((index, path.as_str())))
+                .unwrap_or(region)
+        };
+        let namespaced_location = |location: FailureLocation| match location {
+            FailureLocation::InRegion { region, offset } => FailureLocation::InRegion {
+                region: namespaced(region),
+                offset,
+            },
+            location => location,
+        };
+        Err(failures
+            .into_iter()
+            .map(|failure| match failure {
+                VerifyFailure::CellNotAssigned {
+                    gate,
+                    region,
+                    gate_offset,
+                    column,
+                    offset,
+                } => VerifyFailure::CellNotAssigned {
+                    gate,
+                    region: namespaced(region),
+                    gate_offset,
+                    column,
+                    offset,
+                },
+                VerifyFailure::ConstraintNotSatisfied {
+                    constraint,
+                    location,
+                    cell_values,
+                } => VerifyFailure::ConstraintNotSatisfied {
+                    constraint,
+                    location: namespaced_location(location),
+                    cell_values,
+                },
+                VerifyFailure::Lookup {
+                    lookup_index,
+                    location,
+                } => VerifyFailure::Lookup {
+                    lookup_index,
+                    location: namespaced_location(location),
+                },
+                VerifyFailure::Permutation { column, location } => VerifyFailure::Permutation {
+                    column,
+                    location: namespaced_location(location),
+                },
+                failure => failure,
+            })
+            .map(|failure| failure.to_string())
+            .collect())
+    }
+
+    /// Returns the advice usage of the circuit.
+    pub fn circuit_usage<C: Circuit<pallas::Base>>(
+        circuit: &C,
+        instances: Vec<Vec<pallas::Base>>,
+    ) -> CircuitUsage {
+        trace(circuit, instances).usage
+    }
+
+    fn witness_resource_variables(
+        mut layouter: impl Layouter<pallas::Base>,
+        advice: Column<Advice>,
+        resource: &Resource,
+    ) -> Result<ResourceVariables, Error> {
+        let mut witness = |name: &'static str, value: pallas::Base| {
+            assign_free_advice(layouter.namespace(|| name), advice, Value::known(value))
+        };
+        Ok(ResourceVariables {
+            logic: witness("logic", resource.get_logic())?,
+            label: witness("label", resource.get_label())?,
+            quantity: witness("quantity", pallas::Base::from(resource.quantity))?,
+            is_ephemeral: witness("is_ephemeral", pallas::Base::from(resource.is_ephemeral))?,
+            value: witness("value", resource.value)?,
+            nonce: witness("nonce", resource.nonce.inner())?,
+            npk: witness("npk", resource.get_npk())?,
+            rseed: witness("rseed", resource.rseed)?,
+        })
+    }
+
+    /// Witnesses the resource variables of a VP without the resource integrity checks: the nfs and
+    /// cms are computed natively and witnessed freely instead of being derived in circuit.
+    pub fn witness_basic_variables(
+        config: &ValidityPredicateConfig,
+        mut layouter: impl Layouter<pallas::Base>,
+        owned_resource_id: pallas::Base,
+        input_resources: &[Resource; NUM_RESOURCE],
+        output_resources: &[Resource; NUM_RESOURCE],
+    ) -> Result<BasicValidityPredicateVariables, Error> {
+        let advice = config.advices[0];
+        let mut input_resource_variables = Vec::with_capacity(NUM_RESOURCE);
+        for input_resource in input_resources.iter() {
+            input_resource_variables.push(InputResourceVariables {
+                nf: assign_free_advice(
+                    layouter.namespace(|| "witness input nf"),
+                    advice,
+                    Value::known(input_resource.get_nf().unwrap().inner()),
+                )?,
+                cm: assign_free_advice(
+                    layouter.namespace(|| "witness input cm"),
+                    advice,
+                    Value::known(input_resource.commitment().inner()),
+                )?,
+                resource_variables: witness_resource_variables(
+                    layouter.namespace(|| "witness input resource"),
+                    advice,
+                    input_resource,
+                )?,
+            });
+        }
+
+        let mut output_resource_variables = Vec::with_capacity(NUM_RESOURCE);
+        for output_resource in output_resources.iter() {
+            output_resource_variables.push(OutputResourceVariables {
+                cm: assign_free_advice(
+                    layouter.namespace(|| "witness output cm"),
+                    advice,
+                    Value::known(output_resource.commitment().inner()),
+                )?,
+                resource_variables: witness_resource_variables(
+                    layouter.namespace(|| "witness output resource"),
+                    advice,
+                    output_resource,
+                )?,
+            });
+        }
+
+        let owned_resource_id = assign_free_advice(
+            layouter.namespace(|| "owned_resource_id"),
+            advice,
+            Value::known(owned_resource_id),
+        )?;
+
+        Ok(BasicValidityPredicateVariables {
+            owned_resource_id,
+            input_resource_variables: input_resource_variables.try_into().unwrap(),
+            output_resource_variables: output_resource_variables.try_into().unwrap(),
+        })
+    }
+
+    /// Runs only the custom_constraints of the VP, on top of the variables from
+    /// `witness_basic_variables`. The mandatory public inputs are not constrained.
+    #[derive(Clone, Debug, Default)]
+    pub struct CustomConstraintsCircuit<VP>(pub VP);
+
+    impl<VP: ValidityPredicateCircuit> Circuit<pallas::Base> for CustomConstraintsCircuit<VP> {
+        type Config = ValidityPredicateConfig;
+        type FloorPlanner = floor_planner::V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            Self::Config::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            // The custom constraints may use the range check table
+            load_table_idx(&config, &mut layouter)?;
+
+            let basic_variables = witness_basic_variables(
+                &config,
+                layouter.namespace(|| "witness basic variables"),
+                self.0.get_owned_resource_id(),
+                self.0.get_input_resources(),
+                self.0.get_output_resources(),
+            )?;
+            self.0.custom_constraints(
+                config,
+                layouter.namespace(|| "custom constraints"),
+                basic_variables,
+            )
+        }
+    }
+
+    // Load the 10-bit range check table of the VP config
+    fn load_table_idx(
+        config: &ValidityPredicateConfig,
+        layouter: &mut impl Layouter<pallas::Base>,
+    ) -> Result<(), Error> {
+        layouter.assign_table(
+            || "table_idx",
+            |mut table| {
+                for index in 0..(1 << 10) {
+                    table.assign_cell(
+                        || "table_idx",
+                        config.table_idx,
+                        index,
+                        || Value::known(pallas::Base::from(index as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// The layout of a gadget test over the VP config. `Layouter` is not object safe, so the
+    /// layout is a trait method rather than a closure.
+    pub trait GadgetTest {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error>;
+    }
+
+    /// Runs a gadget test over the VP config, with the range check table loaded once.
+    #[derive(Clone, Debug, Default)]
+    pub struct GadgetTestCircuit<T>(pub T);
+
+    impl<T: GadgetTest + Default> Circuit<pallas::Base> for GadgetTestCircuit<T> {
+        type Config = ValidityPredicateConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(T::default())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            Self::Config::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            load_table_idx(&config, &mut layouter)?;
+            self.0.synthesize(config, layouter.namespace(|| "gadget"))
+        }
+    }
+
+    /// The layout of a gadget test over the VP config and a merkle config on its last five advices.
+    pub trait MerkleGadgetTest {
+        fn synthesize(
+            &self,
+            config: ValidityPredicateConfig,
+            merkle_config: MerklePoseidonConfig,
+            layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error>;
+    }
+
+    /// Runs a merkle gadget test, with the range check table loaded once.
+    #[derive(Clone, Debug, Default)]
+    pub struct MerkleGadgetTestCircuit<T>(pub T);
+
+    impl<T: MerkleGadgetTest + Default> Circuit<pallas::Base> for MerkleGadgetTestCircuit<T> {
+        type Config = (ValidityPredicateConfig, MerklePoseidonConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(T::default())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let config = ValidityPredicateConfig::configure(meta);
+            let merkle_config = MerklePoseidonChip::configure(
+                meta,
+                config.advices[5..10].try_into().unwrap(),
+                config.poseidon_config.clone(),
+            );
+            (config, merkle_config)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (config, merkle_config) = config;
+            load_table_idx(&config, &mut layouter)?;
+            self.0
+                .synthesize(config, merkle_config, layouter.namespace(|| "gadget"))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::circuit::vp_circuit::{
@@ -19,12 +19,15 @@ use halo2_proofs::plonk::{keygen_pk, keygen_vk, ProvingKey};
 use halo2_proofs::{
     circuit::{floor_planner, Layouter},
     plonk::{Circuit, ConstraintSystem, Error},
+    poly::commitment::Params,
 };
 use lazy_static::lazy_static;
 use pasta_curves::{pallas, vesta};
 use rand::{rngs::OsRng, RngCore};
 #[cfg(feature = "nif")]
 use rustler::{Decoder, Encoder, Env, NifResult, NifStruct, Term};
+use std::time::{Duration, Instant};
+use zeroize::Zeroize;
 
 #[cfg(feature = "examples")]
 pub mod cascade_intent;
@@ -65,6 +68,13 @@ pub struct TrivialValidityPredicateCircuit {
     pub output_resources: [Resource; NUM_RESOURCE],
 }
 
+impl Zeroize for TrivialValidityPredicateCircuit {
+    fn zeroize(&mut self) {
+        self.input_resources.iter_mut().for_each(Zeroize::zeroize);
+        self.output_resources.iter_mut().for_each(Zeroize::zeroize);
+    }
+}
+
 // I only exist to allow trivial derivation of the nifstruct
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "nif", derive(NifStruct))]
@@ -88,6 +98,20 @@ impl TrivialValidityPredicateCircuit {
         }
     }
 
+    // Compile the circuit and create a proof, returning the elapsed time for performance tracking.
+    pub fn prove_and_time(
+        &self,
+        params: &Params<vesta::Affine>,
+        public_inputs: &ValidityPredicatePublicInputs,
+    ) -> (Proof, Duration) {
+        let start = Instant::now();
+        let vk = keygen_vk(params, self).expect("keygen_vk should not fail");
+        let pk = keygen_pk(params, vk, self).expect("keygen_pk should not fail");
+        let proof =
+            Proof::create(&pk, params, self.clone(), &[public_inputs.inner()], OsRng).unwrap();
+        (proof, start.elapsed())
+    }
+
     // Only for test
     #[cfg(feature = "borsh")]
     pub fn to_bytecode(&self) -> ValidityPredicateByteCode {
@@ -274,4 +298,304 @@ pub mod tests {
         .unwrap();
         assert_eq!(prover.verify(), Ok(()));
     }
+
+    #[test]
+    fn test_halo2_trivial_vp_failure_names_resource() {
+        use crate::circuit::vp_circuit::{
+            testing::mock_verify_with_namespaces, ValidityPredicateCircuit,
+        };
+        use crate::constant::VP_CIRCUIT_PARAMS_SIZE;
+        use crate::nullifier::NullifierKeyContainer;
+        use rand::rngs::OsRng;
+
+        // Break the nk of the second input resource
+        let mut rng = OsRng;
+        let mut circuit = random_trivial_vp_circuit(&mut rng);
+        circuit.input_resources[1].nk_container =
+            NullifierKeyContainer::from_key(pallas::Base::zero());
+        let public_inputs = circuit.get_public_inputs(&mut rng);
+
+        let failures = mock_verify_with_namespaces(
+            VP_CIRCUIT_PARAMS_SIZE,
+            &circuit,
+            vec![public_inputs.to_vec()],
+        )
+        .unwrap_err();
+        assert!(!failures.is_empty());
+        assert!(failures
+            .iter()
+            .all(|failure| failure.contains("check input resource 1/nk != 0")));
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_trivial_vp_dump_and_replay() {
+        use crate::circuit::vp_circuit::{
+            replay, ReplayError, ValidityPredicateCircuit, WitnessBundle,
+        };
+        use crate::constant::VP_CIRCUIT_PARAMS_SIZE;
+        use crate::nullifier::NullifierKeyContainer;
+        use halo2_proofs::dev::MockProver;
+        use rand::rngs::OsRng;
+
+        let mut rng = OsRng;
+        let valid_circuit = random_trivial_vp_circuit(&mut rng);
+        let mut broken_circuit = random_trivial_vp_circuit(&mut rng);
+        broken_circuit.input_resources[1].nk_container =
+            NullifierKeyContainer::from_key(pallas::Base::zero());
+
+        for circuit in [valid_circuit, broken_circuit] {
+            let public_inputs = circuit.get_public_inputs(&mut rng);
+            let expected = MockProver::<pallas::Base>::run(
+                VP_CIRCUIT_PARAMS_SIZE,
+                &circuit,
+                vec![public_inputs.to_vec()],
+            )
+            .unwrap()
+            .verify();
+
+            // The bundle goes through its serialized form, as it would out of production
+            let bytes = borsh::to_vec(&circuit.dump_witness(&public_inputs)).unwrap();
+            let bundle: WitnessBundle = borsh::from_slice(&bytes).unwrap();
+            let replayed = match replay::<TrivialValidityPredicateCircuit>(&bundle) {
+                Ok(()) => Ok(()),
+                Err(ReplayError::Verify(failures)) => Err(failures),
+                Err(e) => panic!("{e}"),
+            };
+            assert_eq!(replayed, expected);
+        }
+
+        // The witness isn't a trivial VP
+        let bundle = WitnessBundle {
+            witness: vec![0u8; 3],
+            public_inputs: random_trivial_vp_circuit(&mut rng).get_public_inputs(&mut rng),
+        };
+        assert!(matches!(
+            replay::<TrivialValidityPredicateCircuit>(&bundle),
+            Err(ReplayError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn test_trivial_vp_compile_and_verify() {
+        use crate::circuit::vp_circuit::testing::compile_and_verify_vp;
+        use rand::rngs::OsRng;
+
+        compile_and_verify_vp(&random_trivial_vp_circuit(OsRng));
+    }
+
+    #[test]
+    fn test_trivial_vp_test_setup() {
+        use crate::circuit::vp_circuit::{testing::test_setup, ValidityPredicateCircuit};
+        use crate::proof::Proof;
+        use rand::rngs::OsRng;
+
+        let mut rng = OsRng;
+        let circuit = random_trivial_vp_circuit(&mut rng);
+        let public_inputs = circuit.get_public_inputs(&mut rng);
+        let (params, pk) = test_setup(&circuit);
+        let proof =
+            Proof::create(&pk, params, circuit, &[public_inputs.inner()], &mut rng).unwrap();
+        proof
+            .verify(pk.get_vk(), params, &[public_inputs.inner()])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_trivial_vp_estimated_proof_size() {
+        use crate::circuit::vp_circuit::{testing::test_setup, ValidityPredicateCircuit};
+        use crate::proof::Proof;
+        use rand::rngs::OsRng;
+
+        let mut rng = OsRng;
+        let circuit = random_trivial_vp_circuit(&mut rng);
+        let estimate = circuit.estimated_proof_size();
+        let public_inputs = circuit.get_public_inputs(&mut rng);
+        let (params, pk) = test_setup(&circuit);
+        let proof =
+            Proof::create(&pk, params, circuit, &[public_inputs.inner()], &mut rng).unwrap();
+
+        // Within 5% of the serialized proof
+        let actual = proof.inner().len();
+        assert!(estimate.abs_diff(actual) * 20 <= actual);
+    }
+
+    #[test]
+    fn test_trivial_vp_prove_and_time() {
+        use super::TRIVIAL_VP_VK;
+        use crate::circuit::vp_circuit::ValidityPredicateCircuit;
+        use crate::constant::{SETUP_PARAMS_MAP, VP_CIRCUIT_PARAMS_SIZE};
+        use rand::rngs::OsRng;
+        use std::time::Duration;
+
+        let mut rng = OsRng;
+        let circuit = random_trivial_vp_circuit(&mut rng);
+        let public_inputs = circuit.get_public_inputs(&mut rng);
+        let params = SETUP_PARAMS_MAP.get(&VP_CIRCUIT_PARAMS_SIZE).unwrap();
+
+        let (proof, elapsed) = circuit.prove_and_time(params, &public_inputs);
+        proof
+            .verify(
+                &TRIVIAL_VP_VK.get_vk().unwrap(),
+                params,
+                &[public_inputs.inner()],
+            )
+            .unwrap();
+        assert!(elapsed > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_trivial_vp_expected_public_inputs() {
+        use super::{TRIVIAL_VP_PK, TRIVIAL_VP_VK};
+        use crate::circuit::vp_circuit::ValidityPredicateCircuit;
+        use crate::constant::{SETUP_PARAMS_MAP, VP_CIRCUIT_PARAMS_SIZE};
+        use crate::proof::Proof;
+        use rand::rngs::OsRng;
+
+        let mut rng = OsRng;
+        let circuit = random_trivial_vp_circuit(&mut rng);
+        let params = SETUP_PARAMS_MAP.get(&VP_CIRCUIT_PARAMS_SIZE).unwrap();
+        let expected = circuit.expected_public_inputs();
+        // The prover picks its own padding
+        let public_inputs = circuit.get_public_inputs(&mut rng);
+        assert_eq!(
+            &public_inputs.inner()[..expected.len()],
+            expected.as_slice()
+        );
+        let proof = Proof::create(
+            &TRIVIAL_VP_PK,
+            params,
+            circuit,
+            &[public_inputs.inner()],
+            &mut rng,
+        )
+        .unwrap();
+        let vk = TRIVIAL_VP_VK.get_vk().unwrap();
+        assert!(proof.verify(&vk, params, &[public_inputs.inner()]).is_ok());
+
+        // The nullifier and the output cm are swapped
+        let mut permuted = public_inputs.to_vec();
+        permuted.swap(0, 1);
+        assert_ne!(&permuted[..expected.len()], expected.as_slice());
+        assert!(proof.verify(&vk, params, &[permuted.as_slice()]).is_err());
+    }
+
+    #[test]
+    fn test_trivial_vp_pk_from_cached_vk() {
+        use super::TRIVIAL_VP_VK;
+        use crate::circuit::vp_circuit::{
+            keygen_pk_from_cached_vk, serialize_vk_for_pk, ValidityPredicateCircuit,
+        };
+        use crate::constant::{SETUP_PARAMS_MAP, VP_CIRCUIT_PARAMS_SIZE};
+        use crate::proof::Proof;
+        use halo2_proofs::plonk::{keygen_pk, keygen_vk};
+        use rand::rngs::OsRng;
+
+        let mut rng = OsRng;
+        let params = SETUP_PARAMS_MAP.get(&VP_CIRCUIT_PARAMS_SIZE).unwrap();
+
+        // Compile once and cache the vk of the pk
+        let empty_circuit = TrivialValidityPredicateCircuit::default();
+        let vk = keygen_vk(params, &empty_circuit).unwrap();
+        let pk = keygen_pk(params, vk, &empty_circuit).unwrap();
+        let mut bytes = vec![];
+        serialize_vk_for_pk(&pk, &mut bytes).unwrap();
+
+        // Regenerate the pk from the cached vk and prove with it
+        let pk = keygen_pk_from_cached_vk::<TrivialValidityPredicateCircuit, _>(
+            &mut bytes.as_slice(),
+            params,
+        )
+        .unwrap();
+        let circuit = random_trivial_vp_circuit(&mut rng);
+        let public_inputs = circuit.get_public_inputs(&mut rng);
+        let proof =
+            Proof::create(&pk, params, circuit, &[public_inputs.inner()], &mut rng).unwrap();
+        proof
+            .verify(
+                &TRIVIAL_VP_VK.get_vk().unwrap(),
+                params,
+                &[public_inputs.inner()],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_trivial_vp_vk_from_pk() {
+        use super::TRIVIAL_VP_PK;
+        use crate::circuit::vp_circuit::{vk_from_pk, ValidityPredicateCircuit};
+        use crate::constant::{SETUP_PARAMS_MAP, VP_CIRCUIT_PARAMS_SIZE};
+        use crate::proof::Proof;
+        use rand::rngs::OsRng;
+
+        let mut rng = OsRng;
+        let params = SETUP_PARAMS_MAP.get(&VP_CIRCUIT_PARAMS_SIZE).unwrap();
+        let vk = vk_from_pk(&TRIVIAL_VP_PK);
+
+        let circuit = random_trivial_vp_circuit(&mut rng);
+        let public_inputs = circuit.get_public_inputs(&mut rng);
+        let proof = Proof::create(
+            &TRIVIAL_VP_PK,
+            params,
+            circuit,
+            &[public_inputs.inner()],
+            &mut rng,
+        )
+        .unwrap();
+        proof
+            .verify(&vk.get_vk().unwrap(), params, &[public_inputs.inner()])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_trivial_vp_zeroize_after_proving() {
+        use super::{TRIVIAL_VP_PK, TRIVIAL_VP_VK};
+        use crate::circuit::vp_circuit::ValidityPredicateCircuit;
+        use crate::constant::{SETUP_PARAMS_MAP, VP_CIRCUIT_PARAMS_SIZE};
+        use crate::nullifier::NullifierKeyContainer;
+        use crate::proof::Proof;
+        use rand::rngs::OsRng;
+
+        let mut rng = OsRng;
+        let sentinel = pallas::Base::from(0xdeadbeefu64);
+        let mut circuit = random_trivial_vp_circuit(&mut rng);
+        circuit.input_resources[0].rseed = sentinel;
+        circuit.input_resources[0].nk_container = NullifierKeyContainer::from_key(sentinel);
+        let public_inputs = circuit.get_public_inputs(&mut rng);
+        let params = SETUP_PARAMS_MAP.get(&VP_CIRCUIT_PARAMS_SIZE).unwrap();
+        let copy = circuit.clone();
+
+        let proof = Proof::create_and_zeroize(
+            &TRIVIAL_VP_PK,
+            params,
+            &mut circuit,
+            &[public_inputs.inner()],
+            &mut rng,
+        )
+        .unwrap();
+        proof
+            .verify(
+                &TRIVIAL_VP_VK.get_vk().unwrap(),
+                params,
+                &[public_inputs.inner()],
+            )
+            .unwrap();
+
+        // The secret witnesses are cleared once proving returns
+        for resource in circuit
+            .input_resources
+            .iter()
+            .chain(circuit.output_resources.iter())
+        {
+            assert_eq!(resource.rseed, pallas::Base::zero());
+            assert_eq!(resource.value, pallas::Base::zero());
+            assert_eq!(resource.quantity, 0);
+            assert_eq!(
+                resource.nk_container,
+                NullifierKeyContainer::from_key(pallas::Base::zero())
+            );
+        }
+        // Only the caller's circuit is cleared, not the copies made before proving
+        assert_eq!(copy.input_resources[0].rseed, sentinel);
+    }
 }
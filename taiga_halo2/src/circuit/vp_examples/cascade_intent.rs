@@ -36,6 +36,7 @@ use lazy_static::lazy_static;
 use pasta_curves::{group::ff::PrimeField, pallas};
 use rand::rngs::OsRng;
 use rand::RngCore;
+use zeroize::Zeroize;
 
 lazy_static! {
     pub static ref CASCADE_INTENT_VK: ValidityPredicateVerifyingKey =
@@ -53,6 +54,13 @@ pub struct CascadeIntentValidityPredicateCircuit {
     pub cascade_resource_cm: pallas::Base,
 }
 
+impl Zeroize for CascadeIntentValidityPredicateCircuit {
+    fn zeroize(&mut self) {
+        self.input_resources.iter_mut().for_each(Zeroize::zeroize);
+        self.output_resources.iter_mut().for_each(Zeroize::zeroize);
+    }
+}
+
 impl CascadeIntentValidityPredicateCircuit {
     // We can encode at most three resources to label if needed.
     pub fn encode_label(cascade_resource_cm: pallas::Base) -> pallas::Base {
@@ -263,3 +271,51 @@ fn test_halo2_cascade_intent_vp_circuit() {
     .unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
+
+#[test]
+fn test_halo2_cascade_intent_vp_custom_constraints() {
+    use crate::circuit::vp_circuit::testing::CustomConstraintsCircuit;
+    use crate::resource::tests::random_resource;
+    use halo2_proofs::dev::MockProver;
+    use rand::rngs::OsRng;
+
+    // The intent resource is not created with the compiled intent vk, which is only checked in
+    // the resource integrity.
+    let mut rng = OsRng;
+    let cascade_input_resource = random_resource(&mut rng);
+    let cascade_resource_cm = cascade_input_resource.commitment().inner();
+    let mut intent_resource = random_resource(&mut rng);
+    intent_resource.kind.label =
+        CascadeIntentValidityPredicateCircuit::encode_label(cascade_resource_cm);
+    let input_resources = [intent_resource, cascade_input_resource];
+    let output_resources = [(); NUM_RESOURCE].map(|_| random_resource(&mut rng));
+    let circuit = CascadeIntentValidityPredicateCircuit {
+        owned_resource_id: input_resources[0].get_nf().unwrap().inner(),
+        input_resources,
+        output_resources,
+        cascade_resource_cm,
+    };
+
+    let public_inputs = circuit.get_public_inputs(&mut rng);
+    let prover = MockProver::<pallas::Base>::run(
+        11,
+        &CustomConstraintsCircuit(circuit.clone()),
+        vec![public_inputs.to_vec()],
+    )
+    .unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The cascade resource is not spent along with the intent resource
+    let circuit = CascadeIntentValidityPredicateCircuit {
+        input_resources: [intent_resource, random_resource(&mut rng)],
+        ..circuit
+    };
+    let public_inputs = circuit.get_public_inputs(&mut rng);
+    let prover = MockProver::<pallas::Base>::run(
+        11,
+        &CustomConstraintsCircuit(circuit),
+        vec![public_inputs.to_vec()],
+    )
+    .unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -6,14 +6,16 @@ use crate::{
             assign_free_advice,
         },
         vp_circuit::{
-            BasicValidityPredicateVariables, VPVerifyingInfo, ValidityPredicateCircuit,
-            ValidityPredicateConfig, ValidityPredicatePublicInputs, ValidityPredicateVerifyingInfo,
+            default_public_input_layout, BasicValidityPredicateVariables, VPVerifyingInfo,
+            ValidityPredicateCircuit, ValidityPredicateConfig, ValidityPredicatePublicInputs,
+            ValidityPredicateVerifyingInfo,
         },
     },
     constant::{NUM_RESOURCE, SETUP_PARAMS_MAP, VP_CIRCUIT_CUSTOM_PUBLIC_INPUT_BEGIN_IDX},
     error::TransactionError,
     proof::Proof,
     resource::{RandomSeed, Resource},
+    utils::zeroize_base,
     vp_commitment::ValidityPredicateCommitment,
     vp_vk::ValidityPredicateVerifyingKey,
 };
@@ -24,6 +26,7 @@ use halo2_proofs::{
 use pasta_curves::pallas;
 use rand::rngs::OsRng;
 use rand::RngCore;
+use zeroize::Zeroize;
 
 // FieldAdditionValidityPredicateCircuit with a trivial constraint a + b = c.
 #[derive(Clone, Debug, Default)]
@@ -35,6 +38,15 @@ struct FieldAdditionValidityPredicateCircuit {
     b: pallas::Base,
 }
 
+impl Zeroize for FieldAdditionValidityPredicateCircuit {
+    fn zeroize(&mut self) {
+        self.input_resources.iter_mut().for_each(Zeroize::zeroize);
+        self.output_resources.iter_mut().for_each(Zeroize::zeroize);
+        zeroize_base(&mut self.a);
+        zeroize_base(&mut self.b);
+    }
+}
+
 impl ValidityPredicateCircuit for FieldAdditionValidityPredicateCircuit {
     // Add custom constraints
     // Resource: the trivial vp doesn't constrain on input_resource_variables and output_resource_variables
@@ -77,6 +89,12 @@ impl ValidityPredicateCircuit for FieldAdditionValidityPredicateCircuit {
         Ok(())
     }
 
+    fn public_input_layout() -> Vec<(&'static str, usize)> {
+        let mut layout = default_public_input_layout();
+        layout[VP_CIRCUIT_CUSTOM_PUBLIC_INPUT_BEGIN_IDX].0 = "c";
+        layout
+    }
+
     fn get_input_resources(&self) -> &[Resource; NUM_RESOURCE] {
         &self.input_resources
     }
@@ -141,3 +159,123 @@ fn test_halo2_addition_vp_circuit() {
     .unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
+
+#[test]
+fn test_addition_vp_compile_and_verify() {
+    use crate::circuit::vp_circuit::testing::compile_and_verify_vp;
+    use crate::resource::tests::random_resource;
+    use halo2_proofs::arithmetic::Field;
+    use rand::rngs::OsRng;
+
+    let mut rng = OsRng;
+    let circuit = FieldAdditionValidityPredicateCircuit {
+        owned_resource_id: pallas::Base::random(&mut rng),
+        input_resources: [(); NUM_RESOURCE].map(|_| random_resource(&mut rng)),
+        output_resources: [(); NUM_RESOURCE].map(|_| random_resource(&mut rng)),
+        a: pallas::Base::random(&mut rng),
+        b: pallas::Base::random(&mut rng),
+    };
+    compile_and_verify_vp(&circuit);
+}
+
+#[test]
+fn test_halo2_addition_vp_mock_verify_batch() {
+    use crate::circuit::vp_circuit::testing::mock_verify_batch;
+    use crate::constant::VP_CIRCUIT_PARAMS_SIZE;
+    use crate::resource::tests::random_resource;
+    use halo2_proofs::arithmetic::Field;
+    use rand::rngs::OsRng;
+
+    let mut rng = OsRng;
+    let batch: Vec<_> = (0..3)
+        .map(|i| {
+            let circuit = FieldAdditionValidityPredicateCircuit {
+                owned_resource_id: pallas::Base::random(&mut rng),
+                input_resources: [(); NUM_RESOURCE].map(|_| random_resource(&mut rng)),
+                output_resources: [(); NUM_RESOURCE].map(|_| random_resource(&mut rng)),
+                a: pallas::Base::random(&mut rng),
+                b: pallas::Base::random(&mut rng),
+            };
+            let mut public_inputs = circuit.get_public_inputs(&mut rng).to_vec();
+            // The second variant publishes a wrong sum
+            if i == 1 {
+                public_inputs[VP_CIRCUIT_CUSTOM_PUBLIC_INPUT_BEGIN_IDX] += pallas::Base::one();
+            }
+            (circuit, public_inputs)
+        })
+        .collect();
+
+    let failures = mock_verify_batch(VP_CIRCUIT_PARAMS_SIZE, &batch);
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, 1);
+    assert!(!failures[0].1.is_empty());
+}
+
+#[test]
+fn test_addition_vp_public_input_layout() {
+    use crate::constant::VP_CIRCUIT_PUBLIC_INPUT_NUM;
+    use crate::resource::tests::random_resource;
+    use halo2_proofs::arithmetic::Field;
+    use rand::rngs::OsRng;
+
+    let mut rng = OsRng;
+    let input_resources = [(); NUM_RESOURCE].map(|_| random_resource(&mut rng));
+    let output_resources = [(); NUM_RESOURCE].map(|_| random_resource(&mut rng));
+    let circuit = FieldAdditionValidityPredicateCircuit {
+        owned_resource_id: input_resources[0].get_nf().unwrap().inner(),
+        input_resources,
+        output_resources,
+        a: pallas::Base::random(&mut rng),
+        b: pallas::Base::random(&mut rng),
+    };
+    let public_inputs = circuit.get_public_inputs(&mut rng);
+
+    // The layout covers every public input exactly once
+    let layout = FieldAdditionValidityPredicateCircuit::public_input_layout();
+    let mut indices: Vec<usize> = layout.iter().map(|(_, idx)| *idx).collect();
+    indices.sort();
+    assert_eq!(
+        indices,
+        (0..VP_CIRCUIT_PUBLIC_INPUT_NUM).collect::<Vec<_>>()
+    );
+
+    let get = |name: &str| {
+        let idx = layout.iter().find(|(n, _)| *n == name).unwrap().1;
+        public_inputs.get_from_index(idx)
+    };
+    assert_eq!(
+        get("nullifier[0]"),
+        input_resources[0].get_nf().unwrap().inner()
+    );
+    assert_eq!(
+        get("output_cm[1]"),
+        output_resources[1].commitment().inner()
+    );
+    assert_eq!(get("owned_resource_id"), circuit.owned_resource_id);
+    assert_eq!(get("c"), circuit.a + circuit.b);
+}
+
+#[test]
+fn test_addition_vp_vk_fingerprint() {
+    use crate::circuit::vp_examples::tests::random_trivial_vp_circuit;
+    use crate::constant::VP_CIRCUIT_PARAMS_SIZE;
+    use crate::vp_vk::vk_fingerprint;
+
+    let params = SETUP_PARAMS_MAP.get(&VP_CIRCUIT_PARAMS_SIZE).unwrap();
+    let fingerprint = |vp: &FieldAdditionValidityPredicateCircuit| {
+        vk_fingerprint(&keygen_vk(params, vp).unwrap())
+    };
+
+    // Compiling the VP twice, even on other witnesses, gives the same vk
+    let first = fingerprint(&FieldAdditionValidityPredicateCircuit::default());
+    let second = fingerprint(&FieldAdditionValidityPredicateCircuit {
+        a: pallas::Base::one(),
+        b: pallas::Base::one(),
+        ..Default::default()
+    });
+    assert_eq!(first, second);
+
+    // The trivial VP is the VP without the custom constraint
+    let trivial_vk = keygen_vk(params, &random_trivial_vp_circuit(OsRng)).unwrap();
+    assert_ne!(vk_fingerprint(&trivial_vk), first);
+}
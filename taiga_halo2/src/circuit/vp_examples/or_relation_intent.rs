@@ -23,7 +23,7 @@ use crate::{
     proof::Proof,
     resource::{RandomSeed, Resource},
     utils::poseidon_hash_n,
-    utils::read_base_field,
+    utils::{read_base_field, zeroize_base},
     vp_commitment::ValidityPredicateCommitment,
     vp_vk::ValidityPredicateVerifyingKey,
 };
@@ -37,6 +37,7 @@ use lazy_static::lazy_static;
 use pasta_curves::{group::ff::PrimeField, pallas};
 use rand::rngs::OsRng;
 use rand::RngCore;
+use zeroize::Zeroize;
 
 lazy_static! {
     pub static ref OR_RELATION_INTENT_VK: ValidityPredicateVerifyingKey =
@@ -57,6 +58,15 @@ pub struct OrRelationIntentValidityPredicateCircuit {
     pub receiver_value: pallas::Base,
 }
 
+impl Zeroize for OrRelationIntentValidityPredicateCircuit {
+    fn zeroize(&mut self) {
+        self.input_resources.iter_mut().for_each(Zeroize::zeroize);
+        self.output_resources.iter_mut().for_each(Zeroize::zeroize);
+        zeroize_base(&mut self.receiver_npk);
+        zeroize_base(&mut self.receiver_value);
+    }
+}
+
 impl OrRelationIntentValidityPredicateCircuit {
     pub fn encode_label(
         token_1: &Token,
@@ -34,6 +34,7 @@ use lazy_static::lazy_static;
 use pasta_curves::{group::ff::PrimeField, pallas};
 use rand::rngs::OsRng;
 use rand::RngCore;
+use zeroize::Zeroize;
 
 pub mod swap;
 pub use swap::Swap;
@@ -57,6 +58,14 @@ pub struct PartialFulfillmentIntentValidityPredicateCircuit {
     pub swap: Swap,
 }
 
+impl Zeroize for PartialFulfillmentIntentValidityPredicateCircuit {
+    fn zeroize(&mut self) {
+        self.input_resources.iter_mut().for_each(Zeroize::zeroize);
+        self.output_resources.iter_mut().for_each(Zeroize::zeroize);
+        self.swap.sell.resource.zeroize();
+    }
+}
+
 impl PartialFulfillmentIntentValidityPredicateCircuit {
     pub fn to_bytecode(&self) -> ValidityPredicateByteCode {
         ValidityPredicateByteCode::new(
@@ -18,7 +18,7 @@ use crate::{
     proof::Proof,
     resource::{RandomSeed, Resource},
     resource_encryption::{ResourceCiphertext, ResourcePlaintext, SecretKey},
-    utils::{mod_r_p, read_base_field, read_point},
+    utils::{mod_r_p, read_base_field, read_point, zeroize_base},
     vp_commitment::ValidityPredicateCommitment,
     vp_vk::ValidityPredicateVerifyingKey,
 };
@@ -34,6 +34,7 @@ use lazy_static::lazy_static;
 use pasta_curves::pallas;
 use rand::rngs::OsRng;
 use rand::RngCore;
+use zeroize::Zeroize;
 
 const CIPHER_LEN: usize = 9;
 
@@ -56,6 +57,15 @@ pub struct ReceiverValidityPredicateCircuit {
     pub auth_vp_vk: pallas::Base,
 }
 
+impl Zeroize for ReceiverValidityPredicateCircuit {
+    fn zeroize(&mut self) {
+        self.input_resources.iter_mut().for_each(Zeroize::zeroize);
+        self.output_resources.iter_mut().for_each(Zeroize::zeroize);
+        zeroize_base(&mut self.encrypt_nonce);
+        zeroize_base(&mut self.sk);
+    }
+}
+
 impl ReceiverValidityPredicateCircuit {
     pub fn to_bytecode(&self) -> ValidityPredicateByteCode {
         ValidityPredicateByteCode::new(ValidityPredicateRepresentation::Receiver, self.to_bytes())
@@ -348,6 +358,7 @@ impl BorshDeserialize for ReceiverValidityPredicateCircuit {
 
 #[test]
 fn test_halo2_receiver_vp_circuit() {
+    use crate::circuit::vp_circuit::testing::test_setup;
     use crate::constant::VP_CIRCUIT_PARAMS_SIZE;
     use crate::{resource::tests::random_resource, utils::poseidon_hash_n};
     use ff::{Field, PrimeField};
@@ -417,4 +428,22 @@ fn test_halo2_receiver_vp_circuit() {
         pallas::Base::from(circuit.output_resources[0].is_ephemeral)
     );
     assert_eq!(de_cipher[7], circuit.output_resources[0].rseed);
+
+    // Proving clears the encryption secrets along with the resources
+    let (params, pk) = test_setup(&circuit);
+    let mut circuit = circuit;
+    let proof = Proof::create_and_zeroize(
+        &pk,
+        params,
+        &mut circuit,
+        &[public_inputs.inner()],
+        &mut rng,
+    )
+    .unwrap();
+    proof
+        .verify(pk.get_vk(), params, &[public_inputs.inner()])
+        .unwrap();
+    assert_eq!(circuit.sk, pallas::Base::zero());
+    assert_eq!(circuit.encrypt_nonce, pallas::Base::zero());
+    assert_eq!(circuit.output_resources[0].rseed, pallas::Base::zero());
 }
@@ -2,7 +2,9 @@ use crate::{
     circuit::{
         blake2s::publicize_default_dynamic_vp_commitments,
         gadgets::{
-            assign_free_advice, poseidon_hash::poseidon_hash_gadget,
+            assign_free_advice,
+            poseidon_hash::poseidon_hash_gadget,
+            schnorr_verify::{schnorr_verify_array_gadget, SchnorrSignature},
             target_resource_variable::get_owned_resource_variable,
         },
         vp_bytecode::{ValidityPredicateByteCode, ValidityPredicateRepresentation},
@@ -11,29 +13,28 @@ use crate::{
             ValidityPredicateConfig, ValidityPredicatePublicInputs, ValidityPredicateVerifyingInfo,
         },
     },
-    constant::{TaigaFixedBasesFull, NUM_RESOURCE, SETUP_PARAMS_MAP},
+    constant::{NUM_RESOURCE, SETUP_PARAMS_MAP},
     error::TransactionError,
     proof::Proof,
     resource::{RandomSeed, Resource},
-    utils::{mod_r_p, poseidon_hash_n, read_base_field, read_point, read_scalar_field},
+    utils::read_base_field,
     vp_commitment::ValidityPredicateCommitment,
     vp_vk::ValidityPredicateVerifyingKey,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
-use halo2_gadgets::ecc::{chip::EccChip, FixedPoint, NonIdentityPoint, ScalarFixed, ScalarVar};
+use halo2_gadgets::ecc::{chip::EccChip, NonIdentityPoint};
 use halo2_proofs::{
-    arithmetic::Field,
     circuit::{floor_planner, Layouter, Value},
     plonk::{keygen_pk, keygen_vk, Circuit, ConstraintSystem, Error},
 };
 use lazy_static::lazy_static;
 use pasta_curves::{
-    arithmetic::CurveAffine,
-    group::{ff::PrimeField, Curve, Group, GroupEncoding},
+    group::{ff::PrimeField, Curve},
     pallas,
 };
 use rand::rngs::OsRng;
 use rand::RngCore;
+use zeroize::Zeroize;
 
 // The message contains the input resource nullifiers and output resource commitments
 const MESSAGE_LEN: usize = NUM_RESOURCE * 2;
@@ -44,53 +45,6 @@ lazy_static! {
     pub static ref COMPRESSED_TOKEN_AUTH_VK: pallas::Base = TOKEN_AUTH_VK.get_compressed();
 }
 
-#[derive(Clone, Debug)]
-pub struct SchnorrSignature {
-    // public key
-    pk: pallas::Point,
-    // signature (r,s)
-    r: pallas::Point,
-    s: pallas::Scalar,
-}
-
-impl Default for SchnorrSignature {
-    fn default() -> Self {
-        Self {
-            pk: pallas::Point::generator(),
-            r: pallas::Point::generator(),
-            s: pallas::Scalar::one(),
-        }
-    }
-}
-
-impl SchnorrSignature {
-    pub fn sign<R: RngCore>(mut rng: R, sk: pallas::Scalar, message: Vec<pallas::Base>) -> Self {
-        // TDOD: figure out whether the generator is applicable.
-        let generator = pallas::Point::generator();
-        let pk = generator * sk;
-        let pk_coord = pk.to_affine().coordinates().unwrap();
-        // Generate a random number: z
-        let z = pallas::Scalar::random(&mut rng);
-        // Compute: R = z*G
-        let r = generator * z;
-        let r_coord = r.to_affine().coordinates().unwrap();
-        // Compute: s = z + Hash(r||P||m)*sk
-        assert_eq!(message.len(), MESSAGE_LEN);
-        let h = mod_r_p(poseidon_hash_n::<POSEIDON_HASH_LEN>([
-            *r_coord.x(),
-            *r_coord.y(),
-            *pk_coord.x(),
-            *pk_coord.y(),
-            message[0],
-            message[1],
-            message[2],
-            message[3],
-        ]));
-        let s = z + h * sk;
-        Self { pk, r, s }
-    }
-}
-
 // SignatureVerificationValidityPredicateCircuit uses the schnorr signature.
 #[derive(Clone, Debug, Default)]
 pub struct SignatureVerificationValidityPredicateCircuit {
@@ -102,6 +56,14 @@ pub struct SignatureVerificationValidityPredicateCircuit {
     pub receiver_vp_vk: pallas::Base,
 }
 
+// The signature is checked against a public key, so only the resources are secret.
+impl Zeroize for SignatureVerificationValidityPredicateCircuit {
+    fn zeroize(&mut self) {
+        self.input_resources.iter_mut().for_each(Zeroize::zeroize);
+        self.output_resources.iter_mut().for_each(Zeroize::zeroize);
+    }
+}
+
 impl SignatureVerificationValidityPredicateCircuit {
     pub fn new(
         owned_resource_id: pallas::Base,
@@ -141,7 +103,8 @@ impl SignatureVerificationValidityPredicateCircuit {
                 let cm = output_resource.commitment();
                 message.push(cm.inner());
             });
-        let signature = SchnorrSignature::sign(&mut rng, sk, message);
+        let signature =
+            SchnorrSignature::sign_array::<_, POSEIDON_HASH_LEN>(&mut rng, sk, &message);
         Self {
             owned_resource_id,
             input_resources,
@@ -217,53 +180,23 @@ impl ValidityPredicateCircuit for SignatureVerificationValidityPredicateCircuit
             |mut region| region.constrain_equal(encoded_value.cell(), value.cell()),
         )?;
 
-        let r = NonIdentityPoint::new(
-            ecc_chip.clone(),
-            layouter.namespace(|| "witness r"),
-            Value::known(self.signature.r.to_affine()),
-        )?;
-        let s_scalar = ScalarFixed::new(
-            ecc_chip.clone(),
-            layouter.namespace(|| "witness s"),
-            Value::known(self.signature.s),
-        )?;
-
         // Verify: s*G = R + Hash(r||P||m)*P
-        // s*G
-        let generator =
-            FixedPoint::from_inner(ecc_chip.clone(), TaigaFixedBasesFull::BaseGenerator);
-        let (s_g, _) = generator.mul(layouter.namespace(|| "s_scalar * generator"), &s_scalar)?;
-
-        // Hash(r||P||m)
-        let h_scalar = {
-            let nfs = basic_variables.get_input_resource_nfs();
-            let cms = basic_variables.get_output_resource_cms();
-            assert_eq!(NUM_RESOURCE, 2);
-            let h = poseidon_hash_gadget(
-                config.poseidon_config,
-                layouter.namespace(|| "Poseidon_hash(r, P, m)"),
-                [
-                    r.inner().x(),
-                    r.inner().y(),
-                    pk.inner().x(),
-                    pk.inner().y(),
-                    nfs[0].clone(),
-                    cms[0].clone(),
-                    nfs[1].clone(),
-                    cms[1].clone(),
-                ],
-            )?;
-
-            ScalarVar::from_base(ecc_chip, layouter.namespace(|| "ScalarVar from_base"), &h)?
-        };
-
-        // Hash(r||P||m)*P
-        let (h_p, _) = pk.mul(layouter.namespace(|| "hP"), h_scalar)?;
-
-        // R + Hash(r||P||m)*P
-        let rhs = r.add(layouter.namespace(|| "R + Hash(r||P||m)*P"), &h_p)?;
-
-        s_g.constrain_equal(layouter.namespace(|| "s*G = R + Hash(r||P||m)*P"), &rhs)?;
+        let nfs = basic_variables.get_input_resource_nfs();
+        let cms = basic_variables.get_output_resource_cms();
+        assert_eq!(NUM_RESOURCE, 2);
+        schnorr_verify_array_gadget::<POSEIDON_HASH_LEN>(
+            layouter.namespace(|| "verify signature"),
+            ecc_chip,
+            config.poseidon_config,
+            &pk,
+            &self.signature,
+            &[
+                nfs[0].clone(),
+                cms[0].clone(),
+                nfs[1].clone(),
+                cms[1].clone(),
+            ],
+        )?;
 
         // Publicize the dynamic vp commitments with default value
         publicize_default_dynamic_vp_commitments(
@@ -347,25 +280,6 @@ impl BorshDeserialize for SignatureVerificationValidityPredicateCircuit {
     }
 }
 
-impl BorshSerialize for SchnorrSignature {
-    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
-        writer.write_all(&self.pk.to_bytes())?;
-        writer.write_all(&self.r.to_bytes())?;
-        writer.write_all(&self.s.to_repr())?;
-
-        Ok(())
-    }
-}
-
-impl BorshDeserialize for SchnorrSignature {
-    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
-        let pk = read_point(reader)?;
-        let r = read_point(reader)?;
-        let s = read_scalar_field(reader)?;
-        Ok(Self { pk, r, s })
-    }
-}
-
 #[test]
 fn test_halo2_sig_verification_vp_circuit() {
     use crate::circuit::vp_examples::{
@@ -373,7 +287,7 @@ fn test_halo2_sig_verification_vp_circuit() {
     };
     use crate::constant::VP_CIRCUIT_PARAMS_SIZE;
     use crate::resource::tests::random_resource;
-    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::{arithmetic::Field, dev::MockProver};
     use rand::rngs::OsRng;
 
     let mut rng = OsRng;
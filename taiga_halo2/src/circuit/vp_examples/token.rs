@@ -41,6 +41,7 @@ use lazy_static::lazy_static;
 use pasta_curves::arithmetic::CurveAffine;
 use pasta_curves::{group::ff::PrimeField, pallas};
 use rand::{rngs::OsRng, Rng, RngCore};
+use zeroize::Zeroize;
 
 lazy_static! {
     pub static ref TOKEN_VK: ValidityPredicateVerifyingKey =
@@ -271,6 +272,14 @@ pub struct TokenValidityPredicateCircuit {
     pub rseed: RandomSeed,
 }
 
+impl Zeroize for TokenValidityPredicateCircuit {
+    fn zeroize(&mut self) {
+        self.input_resources.iter_mut().for_each(Zeroize::zeroize);
+        self.output_resources.iter_mut().for_each(Zeroize::zeroize);
+        self.rseed.zeroize();
+    }
+}
+
 #[derive(Clone, Debug, Copy)]
 pub struct TokenAuthorization {
     pub pk: pallas::Point,
@@ -14,8 +14,10 @@ pub mod proof;
 pub mod resource;
 pub mod resource_encryption;
 pub mod shielded_ptx;
+pub mod stealth_address;
 pub mod taiga_api;
 pub mod transaction;
+pub mod transcript;
 pub mod transparent_ptx;
 pub mod utils;
 pub mod vp_commitment;
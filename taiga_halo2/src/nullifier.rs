@@ -2,7 +2,7 @@ use std::hash::Hash;
 
 use crate::{
     resource::ResourceCommitment,
-    utils::{poseidon_hash_n, prf_nf, read_base_field},
+    utils::{poseidon_hash_n, prf_nf, read_base_field, zeroize_base},
 };
 use halo2_proofs::arithmetic::Field;
 use pasta_curves::group::ff::PrimeField;
@@ -11,6 +11,7 @@ use rand::RngCore;
 #[cfg(feature = "nif")]
 use rustler::{NifTaggedEnum, NifTuple};
 use subtle::CtOption;
+use zeroize::Zeroize;
 
 #[cfg(feature = "serde")]
 use serde;
@@ -146,6 +147,15 @@ impl NullifierKeyContainer {
     }
 }
 
+impl Zeroize for NullifierKeyContainer {
+    fn zeroize(&mut self) {
+        match self {
+            NullifierKeyContainer::PublicKey(npk) => zeroize_base(npk),
+            NullifierKeyContainer::Key(nk) => zeroize_base(nk),
+        }
+    }
+}
+
 impl Default for NullifierKeyContainer {
     fn default() -> NullifierKeyContainer {
         let key = pallas::Base::default();
@@ -1,12 +1,14 @@
+use crate::transcript::{Blake2sRead, Blake2sWrite, PoseidonRead, PoseidonWrite, TranscriptHash};
 use halo2_proofs::{
     plonk::{self, Circuit, ProvingKey, SingleVerifier, VerifyingKey},
     poly::commitment::Params,
-    transcript::{Blake2bRead, Blake2bWrite},
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptRead, TranscriptWrite},
 };
 use pasta_curves::{pallas, vesta};
 use rand::RngCore;
 #[cfg(feature = "nif")]
 use rustler::NifTuple;
+use zeroize::Zeroize;
 
 #[cfg(feature = "serde")]
 use serde;
@@ -41,6 +43,34 @@ impl Proof {
         Ok(Proof(transcript.finalize()))
     }
 
+    /// Creates a proof like `create`, and zeroizes the secret witnesses of the circuit once
+    /// proving returns. The circuit is borrowed rather than moved so that the prover doesn't
+    /// keep a copy of it.
+    ///
+    /// Only the caller's circuit is cleared. The advice columns and polynomials halo2 computes
+    /// from the witnesses during proving are dropped without being zeroized, and neither are the
+    /// copies of the resources made before proving, as `Resource` is `Copy`.
+    pub fn create_and_zeroize<C: Circuit<pallas::Base> + Zeroize>(
+        pk: &ProvingKey<vesta::Affine>,
+        params: &Params<vesta::Affine>,
+        circuit: &mut C,
+        instance: &[&[pallas::Base]],
+        mut rng: impl RngCore,
+    ) -> Result<Self, plonk::Error> {
+        let mut transcript = Blake2bWrite::<_, vesta::Affine, _>::init(vec![]);
+        let ret = plonk::create_proof(
+            params,
+            pk,
+            std::slice::from_ref(circuit),
+            &[instance],
+            &mut rng,
+            &mut transcript,
+        );
+        circuit.zeroize();
+        ret?;
+        Ok(Proof(transcript.finalize()))
+    }
+
     /// Verifies this proof with the given instances.
     pub fn verify(
         &self,
@@ -53,6 +83,111 @@ impl Proof {
         plonk::verify_proof(params, vk, strategy, &[instance], &mut transcript)
     }
 
+    /// Creates a proof like `create`, with the given transcript hash.
+    pub fn create_with_transcript_hash<C: Circuit<pallas::Base>>(
+        pk: &ProvingKey<vesta::Affine>,
+        params: &Params<vesta::Affine>,
+        circuit: C,
+        instance: &[&[pallas::Base]],
+        mut rng: impl RngCore,
+        transcript_hash: TranscriptHash,
+    ) -> Result<Self, plonk::Error> {
+        let circuits = [circuit];
+        let bytes = match transcript_hash {
+            TranscriptHash::Blake2b => {
+                let mut transcript = Blake2bWrite::<_, vesta::Affine, _>::init(vec![]);
+                plonk::create_proof(
+                    params,
+                    pk,
+                    &circuits,
+                    &[instance],
+                    &mut rng,
+                    &mut transcript,
+                )?;
+                transcript.finalize()
+            }
+            TranscriptHash::Blake2s => {
+                let mut transcript = Blake2sWrite::<_, vesta::Affine>::init(vec![]);
+                plonk::create_proof(
+                    params,
+                    pk,
+                    &circuits,
+                    &[instance],
+                    &mut rng,
+                    &mut transcript,
+                )?;
+                transcript.finalize()
+            }
+            TranscriptHash::Poseidon => {
+                let mut transcript = PoseidonWrite::<_, vesta::Affine>::init(vec![]);
+                plonk::create_proof(
+                    params,
+                    pk,
+                    &circuits,
+                    &[instance],
+                    &mut rng,
+                    &mut transcript,
+                )?;
+                transcript.finalize()
+            }
+        };
+        Ok(Proof(bytes))
+    }
+
+    /// Verifies this proof like `verify`, with the transcript hash it was created with.
+    pub fn verify_with_transcript_hash(
+        &self,
+        vk: &VerifyingKey<vesta::Affine>,
+        params: &Params<vesta::Affine>,
+        instance: &[&[pallas::Base]],
+        transcript_hash: TranscriptHash,
+    ) -> Result<(), plonk::Error> {
+        let strategy = SingleVerifier::new(params);
+        match transcript_hash {
+            TranscriptHash::Blake2b => {
+                let mut transcript = Blake2bRead::init(&self.0[..]);
+                plonk::verify_proof(params, vk, strategy, &[instance], &mut transcript)
+            }
+            TranscriptHash::Blake2s => {
+                let mut transcript = Blake2sRead::init(&self.0[..]);
+                plonk::verify_proof(params, vk, strategy, &[instance], &mut transcript)
+            }
+            TranscriptHash::Poseidon => {
+                let mut transcript = PoseidonRead::init(&self.0[..]);
+                plonk::verify_proof(params, vk, strategy, &[instance], &mut transcript)
+            }
+        }
+    }
+
+    /// Creates a proof like `create`, into a transcript managed by the caller. The caller can absorb
+    /// other values into the transcript before proving, and gets the proof bytes by finalizing it.
+    pub fn create_with_transcript<
+        C: Circuit<pallas::Base>,
+        T: TranscriptWrite<vesta::Affine, Challenge255<vesta::Affine>>,
+    >(
+        pk: &ProvingKey<vesta::Affine>,
+        params: &Params<vesta::Affine>,
+        circuit: C,
+        instance: &[&[pallas::Base]],
+        mut rng: impl RngCore,
+        transcript: &mut T,
+    ) -> Result<(), plonk::Error> {
+        plonk::create_proof(params, pk, &[circuit], &[instance], &mut rng, transcript)
+    }
+
+    /// Verifies a proof read from a transcript managed by the caller, so that the verification can
+    /// be part of a larger Fiat-Shamir transcript. The transcript must be seeded like the one the
+    /// proof was created with.
+    pub fn verify_with_transcript<T: TranscriptRead<vesta::Affine, Challenge255<vesta::Affine>>>(
+        transcript: &mut T,
+        vk: &VerifyingKey<vesta::Affine>,
+        params: &Params<vesta::Affine>,
+        instance: &[&[pallas::Base]],
+    ) -> Result<(), plonk::Error> {
+        let strategy = SingleVerifier::new(params);
+        plonk::verify_proof(params, vk, strategy, &[instance], transcript)
+    }
+
     /// Constructs a new Proof value.
     pub fn new(bytes: Vec<u8>) -> Self {
         Proof(bytes)
@@ -62,3 +197,97 @@ impl Proof {
         self.0.clone()
     }
 }
+
+#[test]
+fn test_proof_transcript_hash() {
+    use crate::circuit::{
+        vp_circuit::ValidityPredicateCircuit,
+        vp_examples::{tests::random_trivial_vp_circuit, TRIVIAL_VP_PK, TRIVIAL_VP_VK},
+    };
+    use crate::constant::{SETUP_PARAMS_MAP, VP_CIRCUIT_PARAMS_SIZE};
+    use rand::rngs::OsRng;
+
+    let mut rng = OsRng;
+    let circuit = random_trivial_vp_circuit(&mut rng);
+    let public_inputs = circuit.get_public_inputs(&mut rng);
+    let params = SETUP_PARAMS_MAP.get(&VP_CIRCUIT_PARAMS_SIZE).unwrap();
+    let vk = TRIVIAL_VP_VK.get_vk().unwrap();
+
+    let transcript_hashes = [
+        TranscriptHash::Blake2b,
+        TranscriptHash::Blake2s,
+        TranscriptHash::Poseidon,
+    ];
+    for prover_hash in transcript_hashes {
+        let proof = Proof::create_with_transcript_hash(
+            &TRIVIAL_VP_PK,
+            params,
+            circuit.clone(),
+            &[public_inputs.inner()],
+            &mut rng,
+            prover_hash,
+        )
+        .unwrap();
+        // The proof only verifies under the transcript it was created with
+        for verifier_hash in transcript_hashes {
+            let result = proof.verify_with_transcript_hash(
+                &vk,
+                params,
+                &[public_inputs.inner()],
+                verifier_hash,
+            );
+            assert_eq!(result.is_ok(), prover_hash == verifier_hash);
+        }
+    }
+}
+
+#[test]
+fn test_proof_external_transcript() {
+    use crate::circuit::{
+        vp_circuit::ValidityPredicateCircuit,
+        vp_examples::{tests::random_trivial_vp_circuit, TRIVIAL_VP_PK, TRIVIAL_VP_VK},
+    };
+    use crate::constant::{SETUP_PARAMS_MAP, VP_CIRCUIT_PARAMS_SIZE};
+    use ff::Field;
+    use halo2_proofs::transcript::Transcript;
+    use rand::rngs::OsRng;
+
+    let mut rng = OsRng;
+    let circuit = random_trivial_vp_circuit(&mut rng);
+    let public_inputs = circuit.get_public_inputs(&mut rng);
+    let params = SETUP_PARAMS_MAP.get(&VP_CIRCUIT_PARAMS_SIZE).unwrap();
+    let vk = TRIVIAL_VP_VK.get_vk().unwrap();
+
+    // The outer protocol seeds the transcript before the proof
+    let seed = vesta::Scalar::random(&mut rng);
+    let mut transcript = Blake2bWrite::<_, vesta::Affine, _>::init(vec![]);
+    transcript.common_scalar(seed).unwrap();
+    Proof::create_with_transcript(
+        &TRIVIAL_VP_PK,
+        params,
+        circuit,
+        &[public_inputs.inner()],
+        &mut rng,
+        &mut transcript,
+    )
+    .unwrap();
+    let proof = Proof::new(transcript.finalize());
+
+    let mut transcript = Blake2bRead::init(&proof.0[..]);
+    transcript.common_scalar(seed).unwrap();
+    assert!(
+        Proof::verify_with_transcript(&mut transcript, &vk, params, &[public_inputs.inner()])
+            .is_ok()
+    );
+
+    // The proof is bound to the seed
+    let mut transcript = Blake2bRead::init(&proof.0[..]);
+    transcript
+        .common_scalar(seed + vesta::Scalar::one())
+        .unwrap();
+    assert!(
+        Proof::verify_with_transcript(&mut transcript, &vk, params, &[public_inputs.inner()])
+            .is_err()
+    );
+    assert!(proof.verify(&vk, params, &[public_inputs.inner()]).is_err());
+}
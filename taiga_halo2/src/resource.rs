@@ -11,15 +11,16 @@ use crate::{
     merkle_tree::{Anchor, MerklePath, Node},
     nullifier::{Nullifier, NullifierKeyContainer},
     shielded_ptx::ResourceVPVerifyingInfoSet,
-    utils::{poseidon_hash_n, poseidon_to_curve, read_base_field},
+    utils::{poseidon_hash_n, poseidon_to_curve, read_base_field, zeroize_base},
 };
 use blake2b_simd::Params as Blake2bParams;
 use ff::{FromUniformBytes, PrimeField};
 use halo2_proofs::arithmetic::Field;
 use pasta_curves::pallas;
-use rand::RngCore;
+use rand::{Rng, RngCore};
 use std::hash::{Hash, Hasher};
 use subtle::CtOption;
+use zeroize::Zeroize;
 
 #[cfg(feature = "nif")]
 use rustler::{NifStruct, NifTuple};
@@ -117,6 +118,26 @@ pub struct ResourceKind {
 #[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
 pub struct RandomSeed([u8; 32]);
 
+/// Parameters for generating dummy resources in bulk, e.g. for benchmarks and load tests.
+#[derive(Debug, Clone, Copy)]
+pub struct DummyResourceOptions {
+    /// If specified, all the dummy resources share this kind, otherwise each has a random kind.
+    pub kind: Option<ResourceKind>,
+    /// The quantities are sampled uniformly from 0..=max_quantity.
+    pub max_quantity: u64,
+    pub is_ephemeral: bool,
+}
+
+impl Default for DummyResourceOptions {
+    fn default() -> Self {
+        Self {
+            kind: None,
+            max_quantity: u64::MAX,
+            is_ephemeral: false,
+        }
+    }
+}
+
 /// ResourceValidityPredicates includes one application(static) VP and a few dynamic VPs.
 #[derive(Clone)]
 pub struct ResourceValidityPredicates {
@@ -213,13 +234,31 @@ impl Resource {
         }
     }
 
+    // Generate n dummy resources with random fields, honoring the options
+    pub fn dummy_batch<R: RngCore>(n: usize, mut rng: R, opts: &DummyResourceOptions) -> Vec<Self> {
+        (0..n)
+            .map(|_| {
+                let kind = opts.kind.unwrap_or_else(|| {
+                    ResourceKind::new(
+                        pallas::Base::random(&mut rng),
+                        pallas::Base::random(&mut rng),
+                    )
+                });
+                Resource {
+                    kind,
+                    value: pallas::Base::random(&mut rng),
+                    quantity: rng.gen_range(0..=opts.max_quantity),
+                    nk_container: NullifierKeyContainer::from_key(pallas::Base::random(&mut rng)),
+                    nonce: Nullifier::from(pallas::Base::random(&mut rng)),
+                    is_ephemeral: opts.is_ephemeral,
+                    rseed: pallas::Base::random(&mut rng),
+                }
+            })
+            .collect()
+    }
+
     // resource_commitment = poseidon_hash(logic || label || value || npk || nonce || psi || is_ephemeral || quantity || rcm)
     pub fn commitment(&self) -> ResourceCommitment {
-        let compose_is_ephemeral_quantity = if self.is_ephemeral {
-            pallas::Base::from_u128(1 << 64).square() + pallas::Base::from(self.quantity)
-        } else {
-            pallas::Base::from(self.quantity)
-        };
         let ret = poseidon_hash_n([
             self.get_logic(),
             self.get_label(),
@@ -227,12 +266,34 @@ impl Resource {
             self.get_npk(),
             self.nonce.inner(),
             self.get_psi(),
-            compose_is_ephemeral_quantity,
+            self.compose_is_ephemeral_quantity(),
             self.get_rcm(),
         ]);
         ResourceCommitment(ret)
     }
 
+    // binding_commitment = poseidon_hash(logic || label || value || npk || nonce || psi || is_ephemeral || quantity)
+    // It's deterministic in the resource fields, see `resource_commit_dual`.
+    pub fn binding_commitment(&self) -> pallas::Base {
+        poseidon_hash_n([
+            self.get_logic(),
+            self.get_label(),
+            self.value,
+            self.get_npk(),
+            self.nonce.inner(),
+            self.get_psi(),
+            self.compose_is_ephemeral_quantity(),
+        ])
+    }
+
+    fn compose_is_ephemeral_quantity(&self) -> pallas::Base {
+        if self.is_ephemeral {
+            pallas::Base::from_u128(1 << 64).square() + pallas::Base::from(self.quantity)
+        } else {
+            pallas::Base::from(self.quantity)
+        }
+    }
+
     pub fn get_nf(&self) -> Option<Nullifier> {
         Nullifier::derive(
             &self.nk_container,
@@ -242,6 +303,18 @@ impl Resource {
         )
     }
 
+    // Derive the nullifier with an externally-held nullifier key, e.g. when a wallet watches
+    // resources which only carry the npk. It matches the nullifier derived in the circuit.
+    pub fn nullifier(&self, nk: pallas::Base) -> Nullifier {
+        Nullifier::derive(
+            &NullifierKeyContainer::from_key(nk),
+            &self.nonce.inner(),
+            &self.get_psi(),
+            &self.commitment(),
+        )
+        .unwrap()
+    }
+
     pub fn get_nk(&self) -> Option<pallas::Base> {
         self.nk_container.get_nk()
     }
@@ -292,6 +365,17 @@ impl Resource {
     }
 }
 
+// Clears the secret fields of the resource. Resource is Copy, so only this instance is cleared
+// and the copies made elsewhere have to be zeroized separately.
+impl Zeroize for Resource {
+    fn zeroize(&mut self) {
+        zeroize_base(&mut self.value);
+        self.quantity.zeroize();
+        self.nk_container.zeroize();
+        zeroize_base(&mut self.rseed);
+    }
+}
+
 #[cfg(feature = "borsh")]
 impl BorshSerialize for Resource {
     fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
@@ -397,6 +481,12 @@ impl Hash for ResourceKind {
     }
 }
 
+impl Zeroize for RandomSeed {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl RandomSeed {
     pub fn random<R: RngCore>(mut rng: R) -> Self {
         let mut rseed = [0; 32];
@@ -509,7 +599,7 @@ impl ResourceValidityPredicates {
 
 #[cfg(test)]
 pub mod tests {
-    use super::{Resource, ResourceKind};
+    use super::{DummyResourceOptions, Resource, ResourceKind};
     use crate::nullifier::tests::*;
     use halo2_proofs::arithmetic::Field;
     use pasta_curves::pallas;
@@ -583,4 +673,28 @@ pub mod tests {
             assert_eq!(ocm, de_ocm);
         }
     }
+
+    #[test]
+    fn test_dummy_batch() {
+        use rand::rngs::OsRng;
+        let mut rng = OsRng;
+
+        let kind = random_kind(&mut rng);
+        let opts = DummyResourceOptions {
+            kind: Some(kind),
+            max_quantity: 1000,
+            ..Default::default()
+        };
+        let resources = Resource::dummy_batch(20, &mut rng, &opts);
+        assert_eq!(resources.len(), 20);
+        for resource in resources.iter() {
+            assert_eq!(resource.kind, kind);
+            assert!(resource.quantity <= 1000);
+            assert!(!resource.is_ephemeral);
+        }
+
+        // Without a fixed kind, every resource gets its own random kind
+        let resources = Resource::dummy_batch(2, &mut rng, &DummyResourceOptions::default());
+        assert_ne!(resources[0].kind, resources[1].kind);
+    }
 }
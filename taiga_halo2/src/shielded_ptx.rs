@@ -480,6 +480,50 @@ impl ResourceVPVerifyingInfoSet {
     }
 }
 
+/// A compliance proof linked to the application VP proof of the resource it consumes. Both proofs
+/// must refer to the same nullifier and the same output resource commitment.
+#[derive(Debug, Clone)]
+pub struct LinkedAction {
+    compliance: ComplianceVerifyingInfo,
+    input_vp: VPVerifyingInfo,
+}
+
+impl LinkedAction {
+    pub fn new(compliance: ComplianceVerifyingInfo, input_vp: VPVerifyingInfo) -> Self {
+        Self {
+            compliance,
+            input_vp,
+        }
+    }
+
+    // verify both proofs and their linkage
+    pub fn verify(&self) -> Result<(), TransactionError> {
+        self.compliance.verify()?;
+        self.input_vp.verify()?;
+        self.check_linkage()
+    }
+
+    fn check_linkage(&self) -> Result<(), TransactionError> {
+        let compliance_nf = self.compliance.compliance_instance.nf.inner();
+        // The vp is the one of the resource consumed by the compliance proof
+        if self.input_vp.get_owned_resource_id() != compliance_nf {
+            return Err(TransactionError::InconsistentOwneResourceID);
+        }
+        if !self.input_vp.get_nullifiers().contains(&compliance_nf) {
+            return Err(TransactionError::InconsistentNullifier);
+        }
+        // The vp constrains the resource created by the compliance proof
+        if !self
+            .input_vp
+            .get_resource_commitments()
+            .contains(&self.compliance.compliance_instance.cm)
+        {
+            return Err(TransactionError::InconsistentOutputResourceCommitment);
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 pub mod testing {
     use crate::{
@@ -646,4 +690,56 @@ pub mod testing {
         )
         .unwrap()
     }
+
+    #[test]
+    fn test_linked_action() {
+        use super::{ComplianceVerifyingInfo, LinkedAction};
+        use crate::error::TransactionError;
+        use crate::resource::tests::random_resource;
+
+        let mut rng = OsRng;
+        let input_resource = random_resource(&mut rng);
+        let mut output_resource = random_resource(&mut rng);
+        let merkle_path = MerklePath::random(&mut rng, TAIGA_COMMITMENT_TREE_DEPTH);
+        let compliance_info = ComplianceInfo::new(
+            input_resource,
+            merkle_path,
+            None,
+            &mut output_resource,
+            &mut rng,
+        );
+        let compliance = ComplianceVerifyingInfo::create(&compliance_info, &mut rng).unwrap();
+
+        let other_input_resource = random_resource(&mut rng);
+        let other_output_resource = random_resource(&mut rng);
+        let vp = TrivialValidityPredicateCircuit {
+            owned_resource_id: input_resource.get_nf().unwrap().inner(),
+            input_resources: [input_resource, other_input_resource],
+            output_resources: [output_resource, other_output_resource],
+        };
+        let linked_action = LinkedAction::new(compliance.clone(), vp.get_verifying_info());
+        assert!(linked_action.verify().is_ok());
+
+        // The vp is owned by another resource
+        let other_vp = TrivialValidityPredicateCircuit {
+            owned_resource_id: other_input_resource.get_nf().unwrap().inner(),
+            ..vp.clone()
+        };
+        let linked_action = LinkedAction::new(compliance.clone(), other_vp.get_verifying_info());
+        assert!(matches!(
+            linked_action.verify(),
+            Err(TransactionError::InconsistentOwneResourceID)
+        ));
+
+        // The vp doesn't commit to the output resource of the compliance proof
+        let other_vp = TrivialValidityPredicateCircuit {
+            output_resources: [other_output_resource, other_output_resource],
+            ..vp
+        };
+        let linked_action = LinkedAction::new(compliance, other_vp.get_verifying_info());
+        assert!(matches!(
+            linked_action.verify(),
+            Err(TransactionError::InconsistentOutputResourceCommitment)
+        ));
+    }
 }
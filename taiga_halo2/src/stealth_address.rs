@@ -0,0 +1,87 @@
+use crate::resource_encryption::SecretKey;
+use crate::utils::{mod_r_p, poseidon_hash};
+use group::{Curve, Group};
+use halo2_proofs::arithmetic::CurveAffine;
+use pasta_curves::pallas;
+
+/// A one-time address of a recipient: recipient_pk + Poseidon(ephemeral_sk * recipient_pk) * G.
+/// Only the recipient can link the address to its public key, using the ephemeral public key
+/// published along with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StealthAddress(pallas::Point);
+
+impl StealthAddress {
+    /// Derives a fresh stealth address of the recipient, along with the ephemeral public key the
+    /// recipient needs to recover it.
+    pub fn derive(
+        recipient_pk: &pallas::Point,
+        ephemeral_sk: &pallas::Scalar,
+    ) -> (Self, pallas::Point) {
+        let shared_secret = SecretKey::from_dh_exchange(recipient_pk, ephemeral_sk);
+        let address = recipient_pk + pallas::Point::generator() * Self::tweak(&shared_secret);
+        let ephemeral_pk = pallas::Point::generator() * ephemeral_sk;
+        (Self(address), ephemeral_pk)
+    }
+
+    /// Recomputes the stealth address from the recipient secret key and the ephemeral public key.
+    /// The recipient owns the address iff it's equal to the published one.
+    pub fn recover(recipient_sk: &pallas::Scalar, ephemeral_pk: &pallas::Point) -> Self {
+        let shared_secret = SecretKey::from_dh_exchange(ephemeral_pk, recipient_sk);
+        let recipient_pk = pallas::Point::generator() * recipient_sk;
+        Self(recipient_pk + pallas::Point::generator() * Self::tweak(&shared_secret))
+    }
+
+    /// The secret key of the stealth address: recipient_sk + Poseidon(recipient_sk * ephemeral_pk).
+    pub fn secret_key(
+        recipient_sk: &pallas::Scalar,
+        ephemeral_pk: &pallas::Point,
+    ) -> pallas::Scalar {
+        let shared_secret = SecretKey::from_dh_exchange(ephemeral_pk, recipient_sk);
+        recipient_sk + Self::tweak(&shared_secret)
+    }
+
+    pub fn inner(&self) -> pallas::Point {
+        self.0
+    }
+
+    pub fn get_coordinates(&self) -> (pallas::Base, pallas::Base) {
+        let coordinates = self.0.to_affine().coordinates().unwrap();
+        (*coordinates.x(), *coordinates.y())
+    }
+
+    fn tweak(shared_secret: &SecretKey) -> pallas::Scalar {
+        let (x, y) = shared_secret.get_coordinates();
+        mod_r_p(poseidon_hash(x, y))
+    }
+}
+
+#[test]
+fn test_stealth_address() {
+    use ff::Field;
+    use rand::rngs::OsRng;
+
+    let mut rng = OsRng;
+    let recipient_sk = pallas::Scalar::random(&mut rng);
+    let recipient_pk = pallas::Point::generator() * recipient_sk;
+    let ephemeral_sk = pallas::Scalar::random(&mut rng);
+    let (address, ephemeral_pk) = StealthAddress::derive(&recipient_pk, &ephemeral_sk);
+
+    // The recipient recovers the address and its secret key
+    assert_eq!(
+        StealthAddress::recover(&recipient_sk, &ephemeral_pk),
+        address
+    );
+    assert_eq!(
+        pallas::Point::generator() * StealthAddress::secret_key(&recipient_sk, &ephemeral_pk),
+        address.inner()
+    );
+
+    // An unrelated key can't
+    let other_sk = pallas::Scalar::random(&mut rng);
+    assert_ne!(StealthAddress::recover(&other_sk, &ephemeral_pk), address);
+
+    // Another payment to the same recipient gets an unlinkable address
+    let (other_address, _) =
+        StealthAddress::derive(&recipient_pk, &pallas::Scalar::random(&mut rng));
+    assert_ne!(other_address, address);
+}
@@ -1,5 +1,7 @@
 use crate::binding_signature::{BindingSignature, BindingSigningKey, BindingVerificationKey};
-use crate::constant::TRANSACTION_BINDING_HASH_PERSONALIZATION;
+use crate::constant::{
+    TRANSACTION_BINDING_HASH_PERSONALIZATION, TRANSACTION_BINDING_MESSAGE_PERSONALIZATION,
+};
 use crate::delta_commitment::DeltaCommitment;
 use crate::error::TransactionError;
 use crate::executable::Executable;
@@ -9,6 +11,7 @@ use crate::resource::ResourceCommitment;
 use crate::shielded_ptx::ShieldedPartialTransaction;
 use crate::transparent_ptx::TransparentPartialTransaction;
 use blake2b_simd::Params as Blake2bParams;
+use blake2s_simd::Params as Blake2sParams;
 use pasta_curves::{group::Group, pallas};
 use rand::{CryptoRng, RngCore};
 
@@ -158,6 +161,31 @@ impl Transaction {
     }
 }
 
+// The canonical message over the public data of a transaction, in the order: nullifiers, output
+// commitments, anchors. Every list is prefixed with its length so that moving an item from one list
+// to another changes the message. The fee is paid by a fee output resource, so it's bound by the
+// output commitments.
+pub fn tx_binding_message(tx: &TransactionResult) -> [u8; 32] {
+    let mut h = Blake2sParams::new()
+        .hash_length(32)
+        .personal(TRANSACTION_BINDING_MESSAGE_PERSONALIZATION)
+        .to_state();
+    h.update(&(tx.nullifiers.len() as u64).to_le_bytes());
+    tx.nullifiers.iter().for_each(|nf| {
+        h.update(&nf.to_bytes());
+    });
+    h.update(&(tx.output_cms.len() as u64).to_le_bytes());
+    tx.output_cms.iter().for_each(|cm| {
+        h.update(&cm.to_bytes());
+    });
+    h.update(&(tx.anchors.len() as u64).to_le_bytes());
+    tx.anchors.iter().for_each(|anchor| {
+        h.update(&anchor.to_bytes());
+    });
+
+    h.finalize().as_bytes().try_into().unwrap()
+}
+
 #[cfg(feature = "nif")]
 atoms! { transaction }
 
@@ -372,4 +400,42 @@ pub mod testing {
             assert_eq!(_ret, de_ret);
         }
     }
+
+    #[test]
+    fn test_tx_binding_message() {
+        use super::*;
+
+        let tx = TransactionResult {
+            anchors: vec![Anchor::from(pallas::Base::from(5u64))],
+            nullifiers: vec![
+                Nullifier::from(pallas::Base::from(1u64)),
+                Nullifier::from(pallas::Base::from(2u64)),
+            ],
+            output_cms: vec![
+                ResourceCommitment::from(pallas::Base::from(3u64)),
+                ResourceCommitment::from(pallas::Base::from(4u64)),
+            ],
+        };
+        let message = tx_binding_message(&tx);
+        assert_eq!(
+            message,
+            [
+                99, 2, 90, 36, 255, 251, 142, 177, 162, 223, 242, 48, 154, 4, 160, 47, 177, 31,
+                104, 136, 65, 228, 77, 222, 96, 200, 104, 115, 89, 4, 97, 81
+            ]
+        );
+
+        // Reorder the nullifiers
+        let mut reordered = tx.clone();
+        reordered.nullifiers.reverse();
+        assert_ne!(tx_binding_message(&reordered), message);
+
+        // Move a nullifier to the output commitments
+        let mut reordered = tx.clone();
+        let nf = reordered.nullifiers.pop().unwrap();
+        reordered
+            .output_cms
+            .insert(0, ResourceCommitment::from(nf.inner()));
+        assert_ne!(tx_binding_message(&reordered), message);
+    }
 }
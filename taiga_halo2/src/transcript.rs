@@ -0,0 +1,241 @@
+use crate::utils::poseidon_hash;
+use blake2s_simd::{Params as Blake2sParams, State as Blake2sState};
+use ff::{FromUniformBytes, PrimeField};
+use halo2_proofs::{
+    arithmetic::{Coordinates, CurveAffine},
+    transcript::{Challenge255, EncodedChallenge, Transcript, TranscriptRead, TranscriptWrite},
+};
+use pasta_curves::pallas;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+const PREFIX_CHALLENGE: u8 = 0;
+const PREFIX_POINT: u8 = 1;
+const PREFIX_SCALAR: u8 = 2;
+
+/// The hash function of the Fiat-Shamir transcript of a proof. Blake2b is the default transcript of
+/// halo2, and the Poseidon transcript is a hash chain over the circuit field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TranscriptHash {
+    #[default]
+    Blake2b,
+    Blake2s,
+    Poseidon,
+}
+
+/// The running state of a transcript hash.
+pub trait TranscriptState: Clone {
+    fn init() -> Self;
+    /// Absorbs the tag and the bytes, whose length is a multiple of 16.
+    fn absorb(&mut self, tag: u8, bytes: &[u8]);
+    /// Squeezes the 64 bytes the challenge is derived from.
+    fn squeeze(&mut self) -> [u8; 64];
+}
+
+#[derive(Clone, Debug)]
+pub struct Blake2sTranscriptState(Blake2sState);
+
+impl TranscriptState for Blake2sTranscriptState {
+    fn init() -> Self {
+        Self(
+            Blake2sParams::new()
+                .hash_length(32)
+                .personal(b"Taiga-Tr")
+                .to_state(),
+        )
+    }
+
+    fn absorb(&mut self, tag: u8, bytes: &[u8]) {
+        self.0.update(&[tag]);
+        self.0.update(bytes);
+    }
+
+    // A blake2s digest is 32 bytes, so the challenge bytes are two digests of the state
+    fn squeeze(&mut self) -> [u8; 64] {
+        self.0.update(&[PREFIX_CHALLENGE]);
+        let mut result = [0u8; 64];
+        for (i, chunk) in result.chunks_mut(32).enumerate() {
+            let mut state = self.0.clone();
+            state.update(&[i as u8]);
+            chunk.copy_from_slice(state.finalize().as_bytes());
+        }
+        result
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PoseidonTranscriptState(pallas::Base);
+
+impl TranscriptState for PoseidonTranscriptState {
+    fn init() -> Self {
+        Self(pallas::Base::from_u128(u128::from_le_bytes(
+            *b"Taiga-Transcript",
+        )))
+    }
+
+    // The bytes are absorbed as 128-bit limbs, which are canonical in the field whatever the field
+    // of the absorbed value is
+    fn absorb(&mut self, tag: u8, bytes: &[u8]) {
+        assert_eq!(bytes.len() % 16, 0);
+        self.0 = poseidon_hash(self.0, pallas::Base::from(tag as u64));
+        for limb in bytes.chunks(16) {
+            let limb = u128::from_le_bytes(limb.try_into().unwrap());
+            self.0 = poseidon_hash(self.0, pallas::Base::from_u128(limb));
+        }
+    }
+
+    fn squeeze(&mut self) -> [u8; 64] {
+        self.0 = poseidon_hash(self.0, pallas::Base::from(PREFIX_CHALLENGE as u64));
+        let mut result = [0u8; 64];
+        result[..32].copy_from_slice(&self.0.to_repr());
+        result[32..].copy_from_slice(&poseidon_hash(self.0, pallas::Base::one()).to_repr());
+        result
+    }
+}
+
+fn absorb_point<C: CurveAffine, H: TranscriptState>(state: &mut H, point: C) -> io::Result<()> {
+    let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            "cannot write points at infinity to the transcript",
+        )
+    })?;
+    let mut bytes = coords.x().to_repr().as_ref().to_vec();
+    bytes.extend_from_slice(coords.y().to_repr().as_ref());
+    state.absorb(PREFIX_POINT, &bytes);
+    Ok(())
+}
+
+fn absorb_scalar<C: CurveAffine, H: TranscriptState>(state: &mut H, scalar: C::Scalar) {
+    state.absorb(PREFIX_SCALAR, scalar.to_repr().as_ref());
+}
+
+/// A transcript reading a proof, with the transcript hash H.
+#[derive(Debug, Clone)]
+pub struct HashTranscriptRead<R: Read, C: CurveAffine, H: TranscriptState> {
+    state: H,
+    reader: R,
+    _marker: PhantomData<C>,
+}
+
+impl<R: Read, C: CurveAffine, H: TranscriptState> HashTranscriptRead<R, C, H> {
+    pub fn init(reader: R) -> Self {
+        Self {
+            state: H::init(),
+            reader,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Read, C: CurveAffine, H: TranscriptState> Transcript<C, Challenge255<C>>
+    for HashTranscriptRead<R, C, H>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        Challenge255::<C>::new(&self.state.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        absorb_point(&mut self.state, point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        absorb_scalar::<C, H>(&mut self.state, scalar);
+        Ok(())
+    }
+}
+
+impl<R: Read, C: CurveAffine, H: TranscriptState> TranscriptRead<C, Challenge255<C>>
+    for HashTranscriptRead<R, C, H>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let mut compressed = C::Repr::default();
+        self.reader.read_exact(compressed.as_mut())?;
+        let point: C = Option::from(C::from_bytes(&compressed)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+        })?;
+        self.common_point(point)?;
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let mut data = <C::Scalar as PrimeField>::Repr::default();
+        self.reader.read_exact(data.as_mut())?;
+        let scalar: C::Scalar = Option::from(C::Scalar::from_repr(data)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "invalid field element encoding in proof",
+            )
+        })?;
+        self.common_scalar(scalar)?;
+        Ok(scalar)
+    }
+}
+
+/// A transcript writing a proof, with the transcript hash H.
+#[derive(Debug, Clone)]
+pub struct HashTranscriptWrite<W: Write, C: CurveAffine, H: TranscriptState> {
+    state: H,
+    writer: W,
+    _marker: PhantomData<C>,
+}
+
+impl<W: Write, C: CurveAffine, H: TranscriptState> HashTranscriptWrite<W, C, H> {
+    pub fn init(writer: W) -> Self {
+        Self {
+            state: H::init(),
+            writer,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn finalize(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write, C: CurveAffine, H: TranscriptState> Transcript<C, Challenge255<C>>
+    for HashTranscriptWrite<W, C, H>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+        Challenge255::<C>::new(&self.state.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        absorb_point(&mut self.state, point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        absorb_scalar::<C, H>(&mut self.state, scalar);
+        Ok(())
+    }
+}
+
+impl<W: Write, C: CurveAffine, H: TranscriptState> TranscriptWrite<C, Challenge255<C>>
+    for HashTranscriptWrite<W, C, H>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        let compressed = point.to_bytes();
+        self.writer.write_all(compressed.as_ref())
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        let data = scalar.to_repr();
+        self.writer.write_all(data.as_ref())
+    }
+}
+
+pub type Blake2sRead<R, C> = HashTranscriptRead<R, C, Blake2sTranscriptState>;
+pub type Blake2sWrite<W, C> = HashTranscriptWrite<W, C, Blake2sTranscriptState>;
+pub type PoseidonRead<R, C> = HashTranscriptRead<R, C, PoseidonTranscriptState>;
+pub type PoseidonWrite<W, C> = HashTranscriptWrite<W, C, PoseidonTranscriptState>;
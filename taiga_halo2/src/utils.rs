@@ -15,6 +15,20 @@ pub(crate) fn mod_r_p(x: pallas::Base) -> pallas::Scalar {
     pallas::Scalar::from_repr(x.to_repr()).unwrap()
 }
 
+/// Returns the low 64 bits of a field element, which is its value once it is range checked to 64
+/// bits.
+pub(crate) fn to_u64(x: &pallas::Base) -> u64 {
+    u64::from_le_bytes(x.to_repr()[..8].try_into().unwrap())
+}
+
+/// Overwrites a secret field element with zero. The volatile write can't be optimized out even
+/// though the element is never read again.
+pub(crate) fn zeroize_base(x: &mut pallas::Base) {
+    // Safety: x is a valid and aligned reference
+    unsafe { std::ptr::write_volatile(x, pallas::Base::zero()) };
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
 /// Coordinate extractor for Pallas.
 ///
 /// Defined in [Zcash Protocol Spec § 5.4.9.7: Coordinate Extractor for Pallas][concreteextractorpallas].
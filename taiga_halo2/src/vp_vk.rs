@@ -72,6 +72,20 @@ impl PartialEq for ValidityPredicateVerifyingKey {
 
 impl Eq for ValidityPredicateVerifyingKey {}
 
+/// The fingerprint of a verifying key, the blake2b hash of its canonical encoding. A redeployed VP
+/// has the same fingerprint as long as it compiles to the same circuit on the same params.
+pub fn vk_fingerprint(vk: &VerifyingKey<vesta::Affine>) -> [u8; 32] {
+    let mut bytes = vec![];
+    vk.write(&mut bytes)
+        .expect("writing a vk to memory should not fail");
+    let mut hasher = Blake2bParams::new()
+        .hash_length(32)
+        .personal(b"Taiga-VK-Fingerp")
+        .to_state();
+    hasher.update(&bytes);
+    hasher.finalize().as_bytes().try_into().unwrap()
+}
+
 #[test]
 fn test_vpd_hashing() {
     use crate::circuit::vp_examples::tests::random_trivial_vp_circuit;